@@ -27,7 +27,7 @@ impl GlslIntrinsicParser {
                 Some("https://github.com/KhronosGroup/GLSL/blob/main/extensions/ext/GLSL_EXT_mesh_shader.txt".into()))
             ),
             requirement: Some(RequirementParameter::Glsl(GlslRequirementParameter {
-                extension: Some("GLSL_EXT_mesh_shader".into()),
+                extension: Some("GL_EXT_mesh_shader".into()),
                 stages: Some(ShaderStageMask::TASK),
                 ..Default::default()
             })),
@@ -68,7 +68,7 @@ impl GlslIntrinsicParser {
                 Some("https://github.com/KhronosGroup/GLSL/blob/main/extensions/ext/GLSL_EXT_mesh_shader.txt".into()))
             ),
             requirement: Some(RequirementParameter::Glsl(GlslRequirementParameter {
-                extension: Some("GLSL_EXT_mesh_shader".into()),
+                extension: Some("GL_EXT_mesh_shader".into()),
                 stages: Some(ShaderStageMask::MESH),
                 ..Default::default()
             })),
@@ -104,7 +104,7 @@ impl GlslIntrinsicParser {
                 Some("https://github.com/KhronosGroup/GLSL/blob/main/extensions/ext/GLSL_EXT_mesh_shader.txt".into()))
             ),
             requirement: Some(RequirementParameter::Glsl(GlslRequirementParameter {
-                extension: Some("GLSL_EXT_mesh_shader".into()),
+                extension: Some("GL_EXT_mesh_shader".into()),
                 stages: Some(ShaderStageMask::TASK | ShaderStageMask::MESH),
                 ..Default::default()
             })),
@@ -162,7 +162,7 @@ impl GlslIntrinsicParser {
                 Some("https://github.com/KhronosGroup/GLSL/blob/main/extensions/ext/GLSL_EXT_mesh_shader.txt".into()))
             ),
             requirement: Some(RequirementParameter::Glsl(GlslRequirementParameter {
-                extension: Some("GLSL_EXT_mesh_shader".into()),
+                extension: Some("GL_EXT_mesh_shader".into()),
                 stages: Some(ShaderStageMask::TASK | ShaderStageMask::MESH),
                 ..Default::default()
             })),
@@ -179,7 +179,7 @@ impl GlslIntrinsicParser {
                 Some("https://github.com/KhronosGroup/GLSL/blob/main/extensions/ext/GLSL_EXT_mesh_shader.txt".into()))
             ),
             requirement: Some(RequirementParameter::Glsl(GlslRequirementParameter {
-                extension: Some("GLSL_EXT_mesh_shader".into()),
+                extension: Some("GL_EXT_mesh_shader".into()),
                 stages: Some(ShaderStageMask::TASK | ShaderStageMask::MESH),
                 ..Default::default()
             })),
@@ -247,7 +247,7 @@ impl GlslIntrinsicParser {
                 Some("https://github.com/KhronosGroup/GLSL/blob/main/extensions/ext/GLSL_EXT_mesh_shader.txt".into()))
             ),
             requirement: Some(RequirementParameter::Glsl(GlslRequirementParameter {
-                extension: Some("GLSL_EXT_mesh_shader".into()),
+                extension: Some("GL_EXT_mesh_shader".into()),
                 stages: Some(ShaderStageMask::TASK | ShaderStageMask::MESH),
                 ..Default::default()
             })),
@@ -264,7 +264,7 @@ impl GlslIntrinsicParser {
                 Some("https://github.com/KhronosGroup/GLSL/blob/main/extensions/ext/GLSL_EXT_mesh_shader.txt".into()))
             ),
             requirement: Some(RequirementParameter::Glsl(GlslRequirementParameter {
-                extension: Some("GLSL_EXT_mesh_shader".into()),
+                extension: Some("GL_EXT_mesh_shader".into()),
                 stages: Some(ShaderStageMask::TASK | ShaderStageMask::MESH),
                 ..Default::default()
             })),
@@ -280,7 +280,7 @@ impl GlslIntrinsicParser {
                 Some("https://github.com/KhronosGroup/GLSL/blob/main/extensions/ext/GLSL_EXT_mesh_shader.txt".into()))
             ),
             requirement: Some(RequirementParameter::Glsl(GlslRequirementParameter {
-                extension: Some("GLSL_EXT_mesh_shader".into()),
+                extension: Some("GL_EXT_mesh_shader".into()),
                 stages: Some(ShaderStageMask::TASK | ShaderStageMask::MESH),
                 ..Default::default()
             })),
@@ -296,7 +296,7 @@ impl GlslIntrinsicParser {
                 Some("https://github.com/KhronosGroup/GLSL/blob/main/extensions/ext/GLSL_EXT_mesh_shader.txt".into()))
             ),
             requirement: Some(RequirementParameter::Glsl(GlslRequirementParameter {
-                extension: Some("GLSL_EXT_mesh_shader".into()),
+                extension: Some("GL_EXT_mesh_shader".into()),
                 stages: Some(ShaderStageMask::TASK | ShaderStageMask::MESH),
                 ..Default::default()
             })),
@@ -19,6 +19,9 @@ impl GlslIntrinsicParser {
         let paths = std::fs::read_dir(cache_path).expect("Failed to read dir");
         for path_dir in paths {
             let path = path_dir.expect("Failed to parse path").path();
+            if path.is_dir() {
+                continue; // ES refpages, read by add_es_versions.
+            }
             println!("Reading {}", path.display());
 
             let mut link_symbol = Vec::new();
@@ -205,108 +208,16 @@ impl GlslIntrinsicParser {
                         }
                     }
                     "versions" => {
-                        let version_nodes = get_childs(
-                            refs.get_child("informaltable")
-                                .unwrap()
-                                .get_child("tgroup")
-                                .unwrap()
-                                .get_child("tbody")
-                                .unwrap(),
-                            "row",
-                        );
-                        for version_node in version_nodes {
-                            // H@CKER
-                            let mut version_key =
-                                match version_node.get_child("entry").unwrap().get_text() {
-                                    Some(value) => value.to_string(),
-                                    None => version_node
-                                        .get_child("entry")
-                                        .unwrap()
-                                        .get_child("varname")
-                                        .unwrap()
-                                        .get_text()
-                                        .unwrap()
-                                        .to_string(),
-                                };
-                            // Fix doc typos
-                            version_key = match version_key.as_str() {
-                                "bitfieldInsert" => {
-                                    if filename == "bitfieldExtract.xml" {
-                                        "bitfieldExtract".to_string()
-                                    } else {
-                                        version_key
-                                    }
+                        for (key, glsl_version) in get_versions(&refs, &filename) {
+                            let mut found = false;
+                            for symbol in &mut link_symbol {
+                                if get_version_label(&symbol.label) == key {
+                                    found = true;
+                                    set_min_version(symbol, glsl_version);
                                 }
-                                "floatBitsToUInt" => "floatBitsToUint".to_string(),
-                                "interpolateAtoOffset" => "interpolateAtOffset".to_string(),
-                                _ => version_key,
-                            };
-                            // TODO: handle {} correctly and , aswell
-                            // TODO: handle variant version aswell...
-                            let reg = Regex::new("\\(([a-zA-Z0-9\\s\\,\\{\\}]*)\\)")
-                                .expect("failed to create regex");
-
-                            let variants = reg
-                                .captures_iter(&version_key)
-                                .map(|f| f.get(1).unwrap().as_str().to_string())
-                                .collect::<Vec<String>>();
-
-                            for variant in &variants {
-                                version_key =
-                                    version_key.replace(format!("({})", variant).as_str(), "");
                             }
-                            let keys: Vec<String> = version_key
-                                .replace(",", "")
-                                .split_whitespace()
-                                .map(|w| String::from(w))
-                                .collect();
-
-                            let version_tag = version_node.get_child("include").unwrap();
-                            let regex = Regex::new(r"@role='(\d+)'")
-                                .expect("failed to create regex for version");
-                            let version_parsed =
-                                regex.captures(&version_tag.attributes["xpointer"]).unwrap();
-                            let glsl_version = version_parsed
-                                .get(1)
-                                .map(|e| e.as_str())
-                                .unwrap()
-                                .parse::<u32>()
-                                .unwrap()
-                                * 10;
-                            for key in keys {
-                                let mut found = false;
-                                for symbol in &mut link_symbol {
-                                    let compare_symbol = symbol
-                                        .label
-                                        .replace("[]", "")
-                                        .replace("[2]", "")
-                                        .replace("[4]", "");
-                                    if compare_symbol == key {
-                                        found = true;
-                                        if let Some(requirement) = symbol.requirement.as_mut() {
-                                            if let RequirementParameter::Glsl(requirement) =
-                                                requirement
-                                            {
-                                                requirement.min_version = Some(glsl_version)
-                                            } else {
-                                                panic!("Requirement are not Glsl...");
-                                            }
-                                        } else {
-                                            symbol.requirement = Some(RequirementParameter::Glsl(
-                                                GlslRequirementParameter {
-                                                    min_version: Some(glsl_version),
-                                                    ..Default::default()
-                                                },
-                                            ));
-                                        }
-                                    }
-                                }
-                                if !found {
-                                    panic!(
-                                        "Could not set version for {:?} :\n{:#?}",
-                                        key, link_symbol
-                                    )
-                                }
+                            if !found {
+                                panic!("Could not set version for {:?} :\n{:#?}", key, link_symbol)
                             }
                         }
                     }
@@ -316,7 +227,6 @@ impl GlslIntrinsicParser {
             // TODO: clean genType / genDType / genIType / genUType / genBType / mat / dmat -> type, vec2, vec3, vec4
             // TODO: parse description latex aswell...
             // TODO: get parameters description
-            // TODO: get variant version correctly
             // TODO: retrieve vk extensions (might need to be manual...).
             // TODO: rgba32f types from imageLoad
 
@@ -332,4 +242,150 @@ impl GlslIntrinsicParser {
             }
         }
     }
+    pub fn add_es_versions(&self, symbols: &mut ShaderSymbolList, es_cache_path: &str) {
+        let paths = std::fs::read_dir(es_cache_path).expect("Failed to read dir");
+        for path_dir in paths {
+            let path = path_dir.expect("Failed to parse path").path();
+            println!("Reading ES versions from {}", path.display());
+            let filename = path
+                .file_name()
+                .expect("Invalid filename")
+                .to_string_lossy()
+                .to_string();
+            let resp = std::fs::read_to_string(path).expect("Failed to read cached file");
+            let elements = Element::parse(resp.as_bytes()).expect("Failed to parse xml");
+            for refs in get_childs(&elements, "refsect1") {
+                if refs.attributes.get("id").map(|id| id.as_str()) != Some("versions") {
+                    continue;
+                }
+                for (key, es_version) in get_versions(&refs, &filename) {
+                    // Symbols not in desktop refpages are ES only, they are not handled.
+                    for symbol in symbols
+                        .functions
+                        .iter_mut()
+                        .chain(symbols.variables.iter_mut())
+                        .chain(symbols.constants.iter_mut())
+                    {
+                        if get_version_label(&symbol.label) == key {
+                            set_min_es_version(symbol, es_version);
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+// Label of a symbol as it appears in version table, without its array size.
+fn get_version_label(label: &str) -> String {
+    label
+        .replace("[]", "")
+        .replace("[2]", "")
+        .replace("[4]", "")
+}
+
+// Get the version table of a refpage, as labels with the version they are available from.
+// Each variant (genType, genDType...) of a symbol has its own row, so a label can appear multiple times.
+fn get_versions(refs: &Element, filename: &str) -> Vec<(String, u32)> {
+    let version_nodes = get_childs(
+        refs.get_child("informaltable")
+            .unwrap()
+            .get_child("tgroup")
+            .unwrap()
+            .get_child("tbody")
+            .unwrap(),
+        "row",
+    );
+    let mut versions = Vec::new();
+    for version_node in version_nodes {
+        // H@CKER
+        let mut version_key = match version_node.get_child("entry").unwrap().get_text() {
+            Some(value) => value.to_string(),
+            None => version_node
+                .get_child("entry")
+                .unwrap()
+                .get_child("varname")
+                .unwrap()
+                .get_text()
+                .unwrap()
+                .to_string(),
+        };
+        // Fix doc typos
+        version_key = match version_key.as_str() {
+            "bitfieldInsert" => {
+                if filename == "bitfieldExtract.xml" {
+                    "bitfieldExtract".to_string()
+                } else {
+                    version_key
+                }
+            }
+            "floatBitsToUInt" => "floatBitsToUint".to_string(),
+            "interpolateAtoOffset" => "interpolateAtOffset".to_string(),
+            _ => version_key,
+        };
+        // TODO: handle {} correctly and , aswell
+        let reg = Regex::new("\\(([a-zA-Z0-9\\s\\,\\{\\}]*)\\)").expect("failed to create regex");
+
+        let variants = reg
+            .captures_iter(&version_key)
+            .map(|f| f.get(1).unwrap().as_str().to_string())
+            .collect::<Vec<String>>();
+
+        for variant in &variants {
+            version_key = version_key.replace(format!("({})", variant).as_str(), "");
+        }
+        let version_tag = version_node.get_child("include").unwrap();
+        // Desktop roles are versions (40 for 4.00), ES ones might be prefixed (es30 for 3.00).
+        let regex = Regex::new(r"@role='[a-z]*(\d+)'").expect("failed to create regex for version");
+        let version_parsed = regex.captures(&version_tag.attributes["xpointer"]).unwrap();
+        let glsl_version = version_parsed
+            .get(1)
+            .map(|e| e.as_str())
+            .unwrap()
+            .parse::<u32>()
+            .unwrap()
+            * 10;
+        for key in version_key.replace(",", "").split_whitespace() {
+            versions.push((String::from(key), glsl_version));
+        }
+    }
+    versions
+}
+
+// Keep the oldest version of the symbol variants, as symbols are filtered as a whole.
+fn set_min_version(symbol: &mut ShaderSymbol, glsl_version: u32) {
+    match symbol.requirement.as_mut() {
+        Some(RequirementParameter::Glsl(requirement)) => {
+            requirement.min_version = Some(match requirement.min_version {
+                Some(min_version) => min_version.min(glsl_version),
+                None => glsl_version,
+            });
+        }
+        Some(_) => panic!("Requirement are not Glsl..."),
+        None => {
+            symbol.requirement = Some(RequirementParameter::Glsl(GlslRequirementParameter {
+                min_version: Some(glsl_version),
+                ..Default::default()
+            }));
+        }
+    }
+}
+
+// Keep the oldest ES version of the symbol variants.
+fn set_min_es_version(symbol: &mut ShaderSymbol, es_version: u32) {
+    match symbol.requirement.as_mut() {
+        Some(RequirementParameter::Glsl(requirement)) => {
+            requirement.min_es_version = Some(match requirement.min_es_version {
+                Some(min_es_version) => min_es_version.min(es_version),
+                None => es_version,
+            });
+        }
+        Some(_) => panic!("Requirement are not Glsl..."),
+        None => {
+            symbol.requirement = Some(RequirementParameter::Glsl(GlslRequirementParameter {
+                min_es_version: Some(es_version),
+                ..Default::default()
+            }));
+        }
+    }
 }
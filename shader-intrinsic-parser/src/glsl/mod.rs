@@ -82,39 +82,58 @@ pub fn merge_text(element: &xmltree::Element) -> String {
         full_text
     }
 }
+
+// Cache glsl refpages of the given api (gl4, es3) in cache_path.
+fn cache_refpages(api: &str, cache_path: &str) {
+    let unique_links = get_links(
+        format!(
+            "https://registry.khronos.org/OpenGL-Refpages/{}/html/indexflat.php",
+            api
+        )
+        .as_str(),
+    );
+    std::fs::create_dir_all(cache_path).expect("Failed to create dir.");
+    for link in unique_links {
+        let filename = link.replace("xhtml", "xml");
+        if filename == "removedTypes.xml" {
+            continue; // Unvalid file.
+        }
+        let url = format!(
+            "https://registry.khronos.org/OpenGL-Refpages/{}/{}",
+            api, filename
+        );
+        println!("Caching file from {} to {}{}", url, cache_path, filename);
+        let mut asset = download_file(url.as_str());
+        // Somehow these characters fail parsing
+        asset = asset.replace("&plus;", "+");
+        asset = asset.replace("&minus;", "-");
+        asset = asset.replace("&dot;", ".");
+        asset = asset.replace("&sdot;", "•");
+        asset = asset.replace("&ge;", "&gt;="); // These dont seems to work fine... So hack
+        asset = asset.replace("&le;", "&lt;="); // These dont seems to work fine... So hack
+        asset = asset.replace("&af;", "()");
+        asset = asset.replace("&delta;", "Δ");
+        asset = asset.replace("&nbsp;", "");
+        asset = asset.replace("&lambda;", "Λ");
+        std::fs::write(format!("{}{}", cache_path, filename).as_str(), asset)
+            .expect("Failed to write file");
+    }
+}
+
 pub struct GlslIntrinsicParser {}
 
+impl GlslIntrinsicParser {
+    // ES refpages are only used for their versions, stored aside desktop ones.
+    fn get_es_cache_path(cache_path: &str) -> String {
+        format!("{}es3/", cache_path)
+    }
+}
+
 impl IntrinsicParser for GlslIntrinsicParser {
     fn cache(&self, cache_path: &str) {
-        // TODO: dont parse only gl4, missing gl2.1, es3 (3.2) / es3.1 / es3.0 / es2.0 / es1.1
-        let unique_links =
-            get_links("https://registry.khronos.org/OpenGL-Refpages/gl4/html/indexflat.php");
-        std::fs::create_dir_all(cache_path).expect("Failed to create dir.");
-        for link in unique_links {
-            let filename = link.replace("xhtml", "xml");
-            if filename == "removedTypes.xml" {
-                continue; // Unvalid file.
-            }
-            let url = format!(
-                "https://registry.khronos.org/OpenGL-Refpages/gl4/{}",
-                filename
-            );
-            println!("Caching file from {} to {}{}", url, cache_path, filename);
-            let mut asset = download_file(url.as_str());
-            // Somehow these characters fail parsing
-            asset = asset.replace("&plus;", "+");
-            asset = asset.replace("&minus;", "-");
-            asset = asset.replace("&dot;", ".");
-            asset = asset.replace("&sdot;", "•");
-            asset = asset.replace("&ge;", "&gt;="); // These dont seems to work fine... So hack
-            asset = asset.replace("&le;", "&lt;="); // These dont seems to work fine... So hack
-            asset = asset.replace("&af;", "()");
-            asset = asset.replace("&delta;", "Δ");
-            asset = asset.replace("&nbsp;", "");
-            asset = asset.replace("&lambda;", "Λ");
-            std::fs::write(format!("{}{}", cache_path, filename).as_str(), asset)
-                .expect("Failed to write file");
-        }
+        // TODO: dont parse only gl4 & es3, missing gl2.1, es2.0 / es1.1
+        cache_refpages("gl4", cache_path);
+        cache_refpages("es3", &Self::get_es_cache_path(cache_path));
     }
     fn parse(&self, cache_path: &str) -> ShaderSymbolList {
         let mut symbols = ShaderSymbolList {
@@ -130,6 +149,7 @@ impl IntrinsicParser for GlslIntrinsicParser {
         };
 
         self.add_methods(&mut symbols, cache_path);
+        self.add_es_versions(&mut symbols, &Self::get_es_cache_path(cache_path));
         self.add_types(&mut symbols);
         self.add_keywords(&mut symbols);
         self.get_extensions(&mut symbols);
@@ -10,7 +10,12 @@ use super::GlslIntrinsicParser;
 
 impl GlslIntrinsicParser {
     pub fn add_types(&self, symbols: &mut ShaderSymbolList) {
-        pub fn new_glsl_type(label: &str, description: &str, min_version: u32) -> ShaderSymbol {
+        pub fn new_glsl_type(
+            label: &str,
+            description: &str,
+            min_version: u32,
+            min_es_version: Option<u32>,
+        ) -> ShaderSymbol {
             ShaderSymbol {
                 label: label.into(),
                 mode: ShaderSymbolMode::Intrinsic(ShaderSymbolIntrinsic::new(
@@ -19,6 +24,7 @@ impl GlslIntrinsicParser {
                 )),
                 requirement: Some(RequirementParameter::Glsl(GlslRequirementParameter {
                     min_version: Some(min_version),
+                    min_es_version,
                     ..Default::default()
                 })),
                 data: ShaderSymbolData::Types {
@@ -53,6 +59,7 @@ impl GlslIntrinsicParser {
             description: &str,
             ty: &str,
             min_version: u32,
+            min_es_version: Option<u32>,
             component_count: u32,
         ) -> ShaderSymbol {
             ShaderSymbol {
@@ -63,6 +70,7 @@ impl GlslIntrinsicParser {
                 )),
                 requirement: Some(RequirementParameter::Glsl(GlslRequirementParameter {
                     min_version: Some(min_version),
+                    min_es_version,
                     ..Default::default()
                 })),
                 data: ShaderSymbolData::Types {
@@ -100,6 +108,7 @@ impl GlslIntrinsicParser {
             description: &str,
             ty: &str,
             min_version: u32,
+            min_es_version: Option<u32>,
             col_count: u32,
             row_count: u32,
         ) -> ShaderSymbol {
@@ -111,6 +120,7 @@ impl GlslIntrinsicParser {
                 )),
                 requirement: Some(RequirementParameter::Glsl(GlslRequirementParameter {
                     min_version: Some(min_version),
+                    min_es_version,
                     ..Default::default()
                 })),
                 data: ShaderSymbolData::Types {
@@ -153,24 +163,31 @@ impl GlslIntrinsicParser {
             "bool",
             "conditional type, values may be either true or false",
             110,
+            Some(100),
         ));
         symbols.types.push(new_glsl_type(
             "int",
             " a signed, two's complement, 32-bit integer",
             110,
+            Some(100),
+        ));
+        symbols.types.push(new_glsl_type(
+            "uint",
+            " an unsigned 32-bit integer",
+            110,
+            Some(300),
         ));
-        symbols
-            .types
-            .push(new_glsl_type("uint", " an unsigned 32-bit integer", 110));
         symbols.types.push(new_glsl_type(
             "float",
             "an IEEE-754 single-precision floating point number",
             110,
+            Some(100),
         ));
         symbols.types.push(new_glsl_type(
             "double",
             "an IEEE-754 double-precision floating-point number",
             110,
+            None,
         ));
         for component in 2..=4 {
             // Vectors
@@ -179,6 +196,7 @@ impl GlslIntrinsicParser {
                 format!("Vector with {} components of booleans", component).as_str(),
                 "bool",
                 110,
+                Some(100),
                 component,
             ));
             symbols.types.push(new_glsl_vec_type(
@@ -186,6 +204,7 @@ impl GlslIntrinsicParser {
                 format!("Vector with {} components of signed integers", component).as_str(),
                 "int",
                 110,
+                Some(100),
                 component,
             ));
             symbols.types.push(new_glsl_vec_type(
@@ -193,6 +212,7 @@ impl GlslIntrinsicParser {
                 format!("Vector with {} components of unsigned integers", component).as_str(),
                 "uint",
                 110,
+                Some(300),
                 component,
             ));
             symbols.types.push(new_glsl_vec_type(
@@ -204,6 +224,7 @@ impl GlslIntrinsicParser {
                 .as_str(),
                 "float",
                 110,
+                Some(100),
                 component,
             ));
             symbols.types.push(new_glsl_vec_type(
@@ -215,6 +236,7 @@ impl GlslIntrinsicParser {
                 .as_str(),
                 "double",
                 110,
+                None,
                 component,
             ));
             // Matrices
@@ -227,6 +249,7 @@ impl GlslIntrinsicParser {
                 .as_str(),
                 "float",
                 110,
+                Some(100),
                 component,
                 component,
             ));
@@ -239,6 +262,7 @@ impl GlslIntrinsicParser {
                 .as_str(),
                 "double",
                 110,
+                None,
                 component,
                 component,
             ));
@@ -248,6 +272,7 @@ impl GlslIntrinsicParser {
                     format!("Matrice with {} columns and {} rows of single-precision floating-point numbers", component, component_row).as_str(),
                     "float",
                     110,
+                    Some(300),
                     component,
                     component_row
                 ));
@@ -256,60 +281,65 @@ impl GlslIntrinsicParser {
                     format!("Matrice with {} columns and {} rows of double-precision floating-point numbers", component, component_row).as_str(),
                     "double", 
                     110,
+                    None,
                     component,
                     component_row
                 ));
             }
         }
-        // Samplers
+        // Samplers, with the ES version of floating-point & integer variants.
         let sampler_types = [
-            "1D",
-            "2D",
-            "3D",
-            "Cube",
-            "2DRect",
-            "1DArray",
-            "2DArray",
-            "CubeArray",
-            "Buffer",
-            "2DMS",
-            "2DMSArray",
+            ("1D", None, None),
+            ("2D", Some(100), Some(300)),
+            ("3D", Some(300), Some(300)),
+            ("Cube", Some(100), Some(300)),
+            ("2DRect", None, None),
+            ("1DArray", None, None),
+            ("2DArray", Some(300), Some(300)),
+            ("CubeArray", Some(320), Some(320)),
+            ("Buffer", Some(320), Some(320)),
+            ("2DMS", Some(310), Some(310)),
+            ("2DMSArray", Some(320), Some(320)),
         ];
-        for sampler_type in sampler_types {
+        for (sampler_type, min_es_version, min_es_version_integer) in sampler_types {
             symbols.types.push(new_glsl_type(
                 format!("sampler{}", sampler_type).as_str(),
                 format!("Floating-point sampler for Texture{}", sampler_type).as_str(),
                 110,
+                min_es_version,
             ));
             symbols.types.push(new_glsl_type(
                 format!("isampler{}", sampler_type).as_str(),
                 format!("Signed integer sampler for Texture{}", sampler_type).as_str(),
                 110,
+                min_es_version_integer,
             ));
             symbols.types.push(new_glsl_type(
                 format!("usampler{}", sampler_type).as_str(),
                 format!("Unsigned integer sampler for Texture{}", sampler_type).as_str(),
                 110,
+                min_es_version_integer,
             ));
         }
         // Shadow Samplers
         let shadow_sampler_types = [
-            "1D",
-            "2D",
-            "Cube",
-            "2DRect",
-            "1DArray",
-            "2DArray",
-            "CubeArray",
+            ("1D", None),
+            ("2D", Some(300)),
+            ("Cube", Some(300)),
+            ("2DRect", None),
+            ("1DArray", None),
+            ("2DArray", Some(300)),
+            ("CubeArray", Some(320)),
         ];
-        for shadow_sampler_type in shadow_sampler_types {
+        for (shadow_sampler_type, min_es_version) in shadow_sampler_types {
             symbols.types.push(new_glsl_type(
                 format!("sampler{}Shadow", shadow_sampler_type).as_str(),
                 format!("Shadow sampler for Texture{}", shadow_sampler_type).as_str(),
                 110,
+                min_es_version,
             ));
         }
         // Atomic counters
-        symbols.types.push(new_glsl_type("atomic_uint", "An Atomic Counter is a GLSL variable type whose storage comes from a Buffer Object. Atomic counters, as the name suggests, can have atomic memory operations performed on them. They can be thought of as a very limited form of buffer image variable.", 460));
+        symbols.types.push(new_glsl_type("atomic_uint", "An Atomic Counter is a GLSL variable type whose storage comes from a Buffer Object. Atomic counters, as the name suggests, can have atomic memory operations performed on them. They can be thought of as a very limited form of buffer image variable.", 460, Some(310)));
     }
 }
@@ -53,6 +53,7 @@ impl HlslIntrinsicParser {
                 requirement: None,
                 data: ShaderSymbolData::Macro {
                     value: value.into(),
+                    parameters: None,
                 },
             });
         }
@@ -71,6 +72,7 @@ impl HlslIntrinsicParser {
                 requirement: Some(RequirementParameter::Hlsl(req)),
                 data: ShaderSymbolData::Macro {
                     value: value.into(),
+                    parameters: None,
                 },
             });
         }
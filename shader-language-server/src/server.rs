@@ -17,9 +17,10 @@ pub mod server_config; // pub for test.
 mod server_connection;
 mod server_file_cache;
 mod server_language_data;
+mod validator_info;
 
 use crossbeam_channel::RecvTimeoutError;
-use debug::{DumpAstRequest, DumpDependencyRequest};
+use debug::{DumpAstRequest, DumpDependencyRequest, ExpandMacroRequest, ExpandMacroResult};
 use log::{debug, error, info, warn};
 use lru::LruCache;
 use lsp_types::notification::{
@@ -28,17 +29,19 @@ use lsp_types::notification::{
     Progress, SetTrace,
 };
 use lsp_types::request::{
-    Completion, DocumentDiagnosticRequest, DocumentSymbolRequest, FoldingRangeRequest, Formatting,
-    GotoDefinition, HoverRequest, InlayHintRequest, RangeFormatting, Request,
-    SemanticTokensFullRequest, SignatureHelpRequest, WorkDoneProgressCreate,
-    WorkspaceSymbolRequest,
+    CallHierarchyIncomingCalls, CallHierarchyOutgoingCalls, CallHierarchyPrepare,
+    CodeActionRequest, Completion, DocumentDiagnosticRequest, DocumentSymbolRequest,
+    FoldingRangeRequest, Formatting, GotoDefinition, HoverRequest, InlayHintRequest,
+    PrepareRenameRequest, RangeFormatting, References, Rename, Request, SemanticTokensFullRequest,
+    SignatureHelpRequest, WorkDoneProgressCreate, WorkspaceSymbolRequest,
 };
 use lsp_types::{
-    CancelParams, CompletionOptionsCompletionItem, CompletionResponse,
-    DidChangeConfigurationParams, DidChangeTextDocumentParams, DidChangeWorkspaceFoldersParams,
-    DidCloseTextDocumentParams, DidOpenTextDocumentParams, DidSaveTextDocumentParams,
-    DocumentSymbolOptions, DocumentSymbolResponse, FoldingRangeProviderCapability,
-    HoverProviderCapability, OneOf, ProgressParams, SemanticTokenType, SemanticTokensFullOptions,
+    CallHierarchyServerCapability, CancelParams, CodeActionProviderCapability,
+    CompletionOptionsCompletionItem, CompletionResponse, DidChangeConfigurationParams,
+    DidChangeTextDocumentParams, DidChangeWorkspaceFoldersParams, DidCloseTextDocumentParams,
+    DidOpenTextDocumentParams, DidSaveTextDocumentParams, DocumentSymbolOptions,
+    DocumentSymbolResponse, FoldingRangeProviderCapability, HoverProviderCapability, OneOf,
+    ProgressParams, RenameOptions, SemanticTokenType, SemanticTokensFullOptions,
     SemanticTokensLegend, SemanticTokensOptions, SemanticTokensServerCapabilities,
     ServerCapabilities, SetTraceParams, SignatureHelpOptions, TextDocumentSyncKind, Url,
     WorkDoneProgress, WorkDoneProgressBegin, WorkDoneProgressCreateParams, WorkDoneProgressEnd,
@@ -54,10 +57,13 @@ use server_connection::ServerConnection;
 use server_file_cache::ServerLanguageFileCache;
 use server_language_data::ServerLanguageData;
 use shader_variant::DidChangeShaderVariant;
+use validator_info::ValidatorInfoRequest;
 
 use crate::profile_scope;
 use crate::server::async_message::{AsyncCacheRequest, AsyncMessage, AsyncRequest};
-use crate::server::common::{lsp_range_to_shader_range, ServerLanguageError};
+use crate::server::common::{
+    lsp_range_to_shader_range, shader_range_to_lsp_range, ServerLanguageError,
+};
 use crate::server::server_config::{ServerTrace, ServerTraceLevel};
 use crate::server::server_file_cache::ServerFileCache;
 
@@ -171,6 +177,14 @@ impl ServerLanguage {
             type_definition_provider: Some(lsp_types::TypeDefinitionProviderCapability::Simple(
                 false, // Disable as definition_provider is doing it.
             )),
+            references_provider: Some(OneOf::Left(true)),
+            rename_provider: Some(OneOf::Right(RenameOptions {
+                prepare_provider: Some(true),
+                work_done_progress_options: WorkDoneProgressOptions {
+                    work_done_progress: None,
+                },
+            })),
+            call_hierarchy_provider: Some(CallHierarchyServerCapability::Simple(true)),
             folding_range_provider: Some(FoldingRangeProviderCapability::Simple(true)),
             document_symbol_provider: Some(OneOf::Right(DocumentSymbolOptions {
                 label: Some("shader-validator".into()),
@@ -185,6 +199,7 @@ impl ServerLanguage {
                 },
             })),
             inlay_hint_provider: Some(OneOf::Left(true)),
+            code_action_provider: Some(CodeActionProviderCapability::Simple(true)),
             semantic_tokens_provider: Some(
                 SemanticTokensServerCapabilities::SemanticTokensOptions(SemanticTokensOptions {
                     work_done_progress_options: WorkDoneProgressOptions {
@@ -227,6 +242,8 @@ impl ServerLanguage {
         }
         // TODO: Check features support from client params.
         debug!("Received client params");
+        // Let user know if validation is degraded.
+        self.notify_validator_fallback();
         // Request configuration as its not sent automatically (at least with vscode)
         self.request_configuration();
 
@@ -334,6 +351,21 @@ impl ServerLanguage {
                     Some(inlay_hints),
                 );
             }
+            AsyncMessage::CodeActionRequest(async_request) => {
+                profile_scope!(
+                    "Received code action request for file {}: {}",
+                    async_request.params.text_document.uri,
+                    self.debug(&async_request.params)
+                );
+                let code_actions = self.recolt_code_action(
+                    &async_request.params.text_document.uri,
+                    &async_request.params.range,
+                )?;
+                self.connection.send_response::<CodeActionRequest>(
+                    async_request.req_id.clone(),
+                    Some(code_actions),
+                );
+            }
             AsyncMessage::HoverRequest(async_request) => {
                 profile_scope!(
                     "Received hover request for file {}: {}",
@@ -426,6 +458,108 @@ impl ServerLanguage {
                 self.connection
                     .send_response::<GotoDefinition>(async_request.req_id.clone(), value);
             }
+            AsyncMessage::References(async_request) => {
+                profile_scope!(
+                    "Received references request for file {}: {}",
+                    async_request
+                        .params
+                        .text_document_position
+                        .text_document
+                        .uri,
+                    self.debug(&async_request.params)
+                );
+                let value = self.recolt_references(
+                    &async_request
+                        .params
+                        .text_document_position
+                        .text_document
+                        .uri,
+                    async_request.params.text_document_position.position,
+                    async_request.params.context.include_declaration,
+                )?;
+                self.connection
+                    .send_response::<References>(async_request.req_id.clone(), value);
+            }
+            AsyncMessage::PrepareRenameRequest(async_request) => {
+                profile_scope!(
+                    "Received prepare rename request for file {}: {}",
+                    async_request.params.text_document.uri,
+                    self.debug(&async_request.params)
+                );
+                let value = self.recolt_prepare_rename(
+                    &async_request.params.text_document.uri,
+                    async_request.params.position,
+                )?;
+                self.connection
+                    .send_response::<PrepareRenameRequest>(async_request.req_id.clone(), value);
+            }
+            AsyncMessage::Rename(async_request) => {
+                profile_scope!(
+                    "Received rename request for file {}: {}",
+                    async_request
+                        .params
+                        .text_document_position
+                        .text_document
+                        .uri,
+                    self.debug(&async_request.params)
+                );
+                let value = self.recolt_rename(
+                    &async_request
+                        .params
+                        .text_document_position
+                        .text_document
+                        .uri,
+                    async_request.params.text_document_position.position,
+                    &async_request.params.new_name,
+                )?;
+                self.connection
+                    .send_response::<Rename>(async_request.req_id.clone(), value);
+            }
+            AsyncMessage::CallHierarchyPrepare(async_request) => {
+                profile_scope!(
+                    "Received call hierarchy prepare request for file {}: {}",
+                    async_request
+                        .params
+                        .text_document_position_params
+                        .text_document
+                        .uri,
+                    self.debug(&async_request.params)
+                );
+                let value = self.recolt_prepare_call_hierarchy(
+                    &async_request
+                        .params
+                        .text_document_position_params
+                        .text_document
+                        .uri,
+                    async_request.params.text_document_position_params.position,
+                )?;
+                self.connection
+                    .send_response::<CallHierarchyPrepare>(async_request.req_id.clone(), value);
+            }
+            AsyncMessage::CallHierarchyIncomingCalls(async_request) => {
+                profile_scope!(
+                    "Received incoming calls request for file {}: {}",
+                    async_request.params.item.uri,
+                    self.debug(&async_request.params)
+                );
+                let value = self.recolt_incoming_calls(&async_request.params.item)?;
+                self.connection.send_response::<CallHierarchyIncomingCalls>(
+                    async_request.req_id.clone(),
+                    value,
+                );
+            }
+            AsyncMessage::CallHierarchyOutgoingCalls(async_request) => {
+                profile_scope!(
+                    "Received outgoing calls request for file {}: {}",
+                    async_request.params.item.uri,
+                    self.debug(&async_request.params)
+                );
+                let value = self.recolt_outgoing_calls(&async_request.params.item)?;
+                self.connection.send_response::<CallHierarchyOutgoingCalls>(
+                    async_request.req_id.clone(),
+                    value,
+                );
+            }
             AsyncMessage::DocumentDiagnosticRequest(async_request) => {
                 profile_scope!(
                     "Received document diagnostic request for file {}: {}",
@@ -439,6 +573,19 @@ impl ServerLanguage {
                     document_diagnostic,
                 );
             }
+            AsyncMessage::ValidatorInfoRequest(async_request) => {
+                profile_scope!(
+                    "Received validator info request for file {}: {}",
+                    async_request.params.text_document.uri,
+                    self.debug(&async_request.params)
+                );
+                let validator_info =
+                    self.recolt_validator_info(&async_request.params.text_document.uri)?;
+                self.connection.send_response::<ValidatorInfoRequest>(
+                    async_request.req_id.clone(),
+                    validator_info,
+                );
+            }
             AsyncMessage::SemanticTokensFullRequest(async_request) => {
                 profile_scope!(
                     "Received semantic token request for file {}: {}",
@@ -490,6 +637,33 @@ impl ServerLanguage {
                 self.connection
                     .send_response::<DumpAstRequest>(async_request.req_id.clone(), Some(ast));
             }
+            AsyncMessage::ExpandMacroRequest(async_request) => {
+                profile_scope!(
+                    "Received expand macro request for file {}: {}",
+                    async_request
+                        .params
+                        .text_document_position
+                        .text_document
+                        .uri,
+                    self.debug(&async_request.params)
+                );
+                let expansion = self.recolt_macro_expansion(
+                    &async_request
+                        .params
+                        .text_document_position
+                        .text_document
+                        .uri,
+                    async_request.params.text_document_position.position,
+                )?;
+                self.connection.send_response::<ExpandMacroRequest>(
+                    async_request.req_id.clone(),
+                    expansion.map(|expansion| ExpandMacroResult {
+                        name: expansion.name,
+                        range: shader_range_to_lsp_range(&expansion.range.range),
+                        expansion: expansion.expansion,
+                    }),
+                );
+            }
         }
         Ok(())
     }
@@ -763,10 +937,33 @@ impl ServerLanguage {
                 DocumentDiagnosticRequest::METHOD => AsyncMessage::DocumentDiagnosticRequest(
                     AsyncRequest::new(req.id, serde_json::from_value(req.params)?),
                 ),
+                ValidatorInfoRequest::METHOD => AsyncMessage::ValidatorInfoRequest(
+                    AsyncRequest::new(req.id, serde_json::from_value(req.params)?),
+                ),
                 GotoDefinition::METHOD => AsyncMessage::GotoDefinition(AsyncRequest::new(
                     req.id,
                     serde_json::from_value(req.params)?,
                 )),
+                References::METHOD => AsyncMessage::References(AsyncRequest::new(
+                    req.id,
+                    serde_json::from_value(req.params)?,
+                )),
+                PrepareRenameRequest::METHOD => AsyncMessage::PrepareRenameRequest(
+                    AsyncRequest::new(req.id, serde_json::from_value(req.params)?),
+                ),
+                Rename::METHOD => AsyncMessage::Rename(AsyncRequest::new(
+                    req.id,
+                    serde_json::from_value(req.params)?,
+                )),
+                CallHierarchyPrepare::METHOD => AsyncMessage::CallHierarchyPrepare(
+                    AsyncRequest::new(req.id, serde_json::from_value(req.params)?),
+                ),
+                CallHierarchyIncomingCalls::METHOD => AsyncMessage::CallHierarchyIncomingCalls(
+                    AsyncRequest::new(req.id, serde_json::from_value(req.params)?),
+                ),
+                CallHierarchyOutgoingCalls::METHOD => AsyncMessage::CallHierarchyOutgoingCalls(
+                    AsyncRequest::new(req.id, serde_json::from_value(req.params)?),
+                ),
                 Completion::METHOD => AsyncMessage::Completion(AsyncRequest::new(
                     req.id,
                     serde_json::from_value(req.params)?,
@@ -785,6 +982,10 @@ impl ServerLanguage {
                 FoldingRangeRequest::METHOD => AsyncMessage::FoldingRangeRequest(
                     AsyncRequest::new(req.id, serde_json::from_value(req.params)?),
                 ),
+                CodeActionRequest::METHOD => AsyncMessage::CodeActionRequest(AsyncRequest::new(
+                    req.id,
+                    serde_json::from_value(req.params)?,
+                )),
                 WorkspaceSymbolRequest::METHOD => AsyncMessage::WorkspaceSymbolRequest(
                     AsyncRequest::new(req.id, serde_json::from_value(req.params)?),
                 ),
@@ -810,6 +1011,10 @@ impl ServerLanguage {
                 DumpDependencyRequest::METHOD => AsyncMessage::DumpDependencyRequest(
                     AsyncRequest::new(req.id, serde_json::from_value(req.params)?),
                 ),
+                ExpandMacroRequest::METHOD => AsyncMessage::ExpandMacroRequest(AsyncRequest::new(
+                    req.id,
+                    serde_json::from_value(req.params)?,
+                )),
                 _ => {
                     warn!("Received unhandled request: {:#?}", req);
                     return Err(ServerLanguageError::MethodNotFound(req.method));
@@ -896,6 +1101,12 @@ impl ServerLanguage {
                 );
                 // File content is updated through DidChangeTextDocument.
                 let cached_file = self.get_cachable_file(&uri)?;
+                // Validators might have cached content read from disk.
+                self.language_data
+                    .get(&cached_file.shading_language)
+                    .unwrap()
+                    .validator
+                    .on_file_saved(&uri.to_file_path().unwrap());
 
                 assert!(
                     params.text.is_none()
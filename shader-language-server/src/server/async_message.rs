@@ -1,20 +1,27 @@
 use lsp_server::RequestId;
 use lsp_types::{
     request::{
-        Completion, DocumentDiagnosticRequest, DocumentSymbolRequest, FoldingRangeRequest,
-        Formatting, GotoDefinition, HoverRequest, InlayHintRequest, RangeFormatting, Request,
+        CallHierarchyIncomingCalls, CallHierarchyOutgoingCalls, CallHierarchyPrepare,
+        CodeActionRequest, Completion, DocumentDiagnosticRequest, DocumentSymbolRequest,
+        FoldingRangeRequest, Formatting, GotoDefinition, HoverRequest, InlayHintRequest,
+        PrepareRenameRequest, RangeFormatting, References, Rename, Request,
         SemanticTokensFullRequest, SignatureHelpRequest, WorkspaceSymbolRequest,
     },
-    CompletionParams, DocumentDiagnosticParams, DocumentFormattingParams,
+    CallHierarchyIncomingCallsParams, CallHierarchyOutgoingCallsParams, CallHierarchyPrepareParams,
+    CodeActionParams, CompletionParams, DocumentDiagnosticParams, DocumentFormattingParams,
     DocumentRangeFormattingParams, DocumentSymbolParams, FoldingRangeParams, GotoDefinitionParams,
-    HoverParams, InlayHintParams, SemanticTokensParams, SignatureHelpParams, Url,
-    WorkspaceSymbolParams,
+    HoverParams, InlayHintParams, ReferenceParams, RenameParams, SemanticTokensParams,
+    SignatureHelpParams, TextDocumentPositionParams, Url, WorkspaceSymbolParams,
 };
 use shader_sense::shader::ShadingLanguage;
 
 use crate::server::{
     clean_url,
-    debug::{DumpAstParams, DumpAstRequest, DumpDependencyParams, DumpDependencyRequest},
+    debug::{
+        DumpAstParams, DumpAstRequest, DumpDependencyParams, DumpDependencyRequest,
+        ExpandMacroParams, ExpandMacroRequest,
+    },
+    validator_info::{ValidatorInfoParams, ValidatorInfoRequest},
 };
 
 pub struct AsyncRequest<R: Request> {
@@ -42,14 +49,23 @@ pub enum AsyncMessage {
     SemanticTokensFullRequest(AsyncRequest<SemanticTokensFullRequest>),
     FoldingRangeRequest(AsyncRequest<FoldingRangeRequest>),
     InlayHintRequest(AsyncRequest<InlayHintRequest>),
+    CodeActionRequest(AsyncRequest<CodeActionRequest>),
     HoverRequest(AsyncRequest<HoverRequest>),
     SignatureHelpRequest(AsyncRequest<SignatureHelpRequest>),
     Completion(AsyncRequest<Completion>),
     GotoDefinition(AsyncRequest<GotoDefinition>),
+    References(AsyncRequest<References>),
+    PrepareRenameRequest(AsyncRequest<PrepareRenameRequest>),
+    Rename(AsyncRequest<Rename>),
+    CallHierarchyPrepare(AsyncRequest<CallHierarchyPrepare>),
+    CallHierarchyIncomingCalls(AsyncRequest<CallHierarchyIncomingCalls>),
+    CallHierarchyOutgoingCalls(AsyncRequest<CallHierarchyOutgoingCalls>),
     DocumentDiagnosticRequest(AsyncRequest<DocumentDiagnosticRequest>),
+    ValidatorInfoRequest(AsyncRequest<ValidatorInfoRequest>),
     // Debug
     DumpDependencyRequest(AsyncRequest<DumpDependencyRequest>),
     DumpAstRequest(AsyncRequest<DumpAstRequest>),
+    ExpandMacroRequest(AsyncRequest<ExpandMacroRequest>),
 }
 
 impl AsyncCacheRequest {
@@ -78,14 +94,23 @@ impl AsyncMessage {
             AsyncMessage::Formatting(async_request) => &async_request.req_id,
             AsyncMessage::SemanticTokensFullRequest(async_request) => &async_request.req_id,
             AsyncMessage::FoldingRangeRequest(async_request) => &async_request.req_id,
+            AsyncMessage::CodeActionRequest(async_request) => &async_request.req_id,
             AsyncMessage::InlayHintRequest(async_request) => &async_request.req_id,
             AsyncMessage::HoverRequest(async_request) => &async_request.req_id,
             AsyncMessage::SignatureHelpRequest(async_request) => &async_request.req_id,
             AsyncMessage::Completion(async_request) => &async_request.req_id,
             AsyncMessage::GotoDefinition(async_request) => &async_request.req_id,
+            AsyncMessage::References(async_request) => &async_request.req_id,
+            AsyncMessage::PrepareRenameRequest(async_request) => &async_request.req_id,
+            AsyncMessage::Rename(async_request) => &async_request.req_id,
+            AsyncMessage::CallHierarchyPrepare(async_request) => &async_request.req_id,
+            AsyncMessage::CallHierarchyIncomingCalls(async_request) => &async_request.req_id,
+            AsyncMessage::CallHierarchyOutgoingCalls(async_request) => &async_request.req_id,
             AsyncMessage::DocumentDiagnosticRequest(async_request) => &async_request.req_id,
+            AsyncMessage::ValidatorInfoRequest(async_request) => &async_request.req_id,
             AsyncMessage::DumpDependencyRequest(async_request) => &async_request.req_id,
             AsyncMessage::DumpAstRequest(async_request) => &async_request.req_id,
+            AsyncMessage::ExpandMacroRequest(async_request) => &async_request.req_id,
             // These variants do not have a RequestId
             AsyncMessage::None | AsyncMessage::UpdateCache(_) => {
                 unreachable!("Should not be reached. Update AsyncMessage::is_update accordingly.");
@@ -101,14 +126,23 @@ impl AsyncMessage {
             AsyncMessage::Formatting(_) => Formatting::METHOD,
             AsyncMessage::SemanticTokensFullRequest(_) => SemanticTokensFullRequest::METHOD,
             AsyncMessage::FoldingRangeRequest(_) => FoldingRangeRequest::METHOD,
+            AsyncMessage::CodeActionRequest(_) => CodeActionRequest::METHOD,
             AsyncMessage::InlayHintRequest(_) => InlayHintRequest::METHOD,
             AsyncMessage::HoverRequest(_) => HoverRequest::METHOD,
             AsyncMessage::SignatureHelpRequest(_) => SignatureHelpRequest::METHOD,
             AsyncMessage::Completion(_) => Completion::METHOD,
             AsyncMessage::GotoDefinition(_) => GotoDefinition::METHOD,
+            AsyncMessage::References(_) => References::METHOD,
+            AsyncMessage::PrepareRenameRequest(_) => PrepareRenameRequest::METHOD,
+            AsyncMessage::Rename(_) => Rename::METHOD,
+            AsyncMessage::CallHierarchyPrepare(_) => CallHierarchyPrepare::METHOD,
+            AsyncMessage::CallHierarchyIncomingCalls(_) => CallHierarchyIncomingCalls::METHOD,
+            AsyncMessage::CallHierarchyOutgoingCalls(_) => CallHierarchyOutgoingCalls::METHOD,
             AsyncMessage::DocumentDiagnosticRequest(_) => DocumentDiagnosticRequest::METHOD,
+            AsyncMessage::ValidatorInfoRequest(_) => ValidatorInfoRequest::METHOD,
             AsyncMessage::DumpDependencyRequest(_) => DumpDependencyRequest::METHOD,
             AsyncMessage::DumpAstRequest(_) => DumpAstRequest::METHOD,
+            AsyncMessage::ExpandMacroRequest(_) => ExpandMacroRequest::METHOD,
             // These variants do not have a method
             AsyncMessage::None | AsyncMessage::UpdateCache(_) => {
                 unreachable!("Should not be reached. Update AsyncMessage::is_update accordingly.");
@@ -132,6 +166,9 @@ impl AsyncMessage {
             AsyncMessage::FoldingRangeRequest(async_request) => {
                 Some(&async_request.params.text_document.uri)
             }
+            AsyncMessage::CodeActionRequest(async_request) => {
+                Some(&async_request.params.text_document.uri)
+            }
             AsyncMessage::InlayHintRequest(async_request) => {
                 Some(&async_request.params.text_document.uri)
             }
@@ -163,15 +200,55 @@ impl AsyncMessage {
                     .text_document
                     .uri,
             ),
+            AsyncMessage::References(async_request) => Some(
+                &async_request
+                    .params
+                    .text_document_position
+                    .text_document
+                    .uri,
+            ),
+            AsyncMessage::PrepareRenameRequest(async_request) => {
+                Some(&async_request.params.text_document.uri)
+            }
+            AsyncMessage::Rename(async_request) => Some(
+                &async_request
+                    .params
+                    .text_document_position
+                    .text_document
+                    .uri,
+            ),
+            AsyncMessage::CallHierarchyPrepare(async_request) => Some(
+                &async_request
+                    .params
+                    .text_document_position_params
+                    .text_document
+                    .uri,
+            ),
+            AsyncMessage::CallHierarchyIncomingCalls(async_request) => {
+                Some(&async_request.params.item.uri)
+            }
+            AsyncMessage::CallHierarchyOutgoingCalls(async_request) => {
+                Some(&async_request.params.item.uri)
+            }
             AsyncMessage::DocumentDiagnosticRequest(async_request) => {
                 Some(&async_request.params.text_document.uri)
             }
+            AsyncMessage::ValidatorInfoRequest(async_request) => {
+                Some(&async_request.params.text_document.uri)
+            }
             AsyncMessage::DumpDependencyRequest(async_request) => {
                 Some(&async_request.params.text_document.uri)
             }
             AsyncMessage::DumpAstRequest(async_request) => {
                 Some(&async_request.params.text_document.uri)
             }
+            AsyncMessage::ExpandMacroRequest(async_request) => Some(
+                &async_request
+                    .params
+                    .text_document_position
+                    .text_document
+                    .uri,
+            ),
             // These variants do not have a uri
             AsyncMessage::WorkspaceSymbolRequest(_) => None,
             // These variants should not have a uri
@@ -228,6 +305,11 @@ impl ParamsDeserialization for InlayHintParams {
         self.text_document.uri = clean_url(&self.text_document.uri)
     }
 }
+impl ParamsDeserialization for CodeActionParams {
+    fn clean(&mut self) {
+        self.text_document.uri = clean_url(&self.text_document.uri)
+    }
+}
 impl ParamsDeserialization for HoverParams {
     fn clean(&mut self) {
         self.text_document_position_params.text_document.uri =
@@ -252,11 +334,49 @@ impl ParamsDeserialization for GotoDefinitionParams {
             clean_url(&self.text_document_position_params.text_document.uri)
     }
 }
+impl ParamsDeserialization for ReferenceParams {
+    fn clean(&mut self) {
+        self.text_document_position.text_document.uri =
+            clean_url(&self.text_document_position.text_document.uri)
+    }
+}
+impl ParamsDeserialization for TextDocumentPositionParams {
+    fn clean(&mut self) {
+        self.text_document.uri = clean_url(&self.text_document.uri)
+    }
+}
+impl ParamsDeserialization for RenameParams {
+    fn clean(&mut self) {
+        self.text_document_position.text_document.uri =
+            clean_url(&self.text_document_position.text_document.uri)
+    }
+}
+impl ParamsDeserialization for CallHierarchyPrepareParams {
+    fn clean(&mut self) {
+        self.text_document_position_params.text_document.uri =
+            clean_url(&self.text_document_position_params.text_document.uri)
+    }
+}
+impl ParamsDeserialization for CallHierarchyIncomingCallsParams {
+    fn clean(&mut self) {
+        self.item.uri = clean_url(&self.item.uri)
+    }
+}
+impl ParamsDeserialization for CallHierarchyOutgoingCallsParams {
+    fn clean(&mut self) {
+        self.item.uri = clean_url(&self.item.uri)
+    }
+}
 impl ParamsDeserialization for DocumentDiagnosticParams {
     fn clean(&mut self) {
         self.text_document.uri = clean_url(&self.text_document.uri)
     }
 }
+impl ParamsDeserialization for ValidatorInfoParams {
+    fn clean(&mut self) {
+        self.text_document.uri = clean_url(&self.text_document.uri)
+    }
+}
 impl ParamsDeserialization for DumpAstParams {
     fn clean(&mut self) {
         self.text_document.uri = clean_url(&self.text_document.uri)
@@ -267,3 +387,9 @@ impl ParamsDeserialization for DumpDependencyParams {
         self.text_document.uri = clean_url(&self.text_document.uri)
     }
 }
+impl ParamsDeserialization for ExpandMacroParams {
+    fn clean(&mut self) {
+        self.text_document_position.text_document.uri =
+            clean_url(&self.text_document_position.text_document.uri)
+    }
+}
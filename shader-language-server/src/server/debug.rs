@@ -1,4 +1,4 @@
-use lsp_types::{request::Request, TextDocumentIdentifier};
+use lsp_types::{request::Request, Range, TextDocumentIdentifier, TextDocumentPositionParams};
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug)]
@@ -32,3 +32,27 @@ impl Request for DumpDependencyRequest {
     type Result = Option<String>;
     const METHOD: &'static str = "debug/dumpDependency";
 }
+
+#[derive(Debug)]
+pub enum ExpandMacroRequest {}
+
+#[derive(Debug, Eq, PartialEq, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExpandMacroParams {
+    #[serde(flatten)]
+    pub text_document_position: TextDocumentPositionParams,
+}
+
+#[derive(Debug, Eq, PartialEq, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExpandMacroResult {
+    pub name: String,
+    pub range: Range,
+    pub expansion: String,
+}
+
+impl Request for ExpandMacroRequest {
+    type Params = ExpandMacroParams;
+    type Result = Option<ExpandMacroResult>;
+    const METHOD: &'static str = "debug/expandMacro";
+}
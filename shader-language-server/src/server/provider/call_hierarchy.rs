@@ -0,0 +1,153 @@
+use std::cell::RefCell;
+
+use shader_sense::{
+    position::ShaderPosition,
+    shader_error::ShaderError,
+    symbols::{
+        call_graph::ShaderCallGraph,
+        symbols::{ShaderScope, ShaderSymbol, ShaderSymbolType},
+    },
+};
+
+use lsp_types::{
+    CallHierarchyIncomingCall, CallHierarchyItem, CallHierarchyOutgoingCall, Position, SymbolKind,
+    Url,
+};
+
+use crate::server::common::{shader_range_to_lsp_range, ServerLanguageError};
+use crate::server::ServerLanguage;
+
+fn get_call_hierarchy_item(function: &ShaderSymbol, main_uri: &Url) -> Option<CallHierarchyItem> {
+    let runtime = function.mode.map_runtime()?;
+    let range = match &runtime.scope {
+        Some(scope) => ShaderScope::join(scope.clone(), runtime.range.clone()),
+        None => runtime.range.clone(),
+    };
+    Some(CallHierarchyItem {
+        name: function.label.clone(),
+        kind: SymbolKind::FUNCTION,
+        tags: None,
+        detail: Some(function.format()),
+        uri: Url::from_file_path(&runtime.file_path).unwrap(),
+        range: shader_range_to_lsp_range(&range),
+        selection_range: shader_range_to_lsp_range(&runtime.range),
+        // Item might be declared in an include, so store the file its symbols are resolved from.
+        data: Some(serde_json::to_value(main_uri).unwrap()),
+    })
+}
+
+fn get_main_uri(item: &CallHierarchyItem) -> Url {
+    item.data
+        .as_ref()
+        .and_then(|data| serde_json::from_value::<Url>(data.clone()).ok())
+        .unwrap_or(item.uri.clone())
+}
+
+impl ServerLanguage {
+    pub fn recolt_prepare_call_hierarchy(
+        &mut self,
+        uri: &Url,
+        position: Position,
+    ) -> Result<Option<Vec<CallHierarchyItem>>, ServerLanguageError> {
+        let cached_file = self.get_cachable_file(&uri)?;
+        let language_data = self
+            .language_data
+            .get(&cached_file.shading_language)
+            .unwrap();
+        let file_path = uri.to_file_path().unwrap();
+        let symbol_list = self.watched_files.get_all_symbols(uri);
+        let word = match language_data.symbol_provider.get_word_range_at_position(
+            &RefCell::borrow(&cached_file.shader_module),
+            &ShaderPosition::new(position.line, position.character),
+        ) {
+            Ok(word) => word,
+            Err(ShaderError::NoSymbol) => return Ok(None),
+            Err(err) => return Err(err.into()),
+        };
+        let items: Vec<CallHierarchyItem> = word
+            .find_symbol_from_parent(file_path, &symbol_list)
+            .iter()
+            .filter(|symbol| symbol.is_type(ShaderSymbolType::Functions))
+            .filter_map(|symbol| get_call_hierarchy_item(symbol, uri))
+            .collect();
+        if items.is_empty() {
+            Ok(None)
+        } else {
+            Ok(Some(items))
+        }
+    }
+    pub fn recolt_incoming_calls(
+        &mut self,
+        item: &CallHierarchyItem,
+    ) -> Result<Option<Vec<CallHierarchyIncomingCall>>, ServerLanguageError> {
+        let main_uri = get_main_uri(item);
+        self.get_cachable_file(&main_uri)?; // Ensure file is cached before querying symbols.
+        let symbol_list = self.watched_files.get_all_symbols(&main_uri);
+        let function = match Self::find_call_hierarchy_function(&symbol_list.functions, item) {
+            Some(function) => function,
+            None => return Ok(None),
+        };
+        let call_graph = ShaderCallGraph::new(&symbol_list);
+        Ok(Some(
+            call_graph
+                .get_incoming_calls(function)
+                .iter()
+                .filter_map(|call| {
+                    Some(CallHierarchyIncomingCall {
+                        from: get_call_hierarchy_item(call.function, &main_uri)?,
+                        from_ranges: call
+                            .ranges
+                            .iter()
+                            .map(|range| shader_range_to_lsp_range(&range.range))
+                            .collect(),
+                    })
+                })
+                .collect(),
+        ))
+    }
+    pub fn recolt_outgoing_calls(
+        &mut self,
+        item: &CallHierarchyItem,
+    ) -> Result<Option<Vec<CallHierarchyOutgoingCall>>, ServerLanguageError> {
+        let main_uri = get_main_uri(item);
+        self.get_cachable_file(&main_uri)?; // Ensure file is cached before querying symbols.
+        let symbol_list = self.watched_files.get_all_symbols(&main_uri);
+        let function = match Self::find_call_hierarchy_function(&symbol_list.functions, item) {
+            Some(function) => function,
+            None => return Ok(None),
+        };
+        let call_graph = ShaderCallGraph::new(&symbol_list);
+        Ok(Some(
+            call_graph
+                .get_outgoing_calls(function)
+                .iter()
+                .filter_map(|call| {
+                    // Intrinsics have no location to show.
+                    Some(CallHierarchyOutgoingCall {
+                        to: get_call_hierarchy_item(call.function, &main_uri)?,
+                        from_ranges: call
+                            .ranges
+                            .iter()
+                            .map(|range| shader_range_to_lsp_range(&range.range))
+                            .collect(),
+                    })
+                })
+                .collect(),
+        ))
+    }
+    fn find_call_hierarchy_function<'a>(
+        functions: &[&'a ShaderSymbol],
+        item: &CallHierarchyItem,
+    ) -> Option<&'a ShaderSymbol> {
+        functions
+            .iter()
+            .find(|function| {
+                function.label == item.name
+                    && function.mode.map_runtime().is_some_and(|runtime| {
+                        Url::from_file_path(&runtime.file_path).unwrap() == item.uri
+                            && shader_range_to_lsp_range(&runtime.range) == item.selection_range
+                    })
+            })
+            .map(|function| *function)
+    }
+}
@@ -0,0 +1,67 @@
+use std::collections::HashMap;
+
+use lsp_types::{
+    CodeAction, CodeActionKind, CodeActionOrCommand, Diagnostic, NumberOrString, Range, TextEdit,
+    Url, WorkspaceEdit,
+};
+
+use crate::server::common::{
+    lsp_range_to_shader_range, shader_range_to_lsp_range, ServerLanguageError,
+};
+use crate::server::ServerLanguage;
+
+impl ServerLanguage {
+    pub fn recolt_code_action(
+        &mut self,
+        uri: &Url,
+        lsp_range: &Range,
+    ) -> Result<Vec<CodeActionOrCommand>, ServerLanguageError> {
+        let cached_file = self.get_cachable_file(&uri)?;
+        let file_path = uri.to_file_path().unwrap();
+        let range = lsp_range_to_shader_range(lsp_range);
+        let mut code_actions = Vec::new();
+        for diagnostic in &cached_file.get_data().diagnostic_cache.diagnostics {
+            if diagnostic.fixes.is_empty()
+                || diagnostic.range.file_path != file_path
+                || diagnostic.range.range.start.line > range.end.line
+                || diagnostic.range.range.end.line < range.start.line
+            {
+                continue;
+            }
+            let lsp_diagnostic = Diagnostic {
+                range: shader_range_to_lsp_range(&diagnostic.range.range),
+                severity: Some(Self::get_lsp_severity(&diagnostic.severity)),
+                message: diagnostic.error.clone(),
+                code: diagnostic.code.clone().map(NumberOrString::String),
+                source: Some(diagnostic.source.to_string()),
+                ..Default::default()
+            };
+            for fix in &diagnostic.fixes {
+                let edit_uri = Url::from_file_path(&fix.range.file_path).unwrap();
+                code_actions.push(CodeActionOrCommand::CodeAction(CodeAction {
+                    title: if fix.range.range.start == fix.range.range.end {
+                        format!("Insert '{}'", fix.new_text)
+                    } else {
+                        format!("Replace with '{}'", fix.new_text)
+                    },
+                    kind: Some(CodeActionKind::QUICKFIX),
+                    diagnostics: Some(vec![lsp_diagnostic.clone()]),
+                    edit: Some(WorkspaceEdit {
+                        changes: Some(HashMap::from([(
+                            edit_uri,
+                            vec![TextEdit {
+                                range: shader_range_to_lsp_range(&fix.range.range),
+                                new_text: fix.new_text.clone(),
+                            }],
+                        )])),
+                        ..Default::default()
+                    }),
+                    // Only a single fix can be applied automatically.
+                    is_preferred: Some(diagnostic.fixes.len() == 1),
+                    ..Default::default()
+                }));
+            }
+        }
+        Ok(code_actions)
+    }
+}
@@ -8,7 +8,10 @@ use shader_sense::{
     position::{ShaderFilePosition, ShaderPosition},
     shader::ShadingLanguage,
     shader_error::ShaderError,
-    symbols::symbols::{ShaderSymbol, ShaderSymbolData, ShaderSymbolMode, ShaderSymbolType},
+    symbols::{
+        symbols::{ShaderSymbol, ShaderSymbolData, ShaderSymbolMode, ShaderSymbolType},
+        type_resolver::ShaderTypeResolver,
+    },
 };
 
 use crate::server::{common::ServerLanguageError, ServerLanguage};
@@ -79,6 +82,24 @@ impl ServerLanguage {
                 ShaderPosition::from_byte_offset(content, new_byte_offset).unwrap()
             }
         };
+        if trigger_character.as_deref() == Some(".") {
+            // Members of the accessed expression type, fallback to the symbol chain if it cannot be inferred.
+            let shader_module = RefCell::borrow(&cached_file.shader_module);
+            let type_resolver = ShaderTypeResolver::new(&shader_module, &symbol_list);
+            let members = type_resolver
+                .resolve_expression_at(&ShaderPosition::new(
+                    position.line,
+                    position.character.saturating_sub(1),
+                ))
+                .map(|ty| type_resolver.find_members(&ty))
+                .unwrap_or_default();
+            if !members.is_empty() {
+                return Ok(members
+                    .iter()
+                    .map(|member| convert_completion_item(cached_file.shading_language, member))
+                    .collect());
+            }
+        }
         let shader_file_position = ShaderFilePosition::from(file_path.clone(), shader_position);
         let symbol_list = symbol_list.filter_scoped_symbol(&shader_file_position);
         match trigger_character {
@@ -2,14 +2,17 @@ use std::collections::HashMap;
 
 use log::info;
 use lsp_types::{
-    Diagnostic, DiagnosticSeverity, DiagnosticTag, DocumentDiagnosticReport,
-    DocumentDiagnosticReportKind, DocumentDiagnosticReportResult, FullDocumentDiagnosticReport,
-    PublishDiagnosticsParams, RelatedFullDocumentDiagnosticReport, Url,
+    Diagnostic, DiagnosticRelatedInformation, DiagnosticSeverity, DiagnosticTag,
+    DocumentDiagnosticReport, DocumentDiagnosticReportKind, DocumentDiagnosticReportResult,
+    FullDocumentDiagnosticReport, NumberOrString, PublishDiagnosticsParams,
+    RelatedFullDocumentDiagnosticReport, Url,
 };
 
 use shader_sense::shader_error::ShaderDiagnosticSeverity;
 
-use crate::server::common::{shader_range_to_lsp_range, ServerLanguageError};
+use crate::server::common::{
+    shader_range_to_location, shader_range_to_lsp_range, ServerLanguageError,
+};
 
 use crate::server::ServerLanguage;
 
@@ -87,7 +90,9 @@ impl ServerLanguage {
                 publish_diagnostics_params,
             );
     }
-    fn get_lsp_severity(severity: &ShaderDiagnosticSeverity) -> lsp_types::DiagnosticSeverity {
+    pub(super) fn get_lsp_severity(
+        severity: &ShaderDiagnosticSeverity,
+    ) -> lsp_types::DiagnosticSeverity {
         match severity {
             ShaderDiagnosticSeverity::Hint => lsp_types::DiagnosticSeverity::HINT,
             ShaderDiagnosticSeverity::Information => lsp_types::DiagnosticSeverity::INFORMATION,
@@ -110,15 +115,50 @@ impl ServerLanguage {
             for diagnostic in &diagnostic_cache.diagnostics {
                 let uri = Url::from_file_path(&diagnostic.range.file_path).unwrap();
                 if diagnostic.severity.is_required(self.config.get_severity()) {
+                    let message = if diagnostic.error.is_empty() {
+                        "No message.".into() // vscode extension send error when empty message.
+                    } else {
+                        diagnostic.error.clone()
+                    };
+                    // Entry points are set when validating all entry points of the file.
+                    let message = if diagnostic.entry_points.is_empty() {
+                        message
+                    } else {
+                        format!(
+                            "{}\nEmitted by entry point {}",
+                            message,
+                            diagnostic
+                                .entry_points
+                                .iter()
+                                .map(|entry_point| format!(
+                                    "{} ({})",
+                                    entry_point.label,
+                                    entry_point.stage.to_string()
+                                ))
+                                .collect::<Vec<String>>()
+                                .join(", ")
+                        )
+                    };
                     let diagnostic = Diagnostic {
                         range: shader_range_to_lsp_range(&diagnostic.range.range),
                         severity: Some(Self::get_lsp_severity(&diagnostic.severity)),
-                        message: if diagnostic.error.is_empty() {
-                            "No message.".into() // vscode extension send error when empty message.
+                        message,
+                        code: diagnostic.code.clone().map(NumberOrString::String),
+                        source: Some(diagnostic.source.to_string()),
+                        related_information: if diagnostic.related_information.is_empty() {
+                            None
                         } else {
-                            diagnostic.error.clone()
+                            Some(
+                                diagnostic
+                                    .related_information
+                                    .iter()
+                                    .map(|related| DiagnosticRelatedInformation {
+                                        location: shader_range_to_location(&related.range),
+                                        message: related.message.clone(),
+                                    })
+                                    .collect(),
+                            )
                         },
-                        source: Some("shader-validator".to_string()),
                         ..Default::default()
                     };
                     match diagnostics.get_mut(&uri) {
@@ -214,7 +254,8 @@ impl ServerLanguage {
                                 range: shader_range_to_lsp_range(&d.range.range),
                                 severity: Some(Self::get_lsp_severity(&d.severity)),
                                 message: d.error.clone(),
-                                source: Some("shader-validator".to_string()),
+                                code: d.code.clone().map(NumberOrString::String),
+                                source: Some(d.source.to_string()),
                                 ..Default::default()
                             }),
                     );
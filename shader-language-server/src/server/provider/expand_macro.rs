@@ -0,0 +1,32 @@
+use std::cell::RefCell;
+
+use lsp_types::{Position, Url};
+
+use shader_sense::{position::ShaderPosition, symbols::prepocessor::ShaderMacroExpansion};
+
+use crate::server::common::ServerLanguageError;
+use crate::server::ServerLanguage;
+
+impl ServerLanguage {
+    pub fn recolt_macro_expansion(
+        &mut self,
+        uri: &Url,
+        position: Position,
+    ) -> Result<Option<ShaderMacroExpansion>, ServerLanguageError> {
+        let cached_file = self.get_cachable_file(&uri)?;
+        let language_data = self
+            .language_data
+            .get(&cached_file.shading_language)
+            .unwrap();
+        let data = match &cached_file.data {
+            Some(data) => data,
+            None => return Ok(None),
+        };
+        let expansion = language_data.symbol_provider.expand_macro_at_position(
+            &RefCell::borrow(&cached_file.shader_module),
+            &data.symbol_cache,
+            &ShaderPosition::new(position.line, position.character),
+        )?;
+        Ok(expansion)
+    }
+}
@@ -3,7 +3,10 @@ use std::cell::RefCell;
 use shader_sense::{
     position::{ShaderFilePosition, ShaderRange},
     shader_error::ShaderError,
-    symbols::symbols::{ShaderSymbolData, ShaderSymbolMode},
+    symbols::{
+        symbols::{ShaderSymbolData, ShaderSymbolMode},
+        type_resolver::ShaderTypeResolver,
+    },
 };
 
 use lsp_types::{GotoDefinitionResponse, Position, Url};
@@ -29,13 +32,14 @@ impl ServerLanguage {
             position.character as u32,
         );
         let symbol_list = self.watched_files.get_all_symbols(uri);
-        match language_data.symbol_provider.get_word_range_at_position(
-            &RefCell::borrow(&cached_file.shader_module),
-            &shader_position.position,
-        ) {
+        let shader_module = RefCell::borrow(&cached_file.shader_module);
+        match language_data
+            .symbol_provider
+            .get_word_range_at_position(&shader_module, &shader_position.position)
+        {
             Ok(word) => {
                 let matching_symbols =
-                    word.find_symbol_from_parent(file_path.clone(), &symbol_list);
+                    ShaderTypeResolver::new(&shader_module, &symbol_list).find_word_symbols(&word);
                 Ok(Some(GotoDefinitionResponse::Link(
                     matching_symbols
                         .iter()
@@ -5,6 +5,7 @@ use lsp_types::{Hover, HoverContents, MarkupContent, Position, Url};
 use shader_sense::position::ShaderFilePosition;
 use shader_sense::shader_error::ShaderError;
 use shader_sense::symbols::symbols::{ShaderSymbolData, ShaderSymbolMode};
+use shader_sense::symbols::type_resolver::ShaderTypeResolver;
 
 use crate::server::common::{shader_range_to_lsp_range, ServerLanguageError};
 use crate::server::ServerLanguage;
@@ -15,6 +16,8 @@ impl ServerLanguage {
         uri: &Url,
         position: Position,
     ) -> Result<Option<Hover>, ServerLanguageError> {
+        // Expansion failure should not prevent hover.
+        let macro_expansion = self.recolt_macro_expansion(uri, position).unwrap_or(None);
         let cached_file = self.get_cachable_file(&uri)?;
         let file_path = uri.to_file_path().unwrap();
         let shader_position = ShaderFilePosition::new(
@@ -26,15 +29,16 @@ impl ServerLanguage {
             .language_data
             .get(&cached_file.shading_language)
             .unwrap();
-        match language_data.symbol_provider.get_word_range_at_position(
-            &RefCell::borrow(&cached_file.shader_module),
-            &shader_position.position,
-        ) {
+        let shader_module = RefCell::borrow(&cached_file.shader_module);
+        match language_data
+            .symbol_provider
+            .get_word_range_at_position(&shader_module, &shader_position.position)
+        {
             // word_range should be the same as symbol range
             Ok(word) => {
                 let symbol_list = self.watched_files.get_all_symbols(uri);
                 let matching_symbols =
-                    word.find_symbol_from_parent(file_path.clone(), &symbol_list);
+                    ShaderTypeResolver::new(&shader_module, &symbol_list).find_word_symbols(&word);
                 if matching_symbols.len() == 0 {
                     Ok(None)
                 } else {
@@ -50,7 +54,10 @@ impl ServerLanguage {
                             (description, link)
                         }
                         ShaderSymbolMode::RuntimeContext(_) => match &symbol.data {
-                            ShaderSymbolData::Macro { value } => {
+                            ShaderSymbolData::Macro {
+                                value,
+                                parameters: _,
+                            } => {
                                 let description = if !value.is_empty() {
                                     format!("Config macro. Expanding to \n```\n{}\n```", value)
                                 } else {
@@ -65,7 +72,10 @@ impl ServerLanguage {
                                 let description = format!("Including file {}", target.display());
                                 (description, "".into())
                             }
-                            ShaderSymbolData::Macro { value } => {
+                            ShaderSymbolData::Macro {
+                                value,
+                                parameters: _,
+                            } => {
                                 let description = if !value.is_empty() {
                                     format!(
                                         "Preprocessor macro. Expanding to \n```\n{}\n```",
@@ -79,6 +89,13 @@ impl ServerLanguage {
                             _ => ("".into(), "".into()),
                         },
                     };
+                    let description = match &macro_expansion {
+                        Some(expansion) if expansion.name == symbol.label => format!(
+                            "{}\n\nExpanded here to \n```\n{}\n```",
+                            description, expansion.expansion
+                        ),
+                        _ => description,
+                    };
                     let location = match &symbol.mode {
                         ShaderSymbolMode::Runtime(runtime) => format!(
                             "Defined in {}, line {}",
@@ -1,11 +1,16 @@
+mod call_hierarchy;
+mod code_action;
 mod completion;
 mod diagnostic;
 mod document_symbol;
+mod expand_macro;
 mod folding_range;
 mod formatting;
 mod goto;
 mod hover;
 mod inlay_hint;
+mod references;
+mod rename;
 mod semantic_token;
 mod signature;
 mod workspace_symbol;
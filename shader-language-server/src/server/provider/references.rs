@@ -0,0 +1,70 @@
+use std::{cell::RefCell, rc::Rc};
+
+use shader_sense::{position::ShaderFilePosition, shader_error::ShaderError};
+
+use lsp_types::{Location, Position, Url};
+
+use crate::server::common::{shader_range_to_location, ServerLanguageError};
+use crate::server::ServerLanguage;
+
+impl ServerLanguage {
+    pub fn recolt_references(
+        &mut self,
+        uri: &Url,
+        position: Position,
+        include_declaration: bool,
+    ) -> Result<Option<Vec<Location>>, ServerLanguageError> {
+        let cached_file = self.get_cachable_file(&uri)?;
+        let language_data = self
+            .language_data
+            .get(&cached_file.shading_language)
+            .unwrap();
+        let file_path = uri.to_file_path().unwrap();
+        let shader_position = ShaderFilePosition::new(
+            file_path.clone(),
+            position.line as u32,
+            position.character as u32,
+        );
+        let symbol_list = self.watched_files.get_all_symbols(uri);
+        let word = match language_data.symbol_provider.get_word_range_at_position(
+            &RefCell::borrow(&cached_file.shader_module),
+            &shader_position.position,
+        ) {
+            Ok(word) => word,
+            Err(ShaderError::NoSymbol) => return Ok(None),
+            Err(err) => return Err(err.into()),
+        };
+        let mut locations: Vec<Location> = Vec::new();
+        // Overloads are all matched, as we cannot discriminate them yet.
+        for symbol in word.find_symbol_from_parent(file_path.clone(), &symbol_list) {
+            // Symbols from a shared include are also referenced by other files including it.
+            for file_uri in self.watched_files.get_files_sharing_symbol(uri, &symbol) {
+                let file = self.watched_files.files.get(&file_uri).unwrap();
+                let file_symbol_list = self.watched_files.get_all_symbols(&file_uri);
+                let references = language_data.symbol_provider.query_references(
+                    &RefCell::borrow(&file.shader_module),
+                    &file.get_data().symbol_cache,
+                    &file_symbol_list,
+                    &symbol,
+                    include_declaration,
+                    // Dependencies are all watched, reuse their content.
+                    &mut |include| {
+                        let include_uri = Url::from_file_path(include.get_absolute_path()).unwrap();
+                        Ok(self
+                            .watched_files
+                            .files
+                            .get(&include_uri)
+                            .map(|include_file| Rc::clone(&include_file.shader_module)))
+                    },
+                )?;
+                for reference in references {
+                    let location = shader_range_to_location(&reference);
+                    if !locations.contains(&location) {
+                        locations.push(location);
+                    }
+                }
+            }
+        }
+        Ok(Some(locations))
+    }
+}
@@ -0,0 +1,121 @@
+use std::{cell::RefCell, collections::HashMap, rc::Rc};
+
+use shader_sense::{position::ShaderPosition, shader_error::ShaderError};
+
+use lsp_types::{Position, PrepareRenameResponse, TextEdit, Url, WorkspaceEdit};
+
+use crate::server::common::{shader_range_to_lsp_range, ServerLanguageError};
+use crate::server::ServerLanguage;
+
+// Rejected renames are user errors, not internal ones.
+fn rename_error(err: ShaderError) -> ServerLanguageError {
+    match err {
+        ShaderError::InvalidRename(message) => ServerLanguageError::InvalidParams(message),
+        err => err.into(),
+    }
+}
+
+impl ServerLanguage {
+    pub fn recolt_prepare_rename(
+        &mut self,
+        uri: &Url,
+        position: Position,
+    ) -> Result<Option<PrepareRenameResponse>, ServerLanguageError> {
+        let cached_file = self.get_cachable_file(&uri)?;
+        let language_data = self
+            .language_data
+            .get(&cached_file.shading_language)
+            .unwrap();
+        let file_path = uri.to_file_path().unwrap();
+        let symbol_list = self.watched_files.get_all_symbols(uri);
+        let word = match language_data.symbol_provider.get_word_range_at_position(
+            &RefCell::borrow(&cached_file.shader_module),
+            &ShaderPosition::new(position.line, position.character),
+        ) {
+            Ok(word) => word,
+            Err(ShaderError::NoSymbol) => return Ok(None),
+            Err(err) => return Err(err.into()),
+        };
+        let matching_symbols = word.find_symbol_from_parent(file_path, &symbol_list);
+        if matching_symbols.is_empty() {
+            return Ok(None);
+        }
+        for symbol in &matching_symbols {
+            language_data
+                .symbol_provider
+                .check_rename(symbol)
+                .map_err(rename_error)?;
+        }
+        Ok(Some(PrepareRenameResponse::RangeWithPlaceholder {
+            range: shader_range_to_lsp_range(word.get_range()),
+            placeholder: word.get_word().into(),
+        }))
+    }
+    pub fn recolt_rename(
+        &mut self,
+        uri: &Url,
+        position: Position,
+        new_name: &str,
+    ) -> Result<Option<WorkspaceEdit>, ServerLanguageError> {
+        let cached_file = self.get_cachable_file(&uri)?;
+        let language_data = self
+            .language_data
+            .get(&cached_file.shading_language)
+            .unwrap();
+        let file_path = uri.to_file_path().unwrap();
+        let symbol_list = self.watched_files.get_all_symbols(uri);
+        let word = match language_data.symbol_provider.get_word_range_at_position(
+            &RefCell::borrow(&cached_file.shader_module),
+            &ShaderPosition::new(position.line, position.character),
+        ) {
+            Ok(word) => word,
+            Err(ShaderError::NoSymbol) => return Ok(None),
+            Err(err) => return Err(err.into()),
+        };
+        let mut changes: HashMap<Url, Vec<TextEdit>> = HashMap::new();
+        // Overloads are all renamed, as they share the same label.
+        for symbol in word.find_symbol_from_parent(file_path, &symbol_list) {
+            // Symbols from a shared include must be renamed in every file including it.
+            for file_uri in self.watched_files.get_files_sharing_symbol(uri, &symbol) {
+                let file = self.watched_files.files.get(&file_uri).unwrap();
+                let file_symbol_list = self.watched_files.get_all_symbols(&file_uri);
+                let edits = language_data
+                    .symbol_provider
+                    .query_rename(
+                        &RefCell::borrow(&file.shader_module),
+                        &file.get_data().symbol_cache,
+                        &file_symbol_list,
+                        &symbol,
+                        new_name,
+                        &mut |include| {
+                            let include_uri =
+                                Url::from_file_path(include.get_absolute_path()).unwrap();
+                            Ok(self
+                                .watched_files
+                                .files
+                                .get(&include_uri)
+                                .map(|include_file| Rc::clone(&include_file.shader_module)))
+                        },
+                    )
+                    .map_err(rename_error)?;
+                for edit in edits {
+                    let text_edits = changes
+                        .entry(Url::from_file_path(&edit.file_path).unwrap())
+                        .or_default();
+                    for range in edit.ranges {
+                        let text_edit =
+                            TextEdit::new(shader_range_to_lsp_range(&range), new_name.into());
+                        if !text_edits.contains(&text_edit) {
+                            text_edits.push(text_edit);
+                        }
+                    }
+                }
+            }
+        }
+        if changes.is_empty() {
+            Ok(None)
+        } else {
+            Ok(Some(WorkspaceEdit::new(changes)))
+        }
+    }
+}
@@ -65,6 +65,35 @@ impl ServerLanguage {
                     let signatures = matching_symbols
                         .iter()
                         .filter_map(|shader_symbol| {
+                            if let ShaderSymbolData::Macro {
+                                value,
+                                parameters: Some(parameters),
+                            } = &shader_symbol.data
+                            {
+                                return Some(vec![SignatureInformation {
+                                    label: format!(
+                                        "{}({})",
+                                        shader_symbol.label,
+                                        parameters.join(", ")
+                                    ),
+                                    documentation: Some(lsp_types::Documentation::MarkupContent(
+                                        MarkupContent {
+                                            kind: lsp_types::MarkupKind::Markdown,
+                                            value: format!("```\n{}\n```", value),
+                                        },
+                                    )),
+                                    parameters: Some(
+                                        parameters
+                                            .iter()
+                                            .map(|parameter| ParameterInformation {
+                                                label: ParameterLabel::Simple(parameter.clone()),
+                                                documentation: None,
+                                            })
+                                            .collect(),
+                                    ),
+                                    active_parameter: None,
+                                }]);
+                            }
                             let signatures = match &shader_symbol.data {
                                 ShaderSymbolData::Types { constructors } => constructors,
                                 ShaderSymbolData::Struct {
@@ -12,8 +12,9 @@ use shader_sense::{
     include::canonicalize,
     shader::{
         GlslCompilationParams, GlslSpirvVersion, GlslTargetClient, HlslCompilationParams,
-        HlslShaderModel, HlslVersion, ShaderCompilationParams, ShaderContextParams, ShaderParams,
-        ShaderStage, WgslCompilationParams,
+        HlslMatrixPacking, HlslShaderModel, HlslSpirvTargetEnv, HlslVersion, HlslVulkanLayout,
+        ShaderCompilationParams, ShaderContextParams, ShaderParams, ShaderStage,
+        ShaderStageMapping, WgslCapabilityProfile, WgslCompilationParams, WgslValidationFlag,
     },
     shader_error::ShaderDiagnosticSeverity,
 };
@@ -35,6 +36,13 @@ pub struct ServerHlslConfig {
     pub version: Option<HlslVersion>,
     pub enable16bit_types: Option<bool>,
     pub spirv: Option<bool>,
+    pub spirv_target_env: Option<HlslSpirvTargetEnv>,
+    pub matrix_packing: Option<HlslMatrixPacking>,
+    pub vulkan_layout: Option<HlslVulkanLayout>,
+    pub enabled_warnings: Option<Vec<String>>,
+    pub disabled_warnings: Option<Vec<String>>,
+    pub warnings_as_errors: Option<bool>,
+    pub extra_args: Option<Vec<String>>,
 }
 
 #[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
@@ -42,6 +50,19 @@ pub struct ServerHlslConfig {
 pub struct ServerGlslConfig {
     pub target_client: Option<GlslTargetClient>,
     pub spirv_version: Option<GlslSpirvVersion>,
+    pub suppress_warnings: Option<bool>,
+    pub relaxed_errors: Option<bool>,
+    pub extra_args: Option<Vec<String>>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct ServerWgslConfig {
+    pub profile: Option<WgslCapabilityProfile>,
+    pub push_constants: Option<bool>,
+    pub subgroups: Option<bool>,
+    pub ray_query: Option<bool>,
+    pub validation_flags: Option<Vec<WgslValidationFlag>>,
 }
 
 #[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
@@ -76,8 +97,11 @@ pub struct ServerSerializedConfigOverride {
     includes: Option<Vec<String>>,
     defines: Option<HashMap<String, String>>,
     path_remapping: Option<HashMap<String, String>>,
+    stage_mappings: Option<Vec<ShaderStageMapping>>,
+    dxc_path: Option<String>,
     hlsl: Option<ServerHlslConfig>,
     glsl: Option<ServerGlslConfig>,
+    wgsl: Option<ServerWgslConfig>,
 }
 
 /// Serialized configuration for the server to be sent through workspace/configuration lsp request or as input when starting the server.
@@ -89,14 +113,18 @@ pub struct ServerSerializedConfig {
     defines: Option<HashMap<String, String>>, // Defines to set
     path_remapping: Option<HashMap<String, String>>, // Virtual path remapping
     validate: Option<bool>,                   // Validation via standard API
-    symbols: Option<bool>,                    // Query symbols
-    symbol_diagnostics: Option<bool>,         // Debug option to visualise issues with tree-sitter
+    validate_entry_points: Option<bool>, // Validate every entry point of a file with its own stage
+    symbols: Option<bool>,               // Query symbols
+    symbol_diagnostics: Option<bool>,    // Debug option to visualise issues with tree-sitter
     stage_define: Option<HashMap<ShaderStage, HashMap<String, String>>>, // Specific macro defined per shader stage
-    trace: Option<ServerTrace>,      // Level of error to display
-    severity: Option<String>,        // Severity of diagnostic to display
-    config_override: Option<String>, // Override configuration file
-    hlsl: Option<ServerHlslConfig>,  // Hlsl specific configuration
-    glsl: Option<ServerGlslConfig>,  // Glsl specific configuration
+    stage_mappings: Option<Vec<ShaderStageMapping>>, // File name patterns mapped to a shader stage
+    trace: Option<ServerTrace>,                      // Level of error to display
+    severity: Option<String>,                        // Severity of diagnostic to display
+    config_override: Option<String>,                 // Override configuration file
+    dxc_path: Option<String>, // Folder containing dxcompiler & dxil libraries
+    hlsl: Option<ServerHlslConfig>, // Hlsl specific configuration
+    glsl: Option<ServerGlslConfig>, // Glsl specific configuration
+    wgsl: Option<ServerWgslConfig>, // Wgsl specific configuration
 }
 
 /// Configuration computed from both server configuration and engine configuration.
@@ -106,11 +134,14 @@ pub struct ServerConfig {
     defines: HashMap<String, String>,
     path_remapping: HashMap<PathBuf, PathBuf>,
     stage_define: HashMap<ShaderStage, HashMap<String, String>>,
+    stage_mappings: Vec<ShaderStageMapping>,
     validate: bool,
+    validate_entry_points: bool,
     symbols: bool,
     symbol_diagnostics: bool,
     trace: ServerTrace,
     severity: ShaderDiagnosticSeverity,
+    dxc_path: Option<PathBuf>,
     hlsl: HlslCompilationParams,
     glsl: GlslCompilationParams,
     wgsl: WgslCompilationParams,
@@ -126,6 +157,23 @@ impl ServerSerializedConfig {
                 PathBuf::from(path)
             })
         }
+        fn verify_stage_mappings(
+            stage_mappings: Vec<ShaderStageMapping>,
+        ) -> Vec<ShaderStageMapping> {
+            // Drop invalid patterns to avoid checking them for every file.
+            stage_mappings
+                .into_iter()
+                .filter(|stage_mapping| {
+                    if !stage_mapping.is_valid() {
+                        warn!(
+                            "Invalid stage mapping pattern {:?}",
+                            stage_mapping.get_pattern()
+                        );
+                    }
+                    stage_mapping.is_valid()
+                })
+                .collect()
+        }
         // Convert ServerConfig to ServerEngineConfig
         let mut config = ServerConfig {
             includes: self
@@ -142,16 +190,25 @@ impl ServerSerializedConfig {
                 })
                 .unwrap_or_default(),
             validate: self.validate.unwrap_or(ServerConfig::DEFAULT_VALIDATE),
+            validate_entry_points: self
+                .validate_entry_points
+                .unwrap_or(ServerConfig::DEFAULT_VALIDATE_ENTRY_POINTS),
             symbols: self.symbols.unwrap_or(ServerConfig::DEFAULT_SYMBOLS),
             symbol_diagnostics: self
                 .symbol_diagnostics
                 .unwrap_or(ServerConfig::DEFAULT_SYMBOL_DIAGNOSTIC),
             trace: self.trace.unwrap_or(ServerConfig::DEFAULT_TRACE),
             stage_define: self.stage_define.unwrap_or_default(),
+            stage_mappings: self
+                .stage_mappings
+                .map(verify_stage_mappings)
+                .unwrap_or_default(),
             severity: self
                 .severity
                 .map(|s| ShaderDiagnosticSeverity::from(s.as_str()))
                 .unwrap_or(ServerConfig::DEFAULT_SEVERITY),
+            // Relative path are resolved per workspace folder.
+            dxc_path: self.dxc_path.map(PathBuf::from),
             hlsl: self
                 .hlsl
                 .map(|hlsl| HlslCompilationParams {
@@ -159,6 +216,13 @@ impl ServerSerializedConfig {
                     version: hlsl.version.unwrap_or_default(),
                     enable16bit_types: hlsl.enable16bit_types.unwrap_or_default(),
                     spirv: hlsl.spirv.unwrap_or_default(),
+                    spirv_target_env: hlsl.spirv_target_env.unwrap_or_default(),
+                    matrix_packing: hlsl.matrix_packing,
+                    vulkan_layout: hlsl.vulkan_layout,
+                    enabled_warnings: hlsl.enabled_warnings.unwrap_or_default(),
+                    disabled_warnings: hlsl.disabled_warnings.unwrap_or_default(),
+                    warnings_as_errors: hlsl.warnings_as_errors.unwrap_or_default(),
+                    extra_args: hlsl.extra_args.unwrap_or_default(),
                 })
                 .unwrap_or_default(),
             glsl: self
@@ -166,9 +230,26 @@ impl ServerSerializedConfig {
                 .map(|glsl| GlslCompilationParams {
                     client: glsl.target_client.unwrap_or_default(),
                     spirv: glsl.spirv_version.unwrap_or_default(),
+                    version: None,
+                    extensions: Vec::new(),
+                    suppress_warnings: glsl.suppress_warnings.unwrap_or_default(),
+                    relaxed_errors: glsl.relaxed_errors.unwrap_or_default(),
+                    extra_args: glsl.extra_args.unwrap_or_default(),
+                })
+                .unwrap_or_default(),
+            wgsl: self
+                .wgsl
+                .map(|wgsl| {
+                    let default = WgslCompilationParams::default();
+                    WgslCompilationParams {
+                        profile: wgsl.profile.unwrap_or(default.profile),
+                        push_constants: wgsl.push_constants.unwrap_or(default.push_constants),
+                        subgroups: wgsl.subgroups.unwrap_or(default.subgroups),
+                        ray_query: wgsl.ray_query.unwrap_or(default.ray_query),
+                        validation_flags: wgsl.validation_flags.unwrap_or(default.validation_flags),
+                    }
                 })
                 .unwrap_or_default(),
-            wgsl: WgslCompilationParams {},
         };
         // Get engine config if set and override them.
         if let Some(config_override) = self.config_override {
@@ -220,6 +301,15 @@ impl ServerSerializedConfig {
                     })
                     .unwrap_or_default(),
             );
+            // Engine mappings are checked first.
+            if let Some(stage_mappings) = override_config.stage_mappings {
+                config
+                    .stage_mappings
+                    .splice(0..0, verify_stage_mappings(stage_mappings));
+            }
+            if let Some(dxc_path) = override_config.dxc_path {
+                config.dxc_path = Some(PathBuf::from(dxc_path));
+            }
             if let Some(override_glsl) = override_config.glsl {
                 if let Some(spirv_version) = override_glsl.spirv_version {
                     config.glsl.spirv = spirv_version;
@@ -227,6 +317,17 @@ impl ServerSerializedConfig {
                 if let Some(target_client) = override_glsl.target_client {
                     config.glsl.client = target_client;
                 }
+                if let Some(suppress_warnings) = override_glsl.suppress_warnings {
+                    config.glsl.suppress_warnings = suppress_warnings;
+                }
+                if let Some(relaxed_errors) = override_glsl.relaxed_errors {
+                    config.glsl.relaxed_errors = relaxed_errors;
+                }
+                // Arguments are appended to the ones from server configuration.
+                config
+                    .glsl
+                    .extra_args
+                    .extend(override_glsl.extra_args.unwrap_or_default());
             }
             if let Some(override_hlsl) = override_config.hlsl {
                 if let Some(version) = override_hlsl.version {
@@ -241,6 +342,48 @@ impl ServerSerializedConfig {
                 if let Some(spirv) = override_hlsl.spirv {
                     config.hlsl.spirv = spirv;
                 }
+                if let Some(spirv_target_env) = override_hlsl.spirv_target_env {
+                    config.hlsl.spirv_target_env = spirv_target_env;
+                }
+                if let Some(matrix_packing) = override_hlsl.matrix_packing {
+                    config.hlsl.matrix_packing = Some(matrix_packing);
+                }
+                if let Some(vulkan_layout) = override_hlsl.vulkan_layout {
+                    config.hlsl.vulkan_layout = Some(vulkan_layout);
+                }
+                if let Some(warnings_as_errors) = override_hlsl.warnings_as_errors {
+                    config.hlsl.warnings_as_errors = warnings_as_errors;
+                }
+                // Warnings & arguments are appended to the ones from server configuration.
+                config
+                    .hlsl
+                    .enabled_warnings
+                    .extend(override_hlsl.enabled_warnings.unwrap_or_default());
+                config
+                    .hlsl
+                    .disabled_warnings
+                    .extend(override_hlsl.disabled_warnings.unwrap_or_default());
+                config
+                    .hlsl
+                    .extra_args
+                    .extend(override_hlsl.extra_args.unwrap_or_default());
+            }
+            if let Some(override_wgsl) = override_config.wgsl {
+                if let Some(profile) = override_wgsl.profile {
+                    config.wgsl.profile = profile;
+                }
+                if let Some(push_constants) = override_wgsl.push_constants {
+                    config.wgsl.push_constants = push_constants;
+                }
+                if let Some(subgroups) = override_wgsl.subgroups {
+                    config.wgsl.subgroups = subgroups;
+                }
+                if let Some(ray_query) = override_wgsl.ray_query {
+                    config.wgsl.ray_query = ray_query;
+                }
+                if let Some(validation_flags) = override_wgsl.validation_flags {
+                    config.wgsl.validation_flags = validation_flags;
+                }
             }
             config
         } else {
@@ -252,6 +395,7 @@ impl ServerSerializedConfig {
 impl ServerConfig {
     pub const DEFAULT_SYMBOLS: bool = true;
     pub const DEFAULT_VALIDATE: bool = true;
+    pub const DEFAULT_VALIDATE_ENTRY_POINTS: bool = false;
     pub const DEFAULT_SYMBOL_DIAGNOSTIC: bool = false; // Mostly for debug
     pub const DEFAULT_SEVERITY: ShaderDiagnosticSeverity = ShaderDiagnosticSeverity::Error;
     pub const DEFAULT_TRACE: ServerTrace = ServerTrace {
@@ -300,6 +444,7 @@ impl ServerConfig {
             compilation: ShaderCompilationParams {
                 entry_point,
                 shader_stage,
+                stage_mappings: self.stage_mappings.clone(),
                 hlsl: hlsl,
                 glsl: glsl,
                 wgsl: wgsl,
@@ -309,6 +454,9 @@ impl ServerConfig {
     pub fn get_validate(&self) -> bool {
         self.validate
     }
+    pub fn get_validate_entry_points(&self) -> bool {
+        self.validate_entry_points
+    }
     pub fn get_symbols(&self) -> bool {
         self.symbols
     }
@@ -324,6 +472,18 @@ impl ServerConfig {
     pub fn set_trace(&mut self, trace: ServerTrace) {
         self.trace = trace
     }
+    /// Get the folder of the DXC library to use for files of the given workspace folder.
+    /// Relative path are resolved from the workspace folder, so each workspace can use its own DXC.
+    pub fn get_dxc_path(&self, workspace_folder: Option<&Url>) -> Option<PathBuf> {
+        let dxc_path = self.dxc_path.as_ref()?;
+        match workspace_folder {
+            Some(workspace_folder) if dxc_path.is_relative() => workspace_folder
+                .to_file_path()
+                .ok()
+                .map(|workspace_path| workspace_path.join(dxc_path)),
+            _ => Some(dxc_path.clone()),
+        }
+    }
 }
 
 impl Default for ServerConfig {
@@ -333,11 +493,14 @@ impl Default for ServerConfig {
             defines: HashMap::new(),
             path_remapping: HashMap::new(),
             validate: ServerConfig::DEFAULT_VALIDATE,
+            validate_entry_points: ServerConfig::DEFAULT_VALIDATE_ENTRY_POINTS,
             symbols: ServerConfig::DEFAULT_SYMBOLS,
             stage_define: HashMap::new(),
+            stage_mappings: Vec::new(),
             symbol_diagnostics: ServerConfig::DEFAULT_SYMBOL_DIAGNOSTIC,
             trace: ServerConfig::DEFAULT_TRACE,
             severity: ServerConfig::DEFAULT_SEVERITY,
+            dxc_path: None,
             hlsl: HlslCompilationParams::default(),
             glsl: GlslCompilationParams::default(),
             wgsl: WgslCompilationParams::default(),
@@ -364,6 +527,10 @@ impl ServerLanguage {
                 if server.config != config {
                     profile_scope!("Updating server config: {:#?}", config);
                     server.config = config.clone();
+                    // Library might have been fixed, retry loading it.
+                    for language_data in server.language_data.values_mut() {
+                        language_data.validator.clear_load_errors();
+                    }
                     // Republish all diagnostics
                     let async_updates: Vec<AsyncCacheRequest> = server
                         .watched_files
@@ -393,7 +560,7 @@ mod tests {
     use shader_sense::shader::{ShaderStage, ShadingLanguage};
 
     use crate::server::{
-        server_config::{ServerConfig, ServerSerializedConfig},
+        server_config::{ServerConfig, ServerHlslConfig, ServerSerializedConfig},
         shader_variant::ShaderVariant,
     };
 
@@ -475,6 +642,7 @@ mod tests {
         let cfg = cfg.compute_engine_config();
         assert!(cfg.get_symbols() == ServerConfig::DEFAULT_SYMBOLS);
         assert!(cfg.get_validate() == ServerConfig::DEFAULT_VALIDATE);
+        assert!(cfg.get_validate_entry_points() == ServerConfig::DEFAULT_VALIDATE_ENTRY_POINTS);
         assert!(cfg.get_symbol_diagnostics() == ServerConfig::DEFAULT_SYMBOL_DIAGNOSTIC);
         assert!(cfg.is_verbose() == ServerConfig::DEFAULT_TRACE.is_verbose());
         assert!(cfg.get_severity() == ServerConfig::DEFAULT_SEVERITY);
@@ -509,6 +677,10 @@ mod tests {
         let cfg = ServerSerializedConfig {
             includes: Some(vec!["D:/other/path/to/my/include".into()]),
             config_override: Some("../shader-sense/test/config-override.json".into()),
+            hlsl: Some(ServerHlslConfig {
+                extra_args: Some(vec!["-fvk-use-dx-layout".into()]),
+                ..Default::default()
+            }),
             ..Default::default()
         };
         let cfg = cfg.compute_engine_config();
@@ -516,5 +688,87 @@ mod tests {
         assert!(cfg.includes[0] == PathBuf::from("D:/other/path/to/my/include"));
         assert!(cfg.includes[1] == PathBuf::from("D:/path/to/my/include"));
         assert!(*cfg.defines.get("MY_MACRO").unwrap() == String::from("1"));
+        assert!(cfg.hlsl.disabled_warnings == vec![String::from("conversion")]);
+        assert!(cfg.hlsl.extra_args == vec![String::from("-fvk-use-dx-layout"), "-Zpr".into()]);
+    }
+
+    #[test]
+    fn test_hlsl_compilation_config() {
+        use shader_sense::shader::{HlslMatrixPacking, HlslSpirvTargetEnv, HlslVulkanLayout};
+
+        let cfg: ServerSerializedConfig = serde_json::from_str(
+            r#"{
+            "hlsl": {
+                "spirv": true,
+                "spirvTargetEnv": "Vulkan1_1",
+                "matrixPacking": "RowMajor",
+                "vulkanLayout": "Dx",
+                "disabledWarnings": ["conversion"],
+                "warningsAsErrors": true
+            }
+        }"#,
+        )
+        .unwrap();
+        let cfg = cfg.compute_engine_config();
+        assert!(cfg.hlsl.spirv_target_env == HlslSpirvTargetEnv::Vulkan1_1);
+        assert!(cfg.hlsl.matrix_packing == Some(HlslMatrixPacking::RowMajor));
+        assert!(cfg.hlsl.vulkan_layout == Some(HlslVulkanLayout::Dx));
+        assert!(cfg.hlsl.disabled_warnings == vec![String::from("conversion")]);
+        assert!(cfg.hlsl.enabled_warnings.is_empty());
+        assert!(cfg.hlsl.warnings_as_errors);
+    }
+
+    #[test]
+    #[cfg(not(target_os = "wasi"))] // Url to file path unsupported.
+    fn test_dxc_path_config() {
+        let cfg = ServerSerializedConfig::default().compute_engine_config();
+        assert!(cfg.get_dxc_path(None).is_none());
+        let cfg: ServerSerializedConfig = serde_json::from_str(
+            r#"{
+            "dxcPath": "tools/dxc"
+        }"#,
+        )
+        .unwrap();
+        let cfg = cfg.compute_engine_config();
+        let workspace_folder = Url::from_directory_path(std::env::current_dir().unwrap()).unwrap();
+        assert!(cfg.get_dxc_path(None) == Some(PathBuf::from("tools/dxc")));
+        assert!(
+            cfg.get_dxc_path(Some(&workspace_folder))
+                == Some(std::env::current_dir().unwrap().join("tools/dxc"))
+        );
+    }
+
+    #[test]
+    fn test_stage_mappings_config() {
+        let cfg: ServerSerializedConfig = serde_json::from_str(
+            r#"{
+            "stageMappings": [
+                { "pattern": { "glob": "*.vs.hlsl" }, "stage": "vertex" },
+                { "pattern": { "regex": "_cs\\.hlsl$" }, "stage": "compute", "entryPoint": "CSMain" },
+                { "pattern": { "regex": "(" }, "stage": "fragment" }
+            ]
+        }"#,
+        )
+        .unwrap();
+        let cfg = cfg.compute_engine_config();
+        let shader_params = cfg.into_shader_params(None, None);
+        // Invalid pattern is dropped.
+        assert!(shader_params.compilation.stage_mappings.len() == 2);
+        let vertex_mapping = shader_params
+            .compilation
+            .get_stage_mapping("mesh.vs.hlsl")
+            .unwrap();
+        assert!(vertex_mapping.stage == ShaderStage::Vertex);
+        assert!(vertex_mapping.entry_point.is_none());
+        let compute_mapping = shader_params
+            .compilation
+            .get_stage_mapping("culling_cs.hlsl")
+            .unwrap();
+        assert!(compute_mapping.stage == ShaderStage::Compute);
+        assert!(compute_mapping.entry_point == Some("CSMain".into()));
+        assert!(shader_params
+            .compilation
+            .get_stage_mapping("common.hlsl")
+            .is_none());
     }
 }
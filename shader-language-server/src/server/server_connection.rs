@@ -1,6 +1,6 @@
 use std::collections::HashMap;
 
-use log::error;
+use log::{error, warn};
 use lsp_server::{Connection, IoThreads, Message, RequestId, Response};
 use lsp_types::{InitializeParams, MessageType, ShowMessageParams};
 use serde_json::Value;
@@ -83,6 +83,13 @@ impl ServerConnection {
             message: message,
         })
     }
+    pub fn send_notification_warning(&self, message: String) {
+        warn!("NOTIFICATION: {}", message);
+        self.send_notification::<lsp_types::notification::ShowMessage>(ShowMessageParams {
+            typ: MessageType::WARNING,
+            message: message,
+        })
+    }
     pub fn send_request<R: lsp_types::request::Request>(
         &mut self,
         params: R::Params,
@@ -18,8 +18,11 @@ use log::{debug, info, warn};
 use lsp_types::Url;
 use shader_sense::{
     position::ShaderFileRange,
-    shader::ShadingLanguage,
-    shader_error::{ShaderDiagnostic, ShaderDiagnosticList, ShaderDiagnosticSeverity, ShaderError},
+    shader::{ShaderParams, ShadingLanguage},
+    shader_error::{
+        ShaderDiagnostic, ShaderDiagnosticList, ShaderDiagnosticRelatedInformation,
+        ShaderDiagnosticSeverity, ShaderError,
+    },
     symbols::{
         intrinsics::ShaderIntrinsics,
         prepocessor::ShaderPreprocessorContext,
@@ -27,6 +30,7 @@ use shader_sense::{
         shader_module_parser::ShaderModuleParser,
         symbol_list::ShaderSymbolListRef,
         symbol_provider::SymbolProvider,
+        symbols::{ShaderSymbol, ShaderSymbolMode},
     },
     validator::validator::ValidatorImpl,
 };
@@ -84,7 +88,7 @@ impl ServerLanguageFileCache {
             workspace_folder: Vec::new(),
         }
     }
-    fn get_workspace_folder(&self, uri: &Url) -> Option<&Url> {
+    pub fn get_workspace_folder(&self, uri: &Url) -> Option<&Url> {
         let file_path = uri.to_file_path().unwrap();
         self.workspace_folder
             .iter()
@@ -161,6 +165,32 @@ impl ServerLanguageFileCache {
             }
         }
     }
+    // Get all cachable files seeing the given symbol, starting with the requesting one.
+    // Files including the declaring file are not part of the requesting file include tree.
+    pub fn get_files_sharing_symbol(&self, uri: &Url, symbol: &ShaderSymbol) -> Vec<Url> {
+        let mut files = vec![uri.clone()];
+        let declaration_uri = match &symbol.mode {
+            ShaderSymbolMode::Runtime(runtime) => match Url::from_file_path(&runtime.file_path) {
+                Ok(declaration_uri) => declaration_uri,
+                Err(()) => return files,
+            },
+            _ => return files,
+        };
+        let mut sharing_files: Vec<Url> = self
+            .get_dependent_main_files(&declaration_uri)
+            .into_iter()
+            .chain(
+                self.files
+                    .get(&declaration_uri)
+                    .filter(|file| file.is_cachable_file() && file.has_data())
+                    .map(|_| declaration_uri.clone()),
+            )
+            .filter(|file_uri| file_uri != uri)
+            .collect();
+        sharing_files.sort();
+        files.extend(sharing_files);
+        files
+    }
     #[allow(unused)]
     pub fn get_relying_variant(&self, url: &Url) -> Option<Url> {
         let file_path = url.to_file_path().unwrap();
@@ -195,8 +225,12 @@ impl ServerLanguageFileCache {
         // Compute params
         let shader_params =
             config.into_shader_params(self.get_workspace_folder(uri), variant.clone());
-        let mut context =
-            ShaderPreprocessorContext::main(&file_path, shader_params.context.clone());
+        // Compiler predefined macros are not passed to validation as they are already defined.
+        let mut context_params = shader_params.context.clone();
+        for (name, value) in validator.get_predefined_macros() {
+            context_params.defines.entry(name).or_insert(value);
+        }
+        let mut context = ShaderPreprocessorContext::main(&file_path, context_params);
 
         // Do not recache & revalidate if not dirty.
         match &self.files.get(&uri).unwrap().data {
@@ -260,6 +294,41 @@ impl ServerLanguageFileCache {
         } else {
             (ShaderSymbols::default(), ShaderDiagnosticList::default())
         };
+        // Apply params declared in file, such as #pragma shader_stage.
+        let shader_params = ShaderParams {
+            context: shader_params.context,
+            compilation: symbols
+                .get_preprocessor()
+                .get_compilation_params(&shader_params.compilation),
+        };
+        // Variants already set the entry point to validate.
+        let entry_points =
+            if config.get_validate() && config.get_validate_entry_points() && variant.is_none() {
+                profile_scope!("Finding entry points for file {}", uri);
+                let shader_module = Rc::clone(&self.files.get(uri).unwrap().shader_module);
+                let shader_module = RefCell::borrow(&shader_module);
+                symbol_provider
+                    .query_entry_points(
+                        &shader_module,
+                        &symbols,
+                        &shader_params,
+                        &mut |deps_path: &Path| -> Option<String> {
+                            // Prefer content of watched files as it might not be saved yet.
+                            match self.get_file(&Url::from_file_path(deps_path).unwrap()) {
+                                Some(deps_file) => {
+                                    Some(RefCell::borrow(&deps_file.shader_module).content.clone())
+                                }
+                                None => read_string_lossy(deps_path).ok(),
+                            }
+                        },
+                    )
+                    .unwrap_or_else(|err| {
+                        warn!("Failed to find entry points for file {}: {}", uri, err);
+                        Vec::new()
+                    })
+            } else {
+                Vec::new()
+            };
         // Get diagnostics
         let diagnostics = if config.get_validate() {
             profile_scope!("Validating file {}", uri);
@@ -275,10 +344,11 @@ impl ServerLanguageFileCache {
                     }
                     None => shader_module,
                 };
-                let diagnostics = match validator.validate_shader(
+                let diagnostics = match validator.validate_entry_points(
                     &RefCell::borrow(&variant_shader_module).content,
                     RefCell::borrow(&variant_shader_module).file_path.as_path(),
                     &shader_params,
+                    &entry_points,
                     &mut |deps_path: &Path| -> Option<String> {
                         let deps_uri = Url::from_file_path(deps_path).unwrap();
                         let deps_file = match self.get_file(&deps_uri) {
@@ -311,11 +381,11 @@ impl ServerLanguageFileCache {
                 ) {
                     Ok(diagnostics) => diagnostics,
                     Err(err) => ShaderDiagnosticList { diagnostics: vec![
-                        ShaderDiagnostic {
-                            severity: ShaderDiagnosticSeverity::Error,
-                            error: format!("Failed to validate shader: {:?}", err),
-                            range: ShaderFileRange::zero(file_path.clone())
-                        }
+                        ShaderDiagnostic::new(
+                            ShaderDiagnosticSeverity::Error,
+                            format!("Failed to validate shader: {:?}", err),
+                            ShaderFileRange::zero(file_path.clone()),
+                        )
                     ]},
                 };
                 diagnostics
@@ -329,51 +399,52 @@ impl ServerLanguageFileCache {
             }
             {
                 // If includes have issues, diagnose them.
-                let mut ascended_diagnostics: Vec<ShaderDiagnostic> =
-                    symbols
-                        .get_preprocessor()
-                        .includes
-                        .iter()
-                        .filter_map(|include| {
-                            for diagnostic in &diagnostic_list.diagnostics {
-                                if diagnostic.severity != ShaderDiagnosticSeverity::Error {
-                                    continue;
-                                }
-                                let diagnostic_path = &diagnostic.range.file_path;
-                                if *diagnostic_path == file_path {
-                                    continue; // Main file diagnostics
-                                }
-                                if *diagnostic_path == include.get_absolute_path() {
-                                    return Some(ShaderDiagnostic {
-                                        severity: ShaderDiagnosticSeverity::Error,
-                                        error: format!(
-                                            "File {} has issues:\n{}", // TODO: add command to file
-                                            include.get_relative_path(),
-                                            diagnostic.error
-                                        ),
-                                        range: include.get_file_range(),
-                                    });
-                                }
+                let mut ascended_diagnostics: Vec<ShaderDiagnostic> = symbols
+                    .get_preprocessor()
+                    .includes
+                    .iter()
+                    .filter_map(|include| {
+                        for diagnostic in &diagnostic_list.diagnostics {
+                            if diagnostic.severity != ShaderDiagnosticSeverity::Error {
+                                continue;
+                            }
+                            let diagnostic_path = &diagnostic.range.file_path;
+                            if *diagnostic_path == file_path {
+                                continue; // Main file diagnostics
+                            }
+                            let relative_path = if *diagnostic_path == include.get_absolute_path() {
+                                include.get_relative_path()
+                            } else {
                                 match include.cache.as_ref().unwrap().find_include(&mut |i| {
                                     i.get_absolute_path() == *diagnostic_path
                                 }) {
-                                    Some(includer) => {
-                                        return Some(ShaderDiagnostic {
-                                            severity: ShaderDiagnosticSeverity::Error,
-                                            error: format!(
-                                                "File {} has issues:\n{}",
-                                                includer.get_relative_path(),
-                                                diagnostic.error
-                                            ),
-                                            range: include.get_file_range(),
-                                        })
-                                    }
-                                    None => {}
+                                    Some(includer) => includer.get_relative_path(),
+                                    None => continue,
                                 }
-                            }
-                            None
-                        })
-                        .collect();
+                            };
+                            // TODO: add command to file
+                            return Some(
+                                ShaderDiagnostic::new(
+                                    ShaderDiagnosticSeverity::Error,
+                                    format!(
+                                        "File {} has issues:\n{}",
+                                        relative_path, diagnostic.error
+                                    ),
+                                    include.get_file_range(),
+                                )
+                                .with_code(diagnostic.code.clone())
+                                .with_source(diagnostic.source)
+                                .with_related_information(vec![
+                                    ShaderDiagnosticRelatedInformation {
+                                        message: diagnostic.error.clone(),
+                                        range: diagnostic.range.clone(),
+                                    },
+                                ]),
+                            );
+                        }
+                        None
+                    })
+                    .collect();
                 diagnostic_list
                     .diagnostics
                     .append(&mut ascended_diagnostics);
@@ -388,8 +459,12 @@ impl ServerLanguageFileCache {
             .diagnostics
             .extend(symbol_diagnostics.diagnostics);
         let shading_language = self.files.get(uri).unwrap().shading_language;
-        let intrinsics = ShaderIntrinsics::get(shading_language)
-            .get_intrinsics_symbol(&shader_params.compilation);
+        // Filter intrinsics with directives from file such as #version.
+        let intrinsics = ShaderIntrinsics::get(shading_language).get_intrinsics_symbol(
+            &symbols
+                .get_preprocessor()
+                .get_compilation_params(&shader_params.compilation),
+        );
         self.files.get_mut(uri).unwrap().data = Some(ServerFileCacheData {
             symbol_cache: symbols,
             intrinsics,
@@ -470,12 +545,16 @@ impl ServerLanguageFileCache {
                                         self.files.get(uri).unwrap().shading_language;
                                     let intrinsics = ShaderIntrinsics::get(shading_language)
                                         .get_intrinsics_symbol(
-                                            &config
-                                                .into_shader_params(
-                                                    self.get_workspace_folder(uri),
-                                                    Some(variant.clone()),
-                                                )
-                                                .compilation,
+                                            &symbol_cache
+                                                .get_preprocessor()
+                                                .get_compilation_params(
+                                                    &config
+                                                        .into_shader_params(
+                                                            self.get_workspace_folder(uri),
+                                                            Some(variant.clone()),
+                                                        )
+                                                        .compilation,
+                                                ),
                                         );
                                     file_to_cache.insert(
                                         include_url,
@@ -627,9 +706,10 @@ impl ServerLanguageFileCache {
                 file_progress_index,
                 unique_remaining_files.len() as u32 + 1,
             );
+            let dxc_path = config.get_dxc_path(self.get_workspace_folder(&variant_url));
             let removed_files = self.cache_file_data(
                 &variant_url,
-                language_data.validator.as_mut(),
+                language_data.validator.get(dxc_path.as_deref()),
                 &mut language_data.shader_module_parser,
                 &mut language_data.symbol_provider,
                 &config,
@@ -689,9 +769,10 @@ impl ServerLanguageFileCache {
                 }
             };
             let language_data = language_data.get_mut(&shading_language).unwrap();
+            let dxc_path = config.get_dxc_path(self.get_workspace_folder(&remaining_file));
             let removed_files = self.cache_file_data(
                 &remaining_file,
-                language_data.validator.as_mut(),
+                language_data.validator.get(dxc_path.as_deref()),
                 &mut language_data.shader_module_parser,
                 &mut language_data.symbol_provider,
                 &config,
@@ -725,9 +806,10 @@ impl ServerLanguageFileCache {
                 (unique_remaining_files.len() + dependent_files_to_update.len()) as u32
                     + need_to_recompute_variant as u32,
             );
+            let dxc_path = config.get_dxc_path(self.get_workspace_folder(&dependent_file));
             let removed_files = self.cache_file_data(
                 &dependent_file,
-                language_data.validator.as_mut(),
+                language_data.validator.get(dxc_path.as_deref()),
                 &mut language_data.shader_module_parser,
                 &mut language_data.symbol_provider,
                 &config,
@@ -1,14 +1,179 @@
+use std::path::Path;
+
 use shader_sense::{
     shader::ShadingLanguage,
     symbols::{shader_module_parser::ShaderModuleParser, symbol_provider::SymbolProvider},
-    validator::{glslang::Glslang, naga::Naga, validator::ValidatorImpl},
+    validator::{
+        glslang::Glslang,
+        naga::Naga,
+        validator::{ValidatorImpl, ValidatorInfo},
+    },
+};
+
+#[cfg(not(target_os = "wasi"))]
+use shader_sense::{
+    position::ShaderFileRange,
+    shader::{ShaderParams, ShaderStage},
+    shader_error::{
+        ShaderDiagnostic, ShaderDiagnosticList, ShaderDiagnosticSeverity, ShaderDiagnosticSource,
+        ShaderError,
+    },
+    validator::{dxc::Dxc, validator::ValidatorBackend},
 };
+#[cfg(not(target_os = "wasi"))]
+use std::{collections::HashMap, path::PathBuf};
+
+/// Validator reporting a DXC library that failed to load on every validation.
+/// Supported stages are the ones of glslang, which is used when no DXC library can be loaded.
+#[cfg(not(target_os = "wasi"))]
+struct DxcLoadError {
+    error: String,
+    fallback: Glslang,
+}
+
+#[cfg(not(target_os = "wasi"))]
+impl ValidatorImpl for DxcLoadError {
+    fn validate_shader(
+        &self,
+        _shader_content: &str,
+        file_path: &Path,
+        _params: &ShaderParams,
+        _include_callback: &mut dyn FnMut(&Path) -> Option<String>,
+    ) -> Result<ShaderDiagnosticList, ShaderError> {
+        Ok(ShaderDiagnosticList::from(
+            ShaderDiagnostic::new(
+                ShaderDiagnosticSeverity::Error,
+                self.error.clone(),
+                ShaderFileRange::zero(file_path.into()),
+            )
+            .with_source(ShaderDiagnosticSource::Dxc),
+        ))
+    }
+    fn support(&self, shader_stage: ShaderStage) -> bool {
+        self.fallback.support(shader_stage)
+    }
+    fn get_backend(&self) -> ValidatorBackend {
+        ValidatorBackend::Dxc
+    }
+}
+
+/// Validator of a language, with one DXC instance per configured library folder for HLSL.
+pub struct ServerValidator {
+    shading_language: ShadingLanguage,
+    default: Box<dyn ValidatorImpl>,
+    fallback: Option<String>, // Reason of the default validator fallback, if any.
+    #[cfg(not(target_os = "wasi"))]
+    dxc_instances: HashMap<PathBuf, Result<Dxc, DxcLoadError>>,
+}
+
+impl ServerValidator {
+    fn new(
+        shading_language: ShadingLanguage,
+        default: Box<dyn ValidatorImpl>,
+        fallback: Option<String>,
+    ) -> Self {
+        Self {
+            shading_language,
+            default,
+            fallback,
+            #[cfg(not(target_os = "wasi"))]
+            dxc_instances: HashMap::new(),
+        }
+    }
+    /// Get the validator to use with the given DXC library folder.
+    /// Libraries are loaded on first use. If loading failed, the returned validator report the load error
+    /// instead of falling back on glslang, and loading is only retried after [`ServerValidator::clear_load_errors`].
+    pub fn get(&mut self, dxc_path: Option<&Path>) -> &dyn ValidatorImpl {
+        match (self.shading_language, dxc_path) {
+            #[cfg(not(target_os = "wasi"))]
+            (ShadingLanguage::Hlsl, Some(dxc_path)) => {
+                if !self.dxc_instances.contains_key(dxc_path) {
+                    let instance = match Dxc::new(Some(dxc_path.into())) {
+                        Ok(dxc) => {
+                            log::info!("Loaded dxc library from {}", dxc_path.display());
+                            log_dxc_instance(&dxc);
+                            Ok(dxc)
+                        }
+                        Err(err) => {
+                            let error = format!(
+                                "Failed to load DXC library from {}: {}",
+                                dxc_path.display(),
+                                err
+                            );
+                            log::error!("{}", error);
+                            Err(DxcLoadError {
+                                error,
+                                fallback: Glslang::hlsl(),
+                            })
+                        }
+                    };
+                    self.dxc_instances.insert(dxc_path.into(), instance);
+                }
+                match self.dxc_instances.get(dxc_path).unwrap() {
+                    Ok(dxc) => dxc as &dyn ValidatorImpl,
+                    Err(load_error) => load_error,
+                }
+            }
+            _ => self.default.as_ref(),
+        }
+    }
+    /// Forget libraries that failed to load, so that they are loaded again on next use.
+    /// Should be called when the configuration changes, as the library might have been fixed.
+    pub fn clear_load_errors(&mut self) {
+        #[cfg(not(target_os = "wasi"))]
+        self.dxc_instances.retain(|_, instance| instance.is_ok());
+    }
+    /// Notify validators that a file was saved on disk.
+    pub fn on_file_saved(&self, file_path: &Path) {
+        self.default.on_file_saved(file_path);
+        #[cfg(not(target_os = "wasi"))]
+        for dxc in self
+            .dxc_instances
+            .values()
+            .filter_map(|instance| instance.as_ref().ok())
+        {
+            dxc.on_file_saved(file_path);
+        }
+    }
+    /// Describe the validator used with the given DXC library folder.
+    pub fn get_info(&mut self, dxc_path: Option<&Path>) -> ValidatorInfo {
+        let use_default = dxc_path.is_none() || self.shading_language != ShadingLanguage::Hlsl;
+        let fallback = if use_default {
+            self.fallback.clone()
+        } else {
+            None
+        };
+        ValidatorInfo {
+            fallback,
+            ..self.get(dxc_path).get_info()
+        }
+    }
+    /// Reason why the default validator is not the preferred one, if any.
+    pub fn get_fallback(&self) -> Option<&String> {
+        self.fallback.as_ref()
+    }
+}
 
 #[cfg(not(target_os = "wasi"))]
-use shader_sense::validator::dxc::Dxc;
+fn log_dxc_instance(dxc: &Dxc) {
+    let version = match dxc.get_version() {
+        Some(version) => version.to_string(),
+        None => "(unknown version)".into(),
+    };
+    let dxil_validation = match dxc.get_dxil_validator_version() {
+        Some((major, minor)) => format!("available ({}.{})", major, minor),
+        None if dxc.is_dxil_validation_available() => "available".into(),
+        None => "unavailable".into(),
+    };
+    log::info!(
+        "Using Dxc {} for HLSL. DXIL validation {}",
+        version,
+        dxil_validation
+    );
+}
 
 pub struct ServerLanguageData {
-    pub validator: Box<dyn ValidatorImpl>,
+    pub validator: ServerValidator,
     pub shader_module_parser: ShaderModuleParser,
     pub symbol_provider: SymbolProvider,
 }
@@ -26,7 +191,7 @@ impl ServerLanguageData {
         let symbol_provider = SymbolProvider::from_shading_language(ShadingLanguage::Glsl);
         log::info!("Using glslang for GLSL validation.");
         Self {
-            validator: Box::new(Glslang::glsl()),
+            validator: ServerValidator::new(ShadingLanguage::Glsl, Box::new(Glslang::glsl()), None),
             shader_module_parser,
             symbol_provider,
         }
@@ -34,42 +199,43 @@ impl ServerLanguageData {
     pub fn hlsl() -> Self {
         let shader_module_parser = ShaderModuleParser::from_shading_language(ShadingLanguage::Hlsl);
         let symbol_provider = SymbolProvider::from_shading_language(ShadingLanguage::Hlsl);
-        Self {
-            #[cfg(target_os = "wasi")]
-            validator: {
-                log::info!("Using glslang for HLSL validation as DXC is unsupported on WASI.");
-                Box::new(Glslang::hlsl())
-            },
-            #[cfg(not(target_os = "wasi"))]
-            validator: match Dxc::new(if let Some(dxc_path) = Dxc::find_dxc_library() {
-                log::info!(
+        #[cfg(target_os = "wasi")]
+        let (validator, fallback): (Box<dyn ValidatorImpl>, Option<String>) = {
+            log::info!("Using glslang for HLSL validation as DXC is unsupported on WASI.");
+            (
+                Box::new(Glslang::hlsl()),
+                Some("DXC is not supported on WASI.".into()),
+            )
+        };
+        #[cfg(not(target_os = "wasi"))]
+        let (validator, fallback): (Box<dyn ValidatorImpl>, Option<String>) = {
+            let dxc_path = Dxc::find_dxc_library();
+            match &dxc_path {
+                Some(dxc_path) => log::info!(
                     "Found dxc library for HLSL validation at {}",
                     dxc_path.display()
-                );
-                Some(dxc_path)
-            } else {
-                log::info!("Did not found dxc library for HLSL validation, will try to use globally available ones.");
-                None
-            }) {
+                ),
+                None => log::info!("Did not found dxc library for HLSL validation, will try to use globally available ones."),
+            }
+            match Dxc::new(dxc_path) {
                 Ok(dxc) => {
-                    log::info!(
-                        "Using Dxc for HLSL. DXIL validation {}",
-                        if dxc.is_dxil_validation_available() {
-                            "available"
-                        } else {
-                            "unavailable"
-                        }
-                    );
-                    Box::new(dxc)
+                    log_dxc_instance(&dxc);
+                    (Box::new(dxc), None)
                 }
                 Err(err) => {
                     log::error!(
-                        "Failed to instantiate DXC: {}\nFallback to glslang instead.",
+                        "Failed to instantiate DXC: {}\nFallback to glslang instead. Set dxcPath to use a specific DXC library.",
                         err.to_string()
                     );
-                    Box::new(Glslang::hlsl())
+                    (
+                        Box::new(Glslang::hlsl()),
+                        Some(format!("Failed to load DXC library: {}", err)),
+                    )
                 }
-            },
+            }
+        };
+        Self {
+            validator: ServerValidator::new(ShadingLanguage::Hlsl, validator, fallback),
             shader_module_parser,
             symbol_provider,
         }
@@ -79,7 +245,7 @@ impl ServerLanguageData {
         let symbol_provider = SymbolProvider::from_shading_language(ShadingLanguage::Wgsl);
         log::info!("Using Naga for WGSL validation.");
         Self {
-            validator: Box::new(Naga::new()),
+            validator: ServerValidator::new(ShadingLanguage::Wgsl, Box::new(Naga::new()), None),
             shader_module_parser,
             symbol_provider,
         }
@@ -0,0 +1,48 @@
+use lsp_types::{request::Request, TextDocumentIdentifier, Url};
+use serde::{Deserialize, Serialize};
+use shader_sense::validator::validator::ValidatorInfo;
+
+use crate::server::{common::ServerLanguageError, ServerLanguage};
+
+#[derive(Debug)]
+pub enum ValidatorInfoRequest {}
+
+#[derive(Debug, Eq, PartialEq, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ValidatorInfoParams {
+    #[serde(flatten)]
+    pub text_document: TextDocumentIdentifier,
+}
+
+impl Request for ValidatorInfoRequest {
+    type Params = ValidatorInfoParams;
+    type Result = ValidatorInfo;
+    const METHOD: &'static str = "textDocument/validatorInfo";
+}
+
+impl ServerLanguage {
+    /// Describe the validator used for the given file.
+    pub fn recolt_validator_info(
+        &mut self,
+        uri: &Url,
+    ) -> Result<ValidatorInfo, ServerLanguageError> {
+        let shading_language = self.get_cachable_file(uri)?.shading_language;
+        let dxc_path = self
+            .config
+            .get_dxc_path(self.watched_files.get_workspace_folder(uri));
+        let language_data = self.language_data.get_mut(&shading_language).unwrap();
+        Ok(language_data.validator.get_info(dxc_path.as_deref()))
+    }
+    /// Warn the user if a language is validated by a fallback validator, as its diagnostics might be inaccurate.
+    pub fn notify_validator_fallback(&self) {
+        for (shading_language, language_data) in &self.language_data {
+            if let Some(fallback) = language_data.validator.get_fallback() {
+                self.connection.send_notification_warning(format!(
+                    "{} validation is using a fallback validator with limited support, diagnostics might be inaccurate. {}",
+                    shading_language.to_string(),
+                    fallback
+                ));
+            }
+        }
+    }
+}
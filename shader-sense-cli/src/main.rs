@@ -9,7 +9,23 @@
 //! -I, --include <PATH>      Add an include directory
 //! -E, --entry-point <NAME>  Specify the shader entry point
 //! -S, --stage <STAGE>       Specify shader stage (vertex, fragment, compute, mesh, task, control, evaluation, geometry)
+//! --dxc-path <DIR>          Use the DXC libraries (dxcompiler & dxil) from this directory for HLSL
+//! --wgsl-profile <PROFILE>  Specify WGSL capability profile (webgpu, native). Default to native
+//! --wgsl-feature <FEATURE>  Enable a WGSL feature (push-constants, subgroups, ray-query)
+//! --wgsl-validation <LIST>  Specify WGSL validation passes separated by commas (expressions, blocks, uniformity, layouts, constants, bindings)
+//! --hlsl-spirv              Target SPIR-V with HLSL
+//! --hlsl-spirv-target <ENV> Specify SPIR-V target environment (vulkan1.0, vulkan1.1, vulkan1.1spirv1.4, vulkan1.2, vulkan1.3, universal1.5)
+//! --hlsl-matrix-packing <ORDER> Specify HLSL matrix packing (column, row)
+//! --hlsl-vulkan-layout <LAYOUT> Specify Vulkan memory layout for SPIR-V (gl, dx, scalar)
+//! --hlsl-warning <NAME>     Enable an HLSL warning (such as conversion)
+//! --hlsl-no-warning <NAME>  Disable an HLSL warning (such as conversion)
+//! --hlsl-warnings-as-errors Treat HLSL warnings as errors
+//! --glsl-suppress-warnings  Suppress GLSL warnings
+//! --glsl-relaxed-errors     Relax GLSL errors
+//! -X, --extra-arg <ARG>     Pass a raw argument to the compiler of the shading language (dxc or glslang)
 //! --validate                Validate the shader
+//! --validate-entry-points   Validate every entry point of the shader with its own stage
+//! --entry-points            List entry points with their stage
 //! --functions               List functions
 //! --includes                List includes
 //! --macros                  List macros
@@ -17,7 +33,7 @@
 //! --constants               List constants
 //! --keywords                List keywords
 //! --types                   List types
-//! --version, -v             Print version information
+//! --version, -v             Print version information and validators used
 //! --help, -h                Print this message
 //! ```
 //!
@@ -36,16 +52,19 @@ use colored::Colorize;
 use shader_sense::{
     shader::{
         GlslCompilationParams, GlslSpirvVersion, GlslTargetClient, HlslCompilationParams,
-        HlslShaderModel, HlslVersion, ShaderCompilationParams, ShaderContextParams, ShaderParams,
-        ShaderStage, ShadingLanguage, WgslCompilationParams,
+        HlslMatrixPacking, HlslShaderModel, HlslSpirvTargetEnv, HlslVersion, HlslVulkanLayout,
+        ShaderCompilationParams, ShaderContextParams, ShaderFilePattern, ShaderParams, ShaderStage,
+        ShaderStageMapping, ShadingLanguage, WgslCapabilityProfile, WgslCompilationParams,
+        WgslValidationFlag,
     },
-    shader_error::ShaderDiagnosticSeverity,
+    shader_error::{ShaderDiagnosticSeverity, ShaderError},
     symbols::{
+        shader_module::ShaderSymbols,
         shader_module_parser::ShaderModuleParser,
         symbol_provider::SymbolProvider,
-        symbols::{ShaderSymbolMode, ShaderSymbolType},
+        symbols::{ShaderEntryPoint, ShaderSymbolMode, ShaderSymbolType},
     },
-    validator::validator::Validator,
+    validator::validator::{Validator, ValidatorBackend},
 };
 
 fn get_version() -> &'static str {
@@ -57,6 +76,129 @@ fn print_version() {
     println!("shader-sense-cli v{}", get_version());
 }
 
+// Print the validator used for each language, as HLSL might fallback on glslang.
+fn print_validators(dxc_path: Option<&Path>) {
+    for shading_language in [
+        ShadingLanguage::Hlsl,
+        ShadingLanguage::Glsl,
+        ShadingLanguage::Wgsl,
+    ] {
+        let validator = match (shading_language, dxc_path) {
+            // Same validator as the one used for validation.
+            #[cfg(not(target_os = "wasi"))]
+            (ShadingLanguage::Hlsl, Some(dxc_path)) => {
+                match Validator::dxc(Some(dxc_path.into())) {
+                    Ok(validator) => validator,
+                    Err(err) => {
+                        println!(
+                            "{}: {}",
+                            shading_language.to_string(),
+                            format!("❌ {}", err).red()
+                        );
+                        continue;
+                    }
+                }
+            }
+            _ => Validator::from_shading_language(shading_language),
+        };
+        let info = validator.get_info();
+        let version = match info.version {
+            Some(version) => format!(" {}", version),
+            None => String::new(),
+        };
+        let dxil_validation = match (info.backend, info.dxil_validator_version) {
+            (ValidatorBackend::Dxc, Some((major, minor))) => {
+                format!(" (DXIL validation {}.{})", major, minor)
+            }
+            (ValidatorBackend::Dxc, None) if info.dxil_validation => " (DXIL validation)".into(),
+            (ValidatorBackend::Dxc, None) => " (no DXIL validation)".into(),
+            _ => String::new(),
+        };
+        println!(
+            "{}: {}{}{}",
+            shading_language.to_string(),
+            info.backend,
+            version,
+            dxil_validation
+        );
+        println!(
+            "  stages: {}",
+            info.supported_stages
+                .iter()
+                .map(|stage| stage.to_string())
+                .collect::<Vec<String>>()
+                .join(", ")
+        );
+        if let Some(fallback) = info.fallback {
+            println!("{}", format!("  ⚠️  fallback: {}", fallback).yellow());
+        }
+    }
+}
+
+// Parse a stage mapping following <PATTERN>=<STAGE>[:<ENTRY>] syntax.
+fn parse_stage_mapping(
+    value: &str,
+    into_pattern: fn(String) -> ShaderFilePattern,
+) -> Option<ShaderStageMapping> {
+    let (pattern, stage) = value.rsplit_once('=')?;
+    let (stage, entry_point) = match stage.split_once(':') {
+        Some((stage, entry_point)) => (stage, Some(entry_point.to_string())),
+        None => (stage, None),
+    };
+    Some(ShaderStageMapping::new(
+        into_pattern(pattern.into()),
+        stage.parse::<ShaderStage>().ok()?,
+        entry_point,
+    ))
+}
+
+// Params used for validation, with params declared in the file, such as #pragma shader_stage.
+fn get_preprocessed_params(shader_params: &ShaderParams, symbols: &ShaderSymbols) -> ShaderParams {
+    ShaderParams {
+        context: shader_params.context.clone(),
+        compilation: symbols
+            .get_preprocessor()
+            .get_compilation_params(&shader_params.compilation),
+    }
+}
+
+// Query symbols of the file to get params declared in it, such as #pragma shader_stage, and its entry points if requested.
+fn preprocess_shader(
+    shading_language: ShadingLanguage,
+    shader_path: &Path,
+    shader_content: &str,
+    shader_params: &ShaderParams,
+    find_entry_points: bool,
+) -> Result<(ShaderParams, Vec<ShaderEntryPoint>), ShaderError> {
+    let mut shader_module_parser = ShaderModuleParser::from_shading_language(shading_language);
+    let symbol_provider = SymbolProvider::from_shading_language(shading_language);
+    let shader_module = shader_module_parser.create_module(shader_path, shader_content)?;
+    let symbols = symbol_provider.query_symbols(
+        &shader_module,
+        shader_params.clone(),
+        &mut |include| {
+            let include_module = shader_module_parser.create_module(
+                &include.get_absolute_path(),
+                std::fs::read_to_string(&include.get_absolute_path())?.as_str(),
+            )?;
+            Ok(Some(Rc::new(RefCell::new(include_module))))
+        },
+        None,
+    )?;
+    let shader_params = get_preprocessed_params(shader_params, &symbols);
+    let entry_points = if find_entry_points {
+        symbol_provider.query_entry_points(
+            &shader_module,
+            &symbols,
+            &shader_params,
+            &mut |path: &Path| std::fs::read_to_string(path).ok(),
+        )?
+    } else {
+        Vec::new()
+    };
+    Ok((shader_params, entry_points))
+}
+
 pub fn usage() {
     print_version();
     println!("Overview: Command line to validate shaders & inspect symbols.");
@@ -70,7 +212,32 @@ pub fn usage() {
     println!("  -I, --include <PATH>      Add an include directory");
     println!("  -E, --entry-point <NAME>  Specify the shader entry point");
     println!("  -S, --stage <STAGE>       Specify shader stage (vertex, fragment, compute, mesh, task, control, evaluation, geometry)");
+    println!("  --stage-mapping <MAP>     Map file names matching a glob to a stage, following <GLOB>=<STAGE>[:<ENTRY>] (such as *.vs.hlsl=vertex)");
+    println!("  --stage-mapping-regex <MAP> Map file names matching a regex to a stage, following <REGEX>=<STAGE>[:<ENTRY>]");
+    println!("  --dxc-path <DIR>          Use the DXC libraries (dxcompiler & dxil) from this directory for HLSL");
+    println!("  --wgsl-profile <PROFILE>  Specify WGSL capability profile (webgpu, native). Default to native");
+    println!(
+        "  --wgsl-feature <FEATURE>  Enable a WGSL feature (push-constants, subgroups, ray-query)"
+    );
+    println!("  --wgsl-validation <LIST>  Specify WGSL validation passes separated by commas (expressions, blocks, uniformity, layouts, constants, bindings)");
+    println!("  --hlsl-spirv              Target SPIR-V with HLSL");
+    println!("  --hlsl-spirv-target <ENV> Specify SPIR-V target environment (vulkan1.0, vulkan1.1, vulkan1.1spirv1.4, vulkan1.2, vulkan1.3, universal1.5)");
+    println!("  --hlsl-matrix-packing <ORDER> Specify HLSL matrix packing (column, row)");
+    println!(
+        "  --hlsl-vulkan-layout <LAYOUT> Specify Vulkan memory layout for SPIR-V (gl, dx, scalar)"
+    );
+    println!("  --hlsl-warning <NAME>     Enable an HLSL warning (such as conversion)");
+    println!("  --hlsl-no-warning <NAME>  Disable an HLSL warning (such as conversion)");
+    println!("  --hlsl-warnings-as-errors Treat HLSL warnings as errors");
+    println!("  --glsl-target <CLIENT>    Specify GLSL target client (vulkan1.0, vulkan1.1, vulkan1.2, vulkan1.3, opengl3.3, opengl4.1, opengl4.5, opengl4.6, opengles1.0, opengles3.0, opengles3.1, opengles3.2)");
+    println!("  --glsl-suppress-warnings  Suppress GLSL warnings");
+    println!("  --glsl-relaxed-errors     Relax GLSL errors");
+    println!("  -X, --extra-arg <ARG>     Pass a raw argument to the compiler of the shading language (dxc or glslang)");
     println!("  --validate                Validate the shader");
+    println!(
+        "  --validate-entry-points   Validate every entry point of the shader with its own stage"
+    );
+    println!("  --entry-points            List entry points with their stage");
     println!("  --functions               List functions");
     println!("  --includes                List includes");
     println!("  --macros                  List macros");
@@ -78,7 +245,7 @@ pub fn usage() {
     println!("  --constants               List constants");
     println!("  --keywords                List keywords");
     println!("  --types                   List types");
-    println!("  --version, -v             Print version information");
+    println!("  --version, -v             Print version information and validators used");
     println!("  --help, -h                Print this message");
     println!();
     println!("Example:");
@@ -90,12 +257,29 @@ pub fn main() {
 
     let mut file_name: Option<String> = None;
     let mut should_validate = false;
+    let mut should_validate_entry_points = false;
+    let mut should_list_entry_points = false;
+    let mut should_print_version = false;
     let mut symbol_type_to_print: HashSet<ShaderSymbolType> = HashSet::new();
     let mut shading_language = ShadingLanguage::Hlsl;
     let mut defines = Vec::new();
     let mut includes = Vec::new();
     let mut entry_point = None;
     let mut shader_stage = None;
+    let mut dxc_path = None;
+    let mut stage_mappings = Vec::new();
+    let mut extra_args = Vec::new();
+    let mut hlsl = HlslCompilationParams {
+        shader_model: HlslShaderModel::ShaderModel6_8,
+        version: HlslVersion::V2018,
+        ..Default::default()
+    };
+    let mut glsl = GlslCompilationParams {
+        client: GlslTargetClient::Vulkan1_3,
+        spirv: GlslSpirvVersion::SPIRV1_6,
+        ..Default::default()
+    };
+    let mut wgsl = WgslCompilationParams::default();
     let _exe = args.next().unwrap();
     while let Some(arg) = args.next() {
         match arg.as_str() {
@@ -148,9 +332,187 @@ pub fn main() {
                     usage();
                 }
             },
+            "--stage-mapping" | "--stage-mapping-regex" => {
+                let into_pattern = if arg == "--stage-mapping" {
+                    ShaderFilePattern::Glob
+                } else {
+                    ShaderFilePattern::Regex
+                };
+                match args.next() {
+                    Some(value) => match parse_stage_mapping(&value, into_pattern) {
+                        Some(stage_mapping) => match stage_mapping.get_pattern().to_regex() {
+                            Ok(_) => stage_mappings.push(stage_mapping),
+                            Err(err) => println!("Invalid stage mapping {}: {}", value, err),
+                        },
+                        None => println!("Invalid stage mapping {}", value),
+                    },
+                    None => {
+                        println!("Missing stage mapping value");
+                        usage();
+                    }
+                }
+            }
+            "--dxc-path" => match args.next() {
+                Some(path) => dxc_path = Some(PathBuf::from(path)),
+                None => {
+                    println!("Missing dxc path value");
+                    usage();
+                }
+            },
+            "--wgsl-profile" => match args.next() {
+                Some(profile) => match profile.as_str() {
+                    "webgpu" => wgsl.profile = WgslCapabilityProfile::WebGpu,
+                    "native" => wgsl.profile = WgslCapabilityProfile::Native,
+                    profile => println!("Unknown wgsl profile {}", profile),
+                },
+                None => {
+                    println!("Missing wgsl profile value");
+                    usage();
+                }
+            },
+            "--wgsl-feature" => match args.next() {
+                Some(feature) => match feature.as_str() {
+                    "shader-f16" => {
+                        println!("Wgsl feature shader-f16 is not supported by naga yet")
+                    }
+                    "push-constants" => wgsl.push_constants = true,
+                    "subgroups" => wgsl.subgroups = true,
+                    "ray-query" => wgsl.ray_query = true,
+                    feature => println!("Unknown wgsl feature {}", feature),
+                },
+                None => {
+                    println!("Missing wgsl feature value");
+                    usage();
+                }
+            },
+            "--wgsl-validation" => match args.next() {
+                Some(flags) => {
+                    wgsl.validation_flags = flags
+                        .split(',')
+                        .filter(|flag| !flag.is_empty())
+                        .filter_map(|flag| match flag.trim() {
+                            "expressions" => Some(WgslValidationFlag::Expressions),
+                            "blocks" => Some(WgslValidationFlag::Blocks),
+                            "uniformity" => Some(WgslValidationFlag::ControlFlowUniformity),
+                            "layouts" => Some(WgslValidationFlag::StructLayouts),
+                            "constants" => Some(WgslValidationFlag::Constants),
+                            "bindings" => Some(WgslValidationFlag::Bindings),
+                            flag => {
+                                println!("Unknown wgsl validation pass {}", flag);
+                                None
+                            }
+                        })
+                        .collect()
+                }
+                None => {
+                    println!("Missing wgsl validation value");
+                    usage();
+                }
+            },
+            "--hlsl-spirv" => {
+                hlsl.spirv = true;
+            }
+            "--hlsl-spirv-target" => match args.next() {
+                Some(target) => match target.as_str() {
+                    "vulkan1.0" => hlsl.spirv_target_env = HlslSpirvTargetEnv::Vulkan1_0,
+                    "vulkan1.1" => hlsl.spirv_target_env = HlslSpirvTargetEnv::Vulkan1_1,
+                    "vulkan1.1spirv1.4" => {
+                        hlsl.spirv_target_env = HlslSpirvTargetEnv::Vulkan1_1Spirv1_4
+                    }
+                    "vulkan1.2" => hlsl.spirv_target_env = HlslSpirvTargetEnv::Vulkan1_2,
+                    "vulkan1.3" => hlsl.spirv_target_env = HlslSpirvTargetEnv::Vulkan1_3,
+                    "universal1.5" => hlsl.spirv_target_env = HlslSpirvTargetEnv::Universal1_5,
+                    target => println!("Unknown spirv target {}", target),
+                },
+                None => {
+                    println!("Missing spirv target value");
+                    usage();
+                }
+            },
+            "--hlsl-matrix-packing" => match args.next() {
+                Some(packing) => match packing.as_str() {
+                    "column" => hlsl.matrix_packing = Some(HlslMatrixPacking::ColumnMajor),
+                    "row" => hlsl.matrix_packing = Some(HlslMatrixPacking::RowMajor),
+                    packing => println!("Unknown matrix packing {}", packing),
+                },
+                None => {
+                    println!("Missing matrix packing value");
+                    usage();
+                }
+            },
+            "--hlsl-vulkan-layout" => match args.next() {
+                Some(layout) => match layout.as_str() {
+                    "gl" => hlsl.vulkan_layout = Some(HlslVulkanLayout::Gl),
+                    "dx" => hlsl.vulkan_layout = Some(HlslVulkanLayout::Dx),
+                    "scalar" => hlsl.vulkan_layout = Some(HlslVulkanLayout::Scalar),
+                    layout => println!("Unknown vulkan layout {}", layout),
+                },
+                None => {
+                    println!("Missing vulkan layout value");
+                    usage();
+                }
+            },
+            "--hlsl-warning" => match args.next() {
+                Some(warning) => hlsl.enabled_warnings.push(warning),
+                None => {
+                    println!("Missing warning value");
+                    usage();
+                }
+            },
+            "--hlsl-no-warning" => match args.next() {
+                Some(warning) => hlsl.disabled_warnings.push(warning),
+                None => {
+                    println!("Missing warning value");
+                    usage();
+                }
+            },
+            "--hlsl-warnings-as-errors" => {
+                hlsl.warnings_as_errors = true;
+            }
+            "--glsl-suppress-warnings" => {
+                glsl.suppress_warnings = true;
+            }
+            "--glsl-target" => match args.next() {
+                Some(client) => match client.as_str() {
+                    "vulkan1.0" => glsl.client = GlslTargetClient::Vulkan1_0,
+                    "vulkan1.1" => glsl.client = GlslTargetClient::Vulkan1_1,
+                    "vulkan1.2" => glsl.client = GlslTargetClient::Vulkan1_2,
+                    "vulkan1.3" => glsl.client = GlslTargetClient::Vulkan1_3,
+                    "opengl3.3" => glsl.client = GlslTargetClient::OpenGL330,
+                    "opengl4.1" => glsl.client = GlslTargetClient::OpenGL410,
+                    "opengl4.5" => glsl.client = GlslTargetClient::OpenGL450,
+                    "opengl4.6" => glsl.client = GlslTargetClient::OpenGL460,
+                    "opengles1.0" => glsl.client = GlslTargetClient::OpenGLES100,
+                    "opengles3.0" => glsl.client = GlslTargetClient::OpenGLES300,
+                    "opengles3.1" => glsl.client = GlslTargetClient::OpenGLES310,
+                    "opengles3.2" => glsl.client = GlslTargetClient::OpenGLES320,
+                    client => println!("Unknown glsl target client {}", client),
+                },
+                None => {
+                    println!("Missing glsl target client value");
+                    usage();
+                }
+            },
+            "--glsl-relaxed-errors" => {
+                glsl.relaxed_errors = true;
+            }
+            "-X" | "--extra-arg" => match args.next() {
+                Some(extra_arg) => extra_args.push(extra_arg),
+                None => {
+                    println!("Missing extra arg value");
+                    usage();
+                }
+            },
             "--validate" => {
                 should_validate = true;
             }
+            "--validate-entry-points" => {
+                should_validate = true;
+                should_validate_entry_points = true;
+            }
+            "--entry-points" => {
+                should_list_entry_points = true;
+            }
             "--functions" => {
                 symbol_type_to_print.insert(ShaderSymbolType::Functions);
             }
@@ -173,7 +535,7 @@ pub fn main() {
                 symbol_type_to_print.insert(ShaderSymbolType::Types);
             }
             "--version" | "-v" => {
-                print_version();
+                should_print_version = true;
             }
             "--help" | "-h" => {
                 usage();
@@ -186,6 +548,21 @@ pub fn main() {
             },
         }
     }
+    // Extra args follow the syntax of the compiler of the shading language, known once parsed.
+    match shading_language {
+        ShadingLanguage::Hlsl => hlsl.extra_args = extra_args,
+        ShadingLanguage::Glsl => glsl.extra_args = extra_args,
+        ShadingLanguage::Wgsl => {
+            if !extra_args.is_empty() {
+                println!("Extra args are not supported with wgsl, ignoring them.");
+            }
+        }
+    }
+    // Printed once all arguments are parsed, to take --dxc-path into account.
+    if should_print_version {
+        print_version();
+        print_validators(dxc_path.as_deref());
+    }
     match file_name {
         Some(file_name) => {
             let shader_params = ShaderParams {
@@ -197,29 +574,50 @@ pub fn main() {
                 compilation: ShaderCompilationParams {
                     entry_point: entry_point,
                     shader_stage: shader_stage,
-                    hlsl: HlslCompilationParams {
-                        shader_model: HlslShaderModel::ShaderModel6_8,
-                        version: HlslVersion::V2018,
-                        enable16bit_types: false,
-                        spirv: false,
-                    },
-                    glsl: GlslCompilationParams {
-                        client: GlslTargetClient::Vulkan1_3,
-                        spirv: GlslSpirvVersion::SPIRV1_6,
-                    },
-                    wgsl: WgslCompilationParams {},
+                    stage_mappings,
+                    hlsl,
+                    glsl,
+                    wgsl,
                 },
             };
             let shader_path = Path::new(&file_name);
             let shader_content = std::fs::read_to_string(shader_path).unwrap();
             // By default validate (if we dont parse symbols)
-            if should_validate || symbol_type_to_print.is_empty() {
+            let should_query_symbols = !symbol_type_to_print.is_empty() || should_list_entry_points;
+            if should_validate || !should_query_symbols {
                 // Validator intended to validate a file using standard API.
-                let validator = Validator::from_shading_language(shading_language);
-                match validator.validate_shader(
-                    &shader_content,
+                let validator = match (shading_language, dxc_path) {
+                    // Do not fallback on glslang if user requested a specific DXC.
+                    #[cfg(not(target_os = "wasi"))]
+                    (ShadingLanguage::Hlsl, Some(dxc_path)) => {
+                        match Validator::dxc(Some(dxc_path)) {
+                            Ok(validator) => validator,
+                            Err(err) => {
+                                println!("{}", format!("❌ {}", err).red().bold());
+                                return;
+                            }
+                        }
+                    }
+                    _ => Validator::from_shading_language(shading_language),
+                };
+                let (validation_params, entry_points) = match preprocess_shader(
+                    shading_language,
                     shader_path,
+                    &shader_content,
                     &shader_params,
+                    should_validate_entry_points,
+                ) {
+                    Ok(preprocessed) => preprocessed,
+                    Err(err) => {
+                        println!("Failed to preprocess shader: {:#?}", err);
+                        return;
+                    }
+                };
+                match validator.validate_entry_points(
+                    &shader_content,
+                    shader_path,
+                    &validation_params,
+                    &entry_points,
                     &mut |path: &Path| Some(std::fs::read_to_string(path).unwrap()),
                 ) {
                     Ok(diagnostic_list) => {
@@ -254,14 +652,44 @@ pub fn main() {
                                         format!("💡 Hint at {}", formatted_path).blue().bold()
                                     }
                                 };
-                                println!("{}\n{}", header, diagnostic.error.italic());
+                                if diagnostic.entry_points.is_empty() {
+                                    println!("{}\n{}", header, diagnostic.error.italic());
+                                } else {
+                                    let entry_points = diagnostic
+                                        .entry_points
+                                        .iter()
+                                        .map(|entry_point| {
+                                            format!(
+                                                "{}, {}",
+                                                entry_point.label,
+                                                entry_point.stage.to_string()
+                                            )
+                                        })
+                                        .collect::<Vec<String>>()
+                                        .join("; ");
+                                    println!(
+                                        "{} {}\n{}",
+                                        header,
+                                        format!("(entry point {})", entry_points).blue(),
+                                        diagnostic.error.italic()
+                                    );
+                                }
+                                for related in diagnostic.related_information {
+                                    println!(
+                                        "  note at {}:{}:{}: {}",
+                                        related.range.file_path.file_name().unwrap().display(),
+                                        related.range.start().line,
+                                        related.range.start().pos,
+                                        related.message.italic()
+                                    );
+                                }
                             }
                         }
                     }
                     Err(err) => println!("Failed to validate file: {:#?}", err),
                 }
             }
-            if !symbol_type_to_print.is_empty() {
+            if should_query_symbols {
                 // SymbolProvider intended to gather file symbol at runtime by inspecting the AST.
                 let mut shader_module_parser =
                     ShaderModuleParser::from_shading_language(shading_language);
@@ -271,7 +699,7 @@ pub fn main() {
                         let symbols = symbol_provider
                             .query_symbols(
                                 &shader_module,
-                                shader_params,
+                                shader_params.clone(),
                                 &mut |include| {
                                     let include_module = shader_module_parser.create_module(
                                         &include.get_absolute_path(),
@@ -284,6 +712,44 @@ pub fn main() {
                                 None,
                             )
                             .unwrap();
+                        if should_list_entry_points {
+                            // Same params as validation, so that stages match the validated ones.
+                            let entry_point_params =
+                                get_preprocessed_params(&shader_params, &symbols);
+                            match symbol_provider.query_entry_points(
+                                &shader_module,
+                                &symbols,
+                                &entry_point_params,
+                                &mut |path: &Path| std::fs::read_to_string(path).ok(),
+                            ) {
+                                Ok(entry_points) if entry_points.is_empty() => {
+                                    println!("{}", "⚠️  Couldn't find any entry point".yellow())
+                                }
+                                Ok(entry_points) => {
+                                    for entry_point in entry_points {
+                                        let header = format!(
+                                            "{}:{}:{}",
+                                            entry_point
+                                                .range
+                                                .file_path
+                                                .file_name()
+                                                .unwrap()
+                                                .display(),
+                                            entry_point.range.start().line,
+                                            entry_point.range.start().pos
+                                        );
+                                        println!(
+                                            "{} {} {} ({})",
+                                            "🚀 Entry point".yellow(),
+                                            header.blue(),
+                                            entry_point.label.italic(),
+                                            entry_point.stage.to_string()
+                                        );
+                                    }
+                                }
+                                Err(err) => println!("Failed to find entry points: {:#?}", err),
+                            }
+                        }
                         let symbol_list = symbols.get_all_symbols();
                         let mut found_some_symbols = false;
                         for symbol in symbol_list.iter() {
@@ -322,7 +788,7 @@ pub fn main() {
                             found_some_symbols = true;
                             println!("{} {} {}", icon, header.blue(), symbol.format().italic());
                         }
-                        if !found_some_symbols {
+                        if !found_some_symbols && !symbol_type_to_print.is_empty() {
                             fn get_type_string(ty: &ShaderSymbolType) -> &'static str {
                                 match ty {
                                     ShaderSymbolType::Types => "types",
@@ -80,6 +80,10 @@ impl IncludeHandler {
     pub fn get_includes(&self) -> &HashSet<PathBuf> {
         &self.includes
     }
+    /// Get the path remapping of handler
+    pub fn get_path_remapping(&self) -> &HashMap<PathBuf, PathBuf> {
+        &self.path_remapping
+    }
     /// Get the number of time a file has been visited
     pub fn get_visited_count(&self, path: &Path) -> usize {
         self.visited_dependencies.get(path).cloned().unwrap_or(0)
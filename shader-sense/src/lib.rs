@@ -65,6 +65,7 @@
 
 pub mod include;
 pub mod position;
+pub mod reflection;
 pub mod shader;
 pub mod shader_error;
 pub mod symbols;
@@ -0,0 +1,262 @@
+//! Reflection from DXIL containers.
+//!
+//! Read the pipeline state validation (PSV0) and signature (ISG1, OSG1) parts
+//! of the container, as the DXC reflection interface is not exposed by hassle-rs.
+
+use crate::{shader::ShaderStage, shader_error::ShaderError};
+
+use super::{
+    ShaderEntryPointReflection, ShaderInterfaceVariable, ShaderReflection, ShaderResourceBinding,
+    ShaderResourceKind,
+};
+
+// https://github.com/microsoft/DirectXShaderCompiler/blob/main/include/dxc/DxilContainer/DxilContainer.h
+const DXIL_CONTAINER_HEADER_SIZE: usize = 32;
+const DXIL_PART_HEADER_SIZE: usize = 8;
+const DXIL_SIGNATURE_ELEMENT_SIZE: usize = 32;
+
+const fn fourcc(code: &[u8; 4]) -> u32 {
+    u32::from_le_bytes(*code)
+}
+const DFCC_CONTAINER: u32 = fourcc(b"DXBC");
+const DFCC_PIPELINE_STATE_VALIDATION: u32 = fourcc(b"PSV0");
+const DFCC_INPUT_SIGNATURE: u32 = fourcc(b"ISG1");
+const DFCC_OUTPUT_SIGNATURE: u32 = fourcc(b"OSG1");
+
+// https://github.com/microsoft/DirectXShaderCompiler/blob/main/include/dxc/DxilContainer/DxilPipelineStateValidation.h
+const PSV_RUNTIME_INFO_1_SIZE: usize = 36;
+const PSV_RUNTIME_INFO_2_SIZE: usize = 48;
+const PSV_RUNTIME_INFO_3_SIZE: usize = 52;
+const PSV_RESOURCE_BIND_INFO_0_SIZE: usize = 16;
+const PSV_RESOURCE_BIND_INFO_1_SIZE: usize = 24;
+
+const RESOURCE_KIND_TYPED_BUFFER: u32 = 10;
+const RESOURCE_KIND_RT_ACCELERATION_STRUCTURE: u32 = 16;
+
+// Add a size read from the container to an offset, as corrupted sizes might overflow.
+fn add_offset(offset: usize, size: usize) -> Result<usize, ShaderError> {
+    offset.checked_add(size).ok_or_else(|| {
+        ShaderError::InternalErr(format!(
+            "Invalid DXIL container: size {} at offset {} overflows.",
+            size, offset
+        ))
+    })
+}
+
+fn read_u32(data: &[u8], offset: usize) -> Result<u32, ShaderError> {
+    match data.get(offset..add_offset(offset, 4)?) {
+        Some(bytes) => Ok(u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]])),
+        None => Err(ShaderError::InternalErr(format!(
+            "Invalid DXIL container: unexpected end of data at offset {}.",
+            offset
+        ))),
+    }
+}
+
+fn read_string(data: &[u8], offset: usize) -> Option<String> {
+    let bytes = data.get(offset..)?;
+    let end = bytes.iter().position(|byte| *byte == 0)?;
+    Some(String::from_utf8_lossy(&bytes[..end]).into())
+}
+
+fn get_parts(container: &[u8]) -> Result<Vec<(u32, &[u8])>, ShaderError> {
+    if read_u32(container, 0)? != DFCC_CONTAINER {
+        return Err(ShaderError::InternalErr(
+            "Invalid DXIL container: bad header.".into(),
+        ));
+    }
+    let part_count = read_u32(container, DXIL_CONTAINER_HEADER_SIZE - 4)? as usize;
+    let mut parts = Vec::with_capacity(part_count);
+    for part_index in 0..part_count {
+        let part_offset =
+            read_u32(container, DXIL_CONTAINER_HEADER_SIZE + part_index * 4)? as usize;
+        let fourcc = read_u32(container, part_offset)?;
+        let part_size = read_u32(container, add_offset(part_offset, 4)?)? as usize;
+        let part_begin = add_offset(part_offset, DXIL_PART_HEADER_SIZE)?;
+        match container.get(part_begin..add_offset(part_begin, part_size)?) {
+            Some(part) => parts.push((fourcc, part)),
+            None => {
+                return Err(ShaderError::InternalErr(format!(
+                    "Invalid DXIL container: part {} out of bounds.",
+                    part_index
+                )))
+            }
+        }
+    }
+    Ok(parts)
+}
+
+fn get_stage(shader_kind: u8) -> Option<ShaderStage> {
+    match shader_kind {
+        0 => Some(ShaderStage::Fragment),
+        1 => Some(ShaderStage::Vertex),
+        2 => Some(ShaderStage::Geometry),
+        3 => Some(ShaderStage::TesselationControl),
+        4 => Some(ShaderStage::TesselationEvaluation),
+        5 => Some(ShaderStage::Compute),
+        7 => Some(ShaderStage::RayGeneration),
+        8 => Some(ShaderStage::Intersect),
+        9 => Some(ShaderStage::AnyHit),
+        10 => Some(ShaderStage::ClosestHit),
+        11 => Some(ShaderStage::Miss),
+        12 => Some(ShaderStage::Callable),
+        13 => Some(ShaderStage::Mesh),
+        14 => Some(ShaderStage::Task),
+        _ => None, // Library, node or invalid.
+    }
+}
+
+fn get_resource_kind(resource_type: u32, resource_kind: Option<u32>) -> Option<ShaderResourceKind> {
+    match resource_type {
+        1 => Some(ShaderResourceKind::Sampler),
+        2 => Some(ShaderResourceKind::UniformBuffer),
+        3 => match resource_kind {
+            Some(RESOURCE_KIND_TYPED_BUFFER) => Some(ShaderResourceKind::TexelBuffer),
+            _ => Some(ShaderResourceKind::SampledTexture),
+        },
+        4 => match resource_kind {
+            Some(RESOURCE_KIND_RT_ACCELERATION_STRUCTURE) => {
+                Some(ShaderResourceKind::AccelerationStructure)
+            }
+            _ => Some(ShaderResourceKind::ReadOnlyStorageBuffer),
+        },
+        5 => Some(ShaderResourceKind::ReadOnlyStorageBuffer),
+        6 => match resource_kind {
+            Some(RESOURCE_KIND_TYPED_BUFFER) => Some(ShaderResourceKind::StorageTexelBuffer),
+            _ => Some(ShaderResourceKind::StorageTexture),
+        },
+        7..=9 => Some(ShaderResourceKind::StorageBuffer),
+        _ => None,
+    }
+}
+
+// Parse an ISG1 or OSG1 part.
+fn parse_signature(part: &[u8]) -> Result<Vec<ShaderInterfaceVariable>, ShaderError> {
+    let element_count = read_u32(part, 0)? as usize;
+    let element_offset = read_u32(part, 4)? as usize;
+    let mut variables = Vec::with_capacity(element_count);
+    for element_index in 0..element_count {
+        let offset = add_offset(element_offset, element_index * DXIL_SIGNATURE_ELEMENT_SIZE)?;
+        let semantic_name = read_string(part, read_u32(part, offset + 4)? as usize);
+        let semantic_index = read_u32(part, offset + 8)?;
+        let system_value = read_u32(part, offset + 12)?;
+        let register = read_u32(part, offset + 20)?;
+        let name = semantic_name.map(|name| {
+            if semantic_index > 0 {
+                format!("{}{}", name, semantic_index)
+            } else {
+                name
+            }
+        });
+        variables.push(ShaderInterfaceVariable {
+            built_in: if system_value != 0 {
+                name.clone()
+            } else {
+                None
+            },
+            name,
+            location: Some(register),
+        });
+    }
+    Ok(variables)
+}
+
+impl ShaderReflection {
+    /// Reflect the interface of a DXIL container.
+    /// Library containers do not hold any pipeline state and return an empty reflection.
+    pub fn from_dxil(container: &[u8]) -> Result<Self, ShaderError> {
+        let mut reflection = ShaderReflection::default();
+        let mut entry_point = ShaderEntryPointReflection {
+            name: None,
+            stage: None,
+            inputs: Vec::new(),
+            outputs: Vec::new(),
+            workgroup_size: None,
+        };
+        let mut has_pipeline_state = false;
+        for (fourcc, part) in get_parts(container)? {
+            match fourcc {
+                DFCC_INPUT_SIGNATURE => entry_point.inputs = parse_signature(part)?,
+                DFCC_OUTPUT_SIGNATURE => entry_point.outputs = parse_signature(part)?,
+                DFCC_PIPELINE_STATE_VALIDATION => {
+                    has_pipeline_state = true;
+                    let runtime_info_size = read_u32(part, 0)? as usize;
+                    let runtime_info_end = add_offset(4, runtime_info_size)?;
+                    let runtime_info = part.get(4..runtime_info_end).ok_or_else(|| {
+                        ShaderError::InternalErr("Invalid PSV0 part: bad runtime info.".into())
+                    })?;
+                    if runtime_info_size >= PSV_RUNTIME_INFO_1_SIZE {
+                        entry_point.stage = get_stage(runtime_info[24]);
+                    }
+                    if runtime_info_size >= PSV_RUNTIME_INFO_2_SIZE {
+                        entry_point.workgroup_size = match entry_point.stage {
+                            Some(ShaderStage::Compute)
+                            | Some(ShaderStage::Mesh)
+                            | Some(ShaderStage::Task) => Some([
+                                read_u32(runtime_info, 36)?,
+                                read_u32(runtime_info, 40)?,
+                                read_u32(runtime_info, 44)?,
+                            ]),
+                            _ => None,
+                        };
+                    }
+                    let mut offset = runtime_info_end;
+                    let resource_count = read_u32(part, offset)? as usize;
+                    offset += 4;
+                    if resource_count > 0 {
+                        let bind_info_size = read_u32(part, offset)? as usize;
+                        offset += 4;
+                        if bind_info_size < PSV_RESOURCE_BIND_INFO_0_SIZE {
+                            return Err(ShaderError::InternalErr(
+                                "Invalid PSV0 part: bad resource bind info size.".into(),
+                            ));
+                        }
+                        for _ in 0..resource_count {
+                            let resource_type = read_u32(part, offset)?;
+                            let space = read_u32(part, offset + 4)?;
+                            let lower_bound = read_u32(part, offset + 8)?;
+                            let upper_bound = read_u32(part, offset + 12)?;
+                            let resource_kind = if bind_info_size >= PSV_RESOURCE_BIND_INFO_1_SIZE {
+                                Some(read_u32(part, offset + 16)?)
+                            } else {
+                                None
+                            };
+                            offset = add_offset(offset, bind_info_size)?;
+                            let kind = match get_resource_kind(resource_type, resource_kind) {
+                                Some(kind) => kind,
+                                None => continue,
+                            };
+                            reflection.resources.push(ShaderResourceBinding {
+                                name: None,
+                                set: space,
+                                binding: lower_bound,
+                                kind,
+                                array_size: if upper_bound == u32::MAX {
+                                    Some(0) // Unbounded
+                                } else if upper_bound > lower_bound {
+                                    Some(upper_bound - lower_bound + 1)
+                                } else {
+                                    None
+                                },
+                            });
+                        }
+                    }
+                    if runtime_info_size >= PSV_RUNTIME_INFO_3_SIZE {
+                        // Entry name is stored in string table following resources.
+                        let string_table_size = read_u32(part, offset)? as usize;
+                        let string_table = part
+                            .get(offset + 4..add_offset(offset + 4, string_table_size)?)
+                            .unwrap_or(&[]);
+                        entry_point.name =
+                            read_string(string_table, read_u32(runtime_info, 48)? as usize);
+                    }
+                }
+                _ => {}
+            }
+        }
+        if has_pipeline_state {
+            reflection.entry_points.push(entry_point);
+        }
+        Ok(reflection)
+    }
+}
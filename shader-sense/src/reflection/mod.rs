@@ -0,0 +1,92 @@
+//! Resource interface reflection of compiled shaders.
+//!
+//! Reflection can be extracted from SPIR-V binaries (glslang, DXC with `-spirv`),
+//! from DXIL containers (DXC) and from naga modules (WGSL).
+
+mod dxil;
+mod naga;
+mod spirv;
+
+use serde::{Deserialize, Serialize};
+
+use crate::shader::ShaderStage;
+
+/// Kind of a resource bound to the pipeline.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[serde(rename_all = "camelCase")]
+pub enum ShaderResourceKind {
+    UniformBuffer,          // cbuffer, uniform block
+    StorageBuffer,          // RWStructuredBuffer, RWByteAddressBuffer, buffer block
+    ReadOnlyStorageBuffer,  // StructuredBuffer, ByteAddressBuffer, readonly buffer block
+    SampledTexture,         // Texture2D, texture2D
+    StorageTexture,         // RWTexture2D, image2D
+    TexelBuffer,            // Buffer, samplerBuffer
+    StorageTexelBuffer,     // RWBuffer, imageBuffer
+    Sampler,                // SamplerState, sampler
+    CombinedTextureSampler, // sampler2D
+    InputAttachment,        // SubpassInput, subpassInput
+    AccelerationStructure,  // RaytracingAccelerationStructure, accelerationStructureEXT
+}
+
+/// A resource bound to the pipeline.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct ShaderResourceBinding {
+    pub name: Option<String>, // Not available with DXIL.
+    pub set: u32,             // Descriptor set for SPIR-V, register space for DXIL, group for WGSL.
+    pub binding: u32,         // Binding for SPIR-V & WGSL, register for DXIL.
+    pub kind: ShaderResourceKind,
+    pub array_size: Option<u32>, // None if not an array, 0 if unbounded.
+}
+
+/// A stage input or output.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct ShaderInterfaceVariable {
+    pub name: Option<String>, // Variable name for SPIR-V & WGSL, semantic for DXIL.
+    pub location: Option<u32>, // Location for SPIR-V & WGSL, register for DXIL.
+    pub built_in: Option<String>, // Built-in or system value, if any.
+}
+
+/// A push constant block.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct ShaderPushConstant {
+    pub name: Option<String>,
+    pub size: u32, // Size in bytes.
+}
+
+/// Interface of a single entry point.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct ShaderEntryPointReflection {
+    pub name: Option<String>, // Not available with old DXIL containers.
+    pub stage: Option<ShaderStage>,
+    pub inputs: Vec<ShaderInterfaceVariable>,
+    pub outputs: Vec<ShaderInterfaceVariable>,
+    pub workgroup_size: Option<[u32; 3]>, // Only for compute, mesh & task stages.
+}
+
+/// Resource interface of a compiled shader.
+#[derive(Debug, Default, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct ShaderReflection {
+    pub resources: Vec<ShaderResourceBinding>,
+    pub push_constants: Vec<ShaderPushConstant>,
+    pub entry_points: Vec<ShaderEntryPointReflection>,
+}
+
+impl ShaderReflection {
+    /// Find a resource from its set & binding.
+    pub fn find_resource(&self, set: u32, binding: u32) -> Option<&ShaderResourceBinding> {
+        self.resources
+            .iter()
+            .find(|resource| resource.set == set && resource.binding == binding)
+    }
+    /// Find an entry point from its name.
+    pub fn find_entry_point(&self, name: &str) -> Option<&ShaderEntryPointReflection> {
+        self.entry_points
+            .iter()
+            .find(|entry_point| entry_point.name.as_deref() == Some(name))
+    }
+}
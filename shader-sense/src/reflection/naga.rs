@@ -0,0 +1,157 @@
+//! Reflection from naga modules.
+
+use naga::{AddressSpace, ArraySize, Binding, ImageClass, Module, StorageAccess, Type, TypeInner};
+
+use crate::shader::ShaderStage;
+
+use super::{
+    ShaderEntryPointReflection, ShaderInterfaceVariable, ShaderPushConstant, ShaderReflection,
+    ShaderResourceBinding, ShaderResourceKind,
+};
+
+fn get_array_size(size: ArraySize) -> u32 {
+    match size {
+        ArraySize::Constant(size) => size.get(),
+        ArraySize::Pending(_) | ArraySize::Dynamic => 0,
+    }
+}
+
+fn get_resource_kind(space: AddressSpace, inner: &TypeInner) -> Option<ShaderResourceKind> {
+    match space {
+        AddressSpace::Uniform => Some(ShaderResourceKind::UniformBuffer),
+        AddressSpace::Storage { access } => Some(if access.contains(StorageAccess::STORE) {
+            ShaderResourceKind::StorageBuffer
+        } else {
+            ShaderResourceKind::ReadOnlyStorageBuffer
+        }),
+        AddressSpace::Handle => match inner {
+            TypeInner::Sampler { .. } => Some(ShaderResourceKind::Sampler),
+            TypeInner::AccelerationStructure => Some(ShaderResourceKind::AccelerationStructure),
+            TypeInner::Image { class, .. } => Some(match class {
+                ImageClass::Storage { .. } => ShaderResourceKind::StorageTexture,
+                ImageClass::Sampled { .. } | ImageClass::Depth { .. } => {
+                    ShaderResourceKind::SampledTexture
+                }
+            }),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+fn get_built_in_name(built_in: naga::BuiltIn) -> String {
+    match built_in {
+        naga::BuiltIn::Position { .. } => "Position".into(),
+        built_in => format!("{:?}", built_in),
+    }
+}
+
+// Flatten IO structs into their bound members.
+fn get_interface_variables(
+    module: &Module,
+    name: Option<&String>,
+    ty: naga::Handle<Type>,
+    binding: Option<&Binding>,
+    variables: &mut Vec<ShaderInterfaceVariable>,
+) {
+    match binding {
+        Some(Binding::BuiltIn(built_in)) => variables.push(ShaderInterfaceVariable {
+            name: name.cloned(),
+            location: None,
+            built_in: Some(get_built_in_name(*built_in)),
+        }),
+        Some(Binding::Location { location, .. }) => variables.push(ShaderInterfaceVariable {
+            name: name.cloned(),
+            location: Some(*location),
+            built_in: None,
+        }),
+        None => {
+            if let TypeInner::Struct { members, .. } = &module.types[ty].inner {
+                for member in members {
+                    get_interface_variables(
+                        module,
+                        member.name.as_ref(),
+                        member.ty,
+                        member.binding.as_ref(),
+                        variables,
+                    );
+                }
+            }
+        }
+    }
+}
+
+impl ShaderReflection {
+    /// Reflect the interface of a validated naga module.
+    pub fn from_naga(module: &Module) -> Self {
+        let mut reflection = ShaderReflection::default();
+        for (_, variable) in module.global_variables.iter() {
+            if variable.space == AddressSpace::PushConstant {
+                reflection.push_constants.push(ShaderPushConstant {
+                    name: variable.name.clone(),
+                    size: module.types[variable.ty].inner.size(module.to_ctx()),
+                });
+                continue;
+            }
+            let binding = match &variable.binding {
+                Some(binding) => binding,
+                None => continue,
+            };
+            let (inner, array_size) = match &module.types[variable.ty].inner {
+                TypeInner::BindingArray { base, size } => {
+                    (&module.types[*base].inner, Some(get_array_size(*size)))
+                }
+                inner => (inner, None),
+            };
+            let kind = match get_resource_kind(variable.space, inner) {
+                Some(kind) => kind,
+                None => continue,
+            };
+            reflection.resources.push(ShaderResourceBinding {
+                name: variable.name.clone(),
+                set: binding.group,
+                binding: binding.binding,
+                kind,
+                array_size,
+            });
+        }
+        for entry_point in &module.entry_points {
+            let mut inputs = Vec::new();
+            for argument in &entry_point.function.arguments {
+                get_interface_variables(
+                    module,
+                    argument.name.as_ref(),
+                    argument.ty,
+                    argument.binding.as_ref(),
+                    &mut inputs,
+                );
+            }
+            let mut outputs = Vec::new();
+            if let Some(result) = &entry_point.function.result {
+                get_interface_variables(
+                    module,
+                    None,
+                    result.ty,
+                    result.binding.as_ref(),
+                    &mut outputs,
+                );
+            }
+            let stage = match entry_point.stage {
+                naga::ShaderStage::Vertex => ShaderStage::Vertex,
+                naga::ShaderStage::Fragment => ShaderStage::Fragment,
+                naga::ShaderStage::Compute => ShaderStage::Compute,
+            };
+            reflection.entry_points.push(ShaderEntryPointReflection {
+                name: Some(entry_point.name.clone()),
+                stage: Some(stage),
+                inputs,
+                outputs,
+                workgroup_size: match stage {
+                    ShaderStage::Compute => Some(entry_point.workgroup_size),
+                    _ => None,
+                },
+            });
+        }
+        reflection
+    }
+}
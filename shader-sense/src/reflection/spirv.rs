@@ -0,0 +1,509 @@
+//! Reflection from SPIR-V binaries.
+
+use std::collections::HashMap;
+
+use spirv::{BuiltIn, Decoration, Dim, ExecutionMode, ExecutionModel, Op, StorageClass};
+
+use crate::{shader::ShaderStage, shader_error::ShaderError};
+
+use super::{
+    ShaderEntryPointReflection, ShaderInterfaceVariable, ShaderPushConstant, ShaderReflection,
+    ShaderResourceBinding, ShaderResourceKind,
+};
+
+enum SpirvType {
+    Scalar(u32), // Width in bits
+    Vector(u32, u32),
+    Matrix(u32, u32),
+    Image { dim: Option<Dim>, sampled: u32 },
+    Sampler,
+    SampledImage,
+    Array(u32, u32), // Element type, length constant id
+    RuntimeArray(u32),
+    Struct(Vec<u32>),
+    Pointer(u32), // Pointee type
+    AccelerationStructure,
+}
+
+#[derive(Default)]
+struct SpirvDecorations {
+    buffer_block: bool,
+    non_writable: bool,
+    array_stride: Option<u32>,
+    built_in: Option<u32>,
+    location: Option<u32>,
+    binding: Option<u32>,
+    descriptor_set: Option<u32>,
+    // Per member decorations
+    member_offsets: HashMap<u32, u32>,
+    member_matrix_strides: HashMap<u32, u32>,
+    member_built_ins: HashMap<u32, u32>,
+    member_non_writable: Vec<u32>,
+}
+
+struct SpirvEntryPoint {
+    id: u32,
+    execution_model: Option<ExecutionModel>,
+    name: String,
+    interface: Vec<u32>,
+}
+
+#[derive(Default)]
+struct SpirvModule {
+    names: HashMap<u32, String>,
+    decorations: HashMap<u32, SpirvDecorations>,
+    types: HashMap<u32, SpirvType>,
+    constants: HashMap<u32, u32>,
+    variables: Vec<(u32, u32, StorageClass)>, // Id, pointer type, storage class
+    entry_points: Vec<SpirvEntryPoint>,
+    workgroup_sizes: HashMap<u32, [u32; 3]>,
+    workgroup_size_ids: HashMap<u32, [u32; 3]>,
+}
+
+fn read_string(words: &[u32]) -> (String, usize) {
+    let mut bytes = Vec::new();
+    for (index, word) in words.iter().enumerate() {
+        for byte in word.to_le_bytes() {
+            if byte == 0 {
+                return (String::from_utf8_lossy(&bytes).into(), index + 1);
+            }
+            bytes.push(byte);
+        }
+    }
+    (String::from_utf8_lossy(&bytes).into(), words.len())
+}
+
+fn get_stage(execution_model: ExecutionModel) -> Option<ShaderStage> {
+    match execution_model {
+        ExecutionModel::Vertex => Some(ShaderStage::Vertex),
+        ExecutionModel::TessellationControl => Some(ShaderStage::TesselationControl),
+        ExecutionModel::TessellationEvaluation => Some(ShaderStage::TesselationEvaluation),
+        ExecutionModel::Geometry => Some(ShaderStage::Geometry),
+        ExecutionModel::Fragment => Some(ShaderStage::Fragment),
+        ExecutionModel::GLCompute => Some(ShaderStage::Compute),
+        ExecutionModel::TaskNV | ExecutionModel::TaskEXT => Some(ShaderStage::Task),
+        ExecutionModel::MeshNV | ExecutionModel::MeshEXT => Some(ShaderStage::Mesh),
+        ExecutionModel::RayGenerationKHR => Some(ShaderStage::RayGeneration),
+        ExecutionModel::IntersectionKHR => Some(ShaderStage::Intersect),
+        ExecutionModel::AnyHitKHR => Some(ShaderStage::AnyHit),
+        ExecutionModel::ClosestHitKHR => Some(ShaderStage::ClosestHit),
+        ExecutionModel::MissKHR => Some(ShaderStage::Miss),
+        ExecutionModel::CallableKHR => Some(ShaderStage::Callable),
+        ExecutionModel::Kernel => None,
+    }
+}
+
+fn get_built_in_name(built_in: u32) -> String {
+    match BuiltIn::from_u32(built_in) {
+        Some(built_in) => format!("{:?}", built_in),
+        None => format!("BuiltIn{}", built_in),
+    }
+}
+
+impl SpirvModule {
+    fn parse(words: &[u32]) -> Result<Self, ShaderError> {
+        if words.len() < 5 || words[0] != spirv::MAGIC_NUMBER {
+            return Err(ShaderError::InternalErr(
+                "Invalid SPIR-V binary: bad header.".into(),
+            ));
+        }
+        let mut module = SpirvModule::default();
+        let mut offset = 5;
+        while offset < words.len() {
+            let word_count = (words[offset] >> 16) as usize;
+            let opcode = words[offset] & 0xffff;
+            if word_count == 0 || offset + word_count > words.len() {
+                return Err(ShaderError::InternalErr(format!(
+                    "Invalid SPIR-V binary: bad instruction at word {}.",
+                    offset
+                )));
+            }
+            let operands = &words[offset + 1..offset + word_count];
+            if let Some(opcode) = Op::from_u32(opcode) {
+                module.parse_instruction(opcode, operands);
+            }
+            offset += word_count;
+        }
+        Ok(module)
+    }
+    fn parse_instruction(&mut self, opcode: Op, operands: &[u32]) {
+        // Ignore malformed instructions with missing operands.
+        let operand = |index: usize| operands.get(index).cloned().unwrap_or(0);
+        match opcode {
+            Op::Name if operands.len() >= 2 => {
+                let (name, _) = read_string(&operands[1..]);
+                if !name.is_empty() {
+                    self.names.insert(operands[0], name);
+                }
+            }
+            Op::EntryPoint if operands.len() >= 3 => {
+                let (name, length) = read_string(&operands[2..]);
+                self.entry_points.push(SpirvEntryPoint {
+                    id: operands[1],
+                    execution_model: ExecutionModel::from_u32(operands[0]),
+                    name,
+                    interface: operands[2 + length..].to_vec(),
+                });
+            }
+            Op::ExecutionMode
+                if operands.len() >= 5
+                    && ExecutionMode::from_u32(operands[1]) == Some(ExecutionMode::LocalSize) =>
+            {
+                self.workgroup_sizes
+                    .insert(operands[0], [operands[2], operands[3], operands[4]]);
+            }
+            Op::ExecutionModeId
+                if operands.len() >= 5
+                    && ExecutionMode::from_u32(operands[1]) == Some(ExecutionMode::LocalSizeId) =>
+            {
+                self.workgroup_size_ids
+                    .insert(operands[0], [operands[2], operands[3], operands[4]]);
+            }
+            Op::TypeBool if !operands.is_empty() => {
+                self.types.insert(operands[0], SpirvType::Scalar(32));
+            }
+            Op::TypeInt | Op::TypeFloat if operands.len() >= 2 => {
+                self.types
+                    .insert(operands[0], SpirvType::Scalar(operands[1]));
+            }
+            Op::TypeVector if operands.len() >= 3 => {
+                self.types
+                    .insert(operands[0], SpirvType::Vector(operands[1], operands[2]));
+            }
+            Op::TypeMatrix if operands.len() >= 3 => {
+                self.types
+                    .insert(operands[0], SpirvType::Matrix(operands[1], operands[2]));
+            }
+            Op::TypeImage if operands.len() >= 7 => {
+                self.types.insert(
+                    operands[0],
+                    SpirvType::Image {
+                        dim: Dim::from_u32(operands[2]),
+                        sampled: operands[6],
+                    },
+                );
+            }
+            Op::TypeSampler if !operands.is_empty() => {
+                self.types.insert(operands[0], SpirvType::Sampler);
+            }
+            Op::TypeSampledImage if !operands.is_empty() => {
+                self.types.insert(operands[0], SpirvType::SampledImage);
+            }
+            Op::TypeArray if operands.len() >= 3 => {
+                self.types
+                    .insert(operands[0], SpirvType::Array(operands[1], operands[2]));
+            }
+            Op::TypeRuntimeArray if operands.len() >= 2 => {
+                self.types
+                    .insert(operands[0], SpirvType::RuntimeArray(operands[1]));
+            }
+            Op::TypeStruct if !operands.is_empty() => {
+                self.types
+                    .insert(operands[0], SpirvType::Struct(operands[1..].to_vec()));
+            }
+            Op::TypePointer if operands.len() >= 3 => {
+                self.types
+                    .insert(operands[0], SpirvType::Pointer(operands[2]));
+            }
+            Op::TypeAccelerationStructureKHR if !operands.is_empty() => {
+                self.types
+                    .insert(operands[0], SpirvType::AccelerationStructure);
+            }
+            Op::Constant if operands.len() >= 3 => {
+                // Only the low word is relevant for sizes.
+                self.constants.insert(operands[1], operands[2]);
+            }
+            Op::Variable if operands.len() >= 3 => {
+                if let Some(storage_class) = StorageClass::from_u32(operands[2]) {
+                    self.variables
+                        .push((operands[1], operands[0], storage_class));
+                }
+            }
+            Op::Decorate if operands.len() >= 2 => {
+                let decorations = self.decorations.entry(operands[0]).or_default();
+                match Decoration::from_u32(operands[1]) {
+                    Some(Decoration::BufferBlock) => decorations.buffer_block = true,
+                    Some(Decoration::NonWritable) => decorations.non_writable = true,
+                    Some(Decoration::ArrayStride) => decorations.array_stride = Some(operand(2)),
+                    Some(Decoration::BuiltIn) => decorations.built_in = Some(operand(2)),
+                    Some(Decoration::Location) => decorations.location = Some(operand(2)),
+                    Some(Decoration::Binding) => decorations.binding = Some(operand(2)),
+                    Some(Decoration::DescriptorSet) => {
+                        decorations.descriptor_set = Some(operand(2))
+                    }
+                    _ => {}
+                }
+            }
+            Op::MemberDecorate if operands.len() >= 3 => {
+                let decorations = self.decorations.entry(operands[0]).or_default();
+                let member = operands[1];
+                match Decoration::from_u32(operands[2]) {
+                    Some(Decoration::Offset) => {
+                        decorations.member_offsets.insert(member, operand(3));
+                    }
+                    Some(Decoration::MatrixStride) => {
+                        decorations.member_matrix_strides.insert(member, operand(3));
+                    }
+                    Some(Decoration::BuiltIn) => {
+                        decorations.member_built_ins.insert(member, operand(3));
+                    }
+                    Some(Decoration::NonWritable) => decorations.member_non_writable.push(member),
+                    _ => {}
+                }
+            }
+            _ => {}
+        }
+    }
+    fn get_decorations(&self, id: u32) -> Option<&SpirvDecorations> {
+        self.decorations.get(&id)
+    }
+    // Strip arrays from type, returning the element type and array size.
+    fn unwrap_array(&self, type_id: u32) -> Result<(u32, Option<u32>), ShaderError> {
+        match self.types.get(&type_id) {
+            Some(SpirvType::Array(element, length)) => {
+                let length = self.constants.get(length).cloned().unwrap_or(0);
+                let (element, size) = self.unwrap_array(*element)?;
+                let size = match size {
+                    Some(size) => size
+                        .checked_mul(length)
+                        .ok_or_else(|| Self::overflow_error(type_id))?,
+                    None => length,
+                };
+                Ok((element, Some(size)))
+            }
+            Some(SpirvType::RuntimeArray(element)) => Ok((self.unwrap_array(*element)?.0, Some(0))),
+            _ => Ok((type_id, None)),
+        }
+    }
+    fn overflow_error(type_id: u32) -> ShaderError {
+        ShaderError::InternalErr(format!(
+            "Invalid SPIR-V binary: size of type {} overflows.",
+            type_id
+        ))
+    }
+    // Compute size of a type using explicit layout decorations.
+    fn get_size(&self, type_id: u32, matrix_stride: Option<u32>) -> Result<u32, ShaderError> {
+        let overflow = || Self::overflow_error(type_id);
+        match self.types.get(&type_id) {
+            Some(SpirvType::Scalar(width)) => Ok(width / 8),
+            Some(SpirvType::Vector(component, count)) => self
+                .get_size(*component, None)?
+                .checked_mul(*count)
+                .ok_or_else(overflow),
+            Some(SpirvType::Matrix(column, count)) => match matrix_stride {
+                Some(stride) => stride.checked_mul(*count).ok_or_else(overflow),
+                None => self
+                    .get_size(*column, None)?
+                    .checked_mul(*count)
+                    .ok_or_else(overflow),
+            },
+            Some(SpirvType::Array(element, length)) => {
+                let length = self.constants.get(length).cloned().unwrap_or(0);
+                let stride = match self
+                    .get_decorations(type_id)
+                    .and_then(|decorations| decorations.array_stride)
+                {
+                    Some(stride) => stride,
+                    None => self.get_size(*element, matrix_stride)?,
+                };
+                stride.checked_mul(length).ok_or_else(overflow)
+            }
+            Some(SpirvType::Struct(members)) => {
+                let decorations = self.get_decorations(type_id);
+                let mut size = 0;
+                for (index, member) in members.iter().enumerate() {
+                    let index = index as u32;
+                    let offset = decorations
+                        .and_then(|decorations| decorations.member_offsets.get(&index))
+                        .cloned()
+                        .unwrap_or(0);
+                    let matrix_stride = decorations.and_then(|decorations| {
+                        decorations.member_matrix_strides.get(&index).cloned()
+                    });
+                    let member_end = offset
+                        .checked_add(self.get_size(*member, matrix_stride)?)
+                        .ok_or_else(overflow)?;
+                    size = size.max(member_end);
+                }
+                Ok(size)
+            }
+            _ => Ok(0),
+        }
+    }
+    fn get_resource_kind(
+        &self,
+        variable_id: u32,
+        storage_class: StorageClass,
+        type_id: u32,
+    ) -> Option<ShaderResourceKind> {
+        let decorations = self.get_decorations(type_id);
+        // Either the variable or all members of the block are decorated.
+        let is_read_only = || {
+            self.get_decorations(variable_id)
+                .is_some_and(|decorations| decorations.non_writable)
+                || match (self.types.get(&type_id), decorations) {
+                    (Some(SpirvType::Struct(members)), Some(decorations)) => {
+                        !members.is_empty()
+                            && (0..members.len() as u32)
+                                .all(|member| decorations.member_non_writable.contains(&member))
+                    }
+                    _ => false,
+                }
+        };
+        match storage_class {
+            StorageClass::Uniform => {
+                if decorations.is_some_and(|decorations| decorations.buffer_block) {
+                    Some(if is_read_only() {
+                        ShaderResourceKind::ReadOnlyStorageBuffer
+                    } else {
+                        ShaderResourceKind::StorageBuffer
+                    })
+                } else {
+                    Some(ShaderResourceKind::UniformBuffer)
+                }
+            }
+            StorageClass::StorageBuffer => Some(if is_read_only() {
+                ShaderResourceKind::ReadOnlyStorageBuffer
+            } else {
+                ShaderResourceKind::StorageBuffer
+            }),
+            StorageClass::UniformConstant => match self.types.get(&type_id) {
+                Some(SpirvType::Sampler) => Some(ShaderResourceKind::Sampler),
+                Some(SpirvType::SampledImage) => Some(ShaderResourceKind::CombinedTextureSampler),
+                Some(SpirvType::AccelerationStructure) => {
+                    Some(ShaderResourceKind::AccelerationStructure)
+                }
+                Some(SpirvType::Image { dim, sampled }) => Some(match (*dim, *sampled) {
+                    (Some(Dim::DimSubpassData), _) => ShaderResourceKind::InputAttachment,
+                    (Some(Dim::DimBuffer), 2) => ShaderResourceKind::StorageTexelBuffer,
+                    (Some(Dim::DimBuffer), _) => ShaderResourceKind::TexelBuffer,
+                    (_, 2) => ShaderResourceKind::StorageTexture,
+                    (_, _) => ShaderResourceKind::SampledTexture,
+                }),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+    fn get_interface_variable(
+        &self,
+        id: u32,
+        type_id: u32,
+    ) -> Result<ShaderInterfaceVariable, ShaderError> {
+        let decorations = self.get_decorations(id);
+        let built_in = match decorations.and_then(|decorations| decorations.built_in) {
+            Some(built_in) => Some(get_built_in_name(built_in)),
+            None => {
+                // Built-in blocks such as gl_PerVertex are decorated per member.
+                let (element, _) = self.unwrap_array(type_id)?;
+                self.get_decorations(element).and_then(|decorations| {
+                    let mut members: Vec<(&u32, &u32)> =
+                        decorations.member_built_ins.iter().collect();
+                    members.sort();
+                    if members.is_empty() {
+                        None
+                    } else {
+                        Some(
+                            members
+                                .iter()
+                                .map(|(_, built_in)| get_built_in_name(**built_in))
+                                .collect::<Vec<String>>()
+                                .join(", "),
+                        )
+                    }
+                })
+            }
+        };
+        Ok(ShaderInterfaceVariable {
+            name: self.names.get(&id).cloned(),
+            location: decorations.and_then(|decorations| decorations.location),
+            built_in,
+        })
+    }
+}
+
+impl ShaderReflection {
+    /// Reflect the interface of a SPIR-V binary.
+    pub fn from_spirv(words: &[u32]) -> Result<Self, ShaderError> {
+        let module = SpirvModule::parse(words)?;
+        let mut reflection = ShaderReflection::default();
+        // Id & type of input & output variables.
+        let mut stage_variables = HashMap::new();
+        for (id, pointer_type, storage_class) in &module.variables {
+            let type_id = match module.types.get(pointer_type) {
+                Some(SpirvType::Pointer(type_id)) => *type_id,
+                _ => continue,
+            };
+            match *storage_class {
+                StorageClass::Input | StorageClass::Output => {
+                    stage_variables.insert(*id, (*storage_class, type_id));
+                }
+                StorageClass::PushConstant => {
+                    reflection.push_constants.push(ShaderPushConstant {
+                        name: module
+                            .names
+                            .get(id)
+                            .or_else(|| module.names.get(&type_id))
+                            .cloned(),
+                        size: module.get_size(type_id, None)?,
+                    });
+                }
+                storage_class => {
+                    let (element, array_size) = module.unwrap_array(type_id)?;
+                    let kind = match module.get_resource_kind(*id, storage_class, element) {
+                        Some(kind) => kind,
+                        None => continue, // Not a resource.
+                    };
+                    let decorations = module.get_decorations(*id);
+                    reflection.resources.push(ShaderResourceBinding {
+                        // Blocks might have an anonymous instance name.
+                        name: module
+                            .names
+                            .get(id)
+                            .or_else(|| module.names.get(&element))
+                            .cloned(),
+                        set: decorations
+                            .and_then(|decorations| decorations.descriptor_set)
+                            .unwrap_or(0),
+                        binding: decorations
+                            .and_then(|decorations| decorations.binding)
+                            .unwrap_or(0),
+                        kind,
+                        array_size,
+                    });
+                }
+            }
+        }
+        for entry_point in &module.entry_points {
+            let stage = entry_point.execution_model.and_then(get_stage);
+            let mut inputs = Vec::new();
+            let mut outputs = Vec::new();
+            for id in &entry_point.interface {
+                match stage_variables.get(id) {
+                    Some((StorageClass::Input, type_id)) => {
+                        inputs.push(module.get_interface_variable(*id, *type_id)?)
+                    }
+                    Some((_, type_id)) => {
+                        outputs.push(module.get_interface_variable(*id, *type_id)?)
+                    }
+                    None => {} // Global resource since SPIR-V 1.4.
+                }
+            }
+            let workgroup_size = match module.workgroup_sizes.get(&entry_point.id) {
+                Some(workgroup_size) => Some(*workgroup_size),
+                None => module
+                    .workgroup_size_ids
+                    .get(&entry_point.id)
+                    .map(|ids| ids.map(|id| module.constants.get(&id).cloned().unwrap_or(0))),
+            };
+            reflection.entry_points.push(ShaderEntryPointReflection {
+                name: Some(entry_point.name.clone()),
+                stage,
+                inputs,
+                outputs,
+                workgroup_size,
+            });
+        }
+        Ok(reflection)
+    }
+}
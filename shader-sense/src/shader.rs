@@ -8,6 +8,8 @@ use std::{
 
 use serde::{Deserialize, Serialize};
 
+use crate::shader_error::ShaderError;
+
 /// All shading language supported
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
 pub enum ShadingLanguage {
@@ -124,9 +126,26 @@ pub enum ShaderStage {
 }
 
 impl ShaderStage {
+    /// All shader stages.
+    pub const ALL: [ShaderStage; 14] = [
+        ShaderStage::Vertex,
+        ShaderStage::Fragment,
+        ShaderStage::Compute,
+        ShaderStage::TesselationControl,
+        ShaderStage::TesselationEvaluation,
+        ShaderStage::Mesh,
+        ShaderStage::Task,
+        ShaderStage::Geometry,
+        ShaderStage::RayGeneration,
+        ShaderStage::ClosestHit,
+        ShaderStage::AnyHit,
+        ShaderStage::Callable,
+        ShaderStage::Miss,
+        ShaderStage::Intersect,
+    ];
     /// Get a stage from its filename. Mostly follow glslang guideline
+    /// Use [`ShaderCompilationParams::get_stage_mapping`] for user defined conventions.
     pub fn from_file_name(file_name: &String) -> Option<ShaderStage> {
-        // TODO: add control for these
         let paths = HashMap::from([
             ("vert", ShaderStage::Vertex),
             ("frag", ShaderStage::Fragment),
@@ -154,6 +173,16 @@ impl ShaderStage {
         // For header files & undefined, will output issue with missing version...
         None
     }
+    /// Get a stage from the name used by `#pragma shader_stage(...)`, following shaderc naming.
+    pub fn from_pragma_name(name: &str) -> Option<ShaderStage> {
+        match name.trim() {
+            "tesscontrol" => Some(ShaderStage::TesselationControl),
+            "tesseval" => Some(ShaderStage::TesselationEvaluation),
+            "raygen" => Some(ShaderStage::RayGeneration),
+            "intersection" => Some(ShaderStage::Intersect),
+            name => ShaderStage::from_str(name).ok(),
+        }
+    }
     pub const fn as_mask(&self) -> ShaderStageMask {
         match self {
             ShaderStage::Vertex => ShaderStageMask::VERTEX,
@@ -243,6 +272,107 @@ impl ToString for ShaderStage {
     }
 }
 
+/// Pattern matched against a file name.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum ShaderFilePattern {
+    Glob(String),  // Such as *.vs.hlsl, where * match any characters and ? a single one.
+    Regex(String), // Such as .*_cs\.hlsl$
+}
+
+impl ShaderFilePattern {
+    /// Build the regex matching this pattern.
+    pub fn to_regex(&self) -> Result<regex::Regex, ShaderError> {
+        match self {
+            ShaderFilePattern::Glob(glob) => {
+                let mut pattern = String::from("^");
+                for character in glob.chars() {
+                    match character {
+                        '*' => pattern.push_str(".*"),
+                        '?' => pattern.push('.'),
+                        character => pattern.push_str(&regex::escape(&character.to_string())),
+                    }
+                }
+                pattern.push('$');
+                Ok(regex::Regex::new(&pattern)?)
+            }
+            ShaderFilePattern::Regex(pattern) => Ok(regex::Regex::new(pattern)?),
+        }
+    }
+}
+
+/// Associate files matching a pattern to a stage, for engines with their own naming conventions.
+/// Pattern is compiled once when the mapping is built, as it is matched against every file.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", from = "SerializedShaderStageMapping")]
+pub struct ShaderStageMapping {
+    pattern: ShaderFilePattern,
+    pub stage: ShaderStage,
+    pub entry_point: Option<String>, // Entry point of matching files, if not the default one.
+    #[serde(skip)]
+    regex: Option<regex::Regex>, // None if pattern is invalid.
+}
+
+// Serialized form of ShaderStageMapping, before its pattern is compiled.
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SerializedShaderStageMapping {
+    pattern: ShaderFilePattern,
+    stage: ShaderStage,
+    #[serde(default)]
+    entry_point: Option<String>,
+}
+
+impl From<SerializedShaderStageMapping> for ShaderStageMapping {
+    fn from(stage_mapping: SerializedShaderStageMapping) -> Self {
+        Self::new(
+            stage_mapping.pattern,
+            stage_mapping.stage,
+            stage_mapping.entry_point,
+        )
+    }
+}
+
+impl PartialEq for ShaderStageMapping {
+    fn eq(&self, other: &Self) -> bool {
+        self.pattern == other.pattern
+            && self.stage == other.stage
+            && self.entry_point == other.entry_point
+    }
+}
+
+impl Eq for ShaderStageMapping {}
+
+impl ShaderStageMapping {
+    pub fn new(
+        pattern: ShaderFilePattern,
+        stage: ShaderStage,
+        entry_point: Option<String>,
+    ) -> Self {
+        let regex = pattern.to_regex().ok();
+        Self {
+            pattern,
+            stage,
+            entry_point,
+            regex,
+        }
+    }
+    pub fn get_pattern(&self) -> &ShaderFilePattern {
+        &self.pattern
+    }
+    /// Check if the pattern could be compiled. Invalid patterns never match.
+    pub fn is_valid(&self) -> bool {
+        self.regex.is_some()
+    }
+    /// Check if the file name match the pattern.
+    pub fn is_match(&self, file_name: &str) -> bool {
+        match &self.regex {
+            Some(regex) => regex.is_match(file_name),
+            None => false,
+        }
+    }
+}
+
 impl FromStr for ShadingLanguage {
     type Err = ();
 
@@ -343,6 +473,33 @@ pub enum HlslVersion {
     V2021,
 }
 
+/// Matrix packing order of HLSL shaders.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum HlslMatrixPacking {
+    ColumnMajor, // -Zpc
+    RowMajor,    // -Zpr
+}
+
+/// Memory layout rules for Vulkan resources when targeting SPIR-V.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum HlslVulkanLayout {
+    Gl,     // -fvk-use-gl-layout
+    Dx,     // -fvk-use-dx-layout
+    Scalar, // -fvk-use-scalar-layout
+}
+
+/// SPIR-V target environment of HLSL shaders.
+#[derive(Debug, Default, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum HlslSpirvTargetEnv {
+    Vulkan1_0,
+    Vulkan1_1,
+    Vulkan1_1Spirv1_4,
+    Vulkan1_2,
+    #[default]
+    Vulkan1_3,
+    Universal1_5,
+}
+
 /// Hlsl compilation parameters for DXC.
 #[derive(Default, Debug, Clone, PartialEq, Eq)]
 pub struct HlslCompilationParams {
@@ -350,6 +507,13 @@ pub struct HlslCompilationParams {
     pub version: HlslVersion,
     pub enable16bit_types: bool,
     pub spirv: bool,
+    pub spirv_target_env: HlslSpirvTargetEnv, // Only used when targeting SPIR-V.
+    pub matrix_packing: Option<HlslMatrixPacking>, // None for compiler default.
+    pub vulkan_layout: Option<HlslVulkanLayout>, // Only used when targeting SPIR-V.
+    pub enabled_warnings: Vec<String>, // Warning names without -W prefix, such as conversion.
+    pub disabled_warnings: Vec<String>, // Warning names without -Wno- prefix.
+    pub warnings_as_errors: bool,
+    pub extra_args: Vec<String>, // Raw arguments passed as is to DXC.
 }
 
 /// Glsl target client
@@ -360,14 +524,90 @@ pub enum GlslTargetClient {
     Vulkan1_2,
     #[default]
     Vulkan1_3,
+    OpenGL330,
+    OpenGL410,
     OpenGL450,
+    OpenGL460,
+    OpenGLES100,
+    OpenGLES300,
+    OpenGLES310,
+    OpenGLES320,
 }
 
 impl GlslTargetClient {
-    /// Check if glsl is for OpenGL or Vulkan
+    /// Check if glsl is for OpenGL (desktop or ES) or Vulkan
     pub fn is_opengl(&self) -> bool {
         match *self {
-            GlslTargetClient::OpenGL450 => true,
+            GlslTargetClient::Vulkan1_0
+            | GlslTargetClient::Vulkan1_1
+            | GlslTargetClient::Vulkan1_2
+            | GlslTargetClient::Vulkan1_3 => false,
+            _ => true,
+        }
+    }
+    /// Check if glsl is for OpenGL ES
+    pub fn is_opengl_es(&self) -> bool {
+        match *self {
+            GlslTargetClient::OpenGLES100
+            | GlslTargetClient::OpenGLES300
+            | GlslTargetClient::OpenGLES310
+            | GlslTargetClient::OpenGLES320 => true,
+            _ => false,
+        }
+    }
+    /// Check if glsl can be compiled to SPIR-V for this client.
+    /// OpenGL only consume SPIR-V since 4.5 with GL_ARB_gl_spirv and ES never does.
+    pub fn is_spirv_supported(&self) -> bool {
+        match *self {
+            GlslTargetClient::OpenGL330
+            | GlslTargetClient::OpenGL410
+            | GlslTargetClient::OpenGLES100
+            | GlslTargetClient::OpenGLES300
+            | GlslTargetClient::OpenGLES310
+            | GlslTargetClient::OpenGLES320 => false,
+            _ => true,
+        }
+    }
+    /// Get the glsl version assumed for files without #version, such as header files.
+    pub fn get_default_version(&self) -> u32 {
+        match *self {
+            GlslTargetClient::Vulkan1_0
+            | GlslTargetClient::Vulkan1_1
+            | GlslTargetClient::Vulkan1_2
+            | GlslTargetClient::Vulkan1_3 => 450,
+            GlslTargetClient::OpenGL330 => 330,
+            GlslTargetClient::OpenGL410 => 410,
+            GlslTargetClient::OpenGL450 => 450,
+            GlslTargetClient::OpenGL460 => 460,
+            GlslTargetClient::OpenGLES100 => 100,
+            GlslTargetClient::OpenGLES300 => 300,
+            GlslTargetClient::OpenGLES310 => 310,
+            GlslTargetClient::OpenGLES320 => 320,
+        }
+    }
+    /// Get the #version directive assumed for files without one, such as header files.
+    pub fn get_default_version_directive(&self) -> String {
+        match *self {
+            // 100 is ES only and does not accept profile.
+            GlslTargetClient::OpenGLES100 => String::from("#version 100"),
+            client if client.is_opengl_es() => {
+                format!("#version {} es", client.get_default_version())
+            }
+            client => format!("#version {}", client.get_default_version()),
+        }
+    }
+}
+
+impl GlslCompilationParams {
+    /// Get the glsl version declared with #version, or the client default one if unknown.
+    pub fn get_version(&self) -> u32 {
+        self.version
+            .unwrap_or_else(|| self.client.get_default_version())
+    }
+    /// Check if glsl version is for OpenGL ES. ES versions do not overlap with desktop ones.
+    pub fn is_opengl_es(&self) -> bool {
+        match self.get_version() {
+            100 | 300 | 310 | 320 => true,
             _ => false,
         }
     }
@@ -390,11 +630,70 @@ pub enum GlslSpirvVersion {
 pub struct GlslCompilationParams {
     pub client: GlslTargetClient,
     pub spirv: GlslSpirvVersion,
+    pub version: Option<u32>,    // Version declared with #version, if known.
+    pub extensions: Vec<String>, // Extensions enabled with #extension. Only relevant if version known.
+    pub suppress_warnings: bool,
+    pub relaxed_errors: bool,
+    pub extra_args: Vec<String>, // Arguments following glslangValidator syntax. Only a subset is supported.
+}
+
+/// Capability profile targeted by wgsl shaders.
+#[derive(Debug, Default, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum WgslCapabilityProfile {
+    /// Capabilities available on every WebGPU device.
+    WebGpu,
+    /// All capabilities supported by naga, for native targets.
+    #[default]
+    Native,
+}
+
+/// Validation passes run by naga.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+pub enum WgslValidationFlag {
+    Expressions,
+    Blocks,
+    ControlFlowUniformity,
+    StructLayouts,
+    Constants,
+    Bindings,
+}
+
+impl WgslValidationFlag {
+    /// Get all validation passes
+    pub fn all() -> Vec<WgslValidationFlag> {
+        vec![
+            WgslValidationFlag::Expressions,
+            WgslValidationFlag::Blocks,
+            WgslValidationFlag::ControlFlowUniformity,
+            WgslValidationFlag::StructLayouts,
+            WgslValidationFlag::Constants,
+            WgslValidationFlag::Bindings,
+        ]
+    }
 }
 
 /// Wgsl compilation parameters for naga.
-#[derive(Default, Debug, Clone, PartialEq, Eq)]
-pub struct WgslCompilationParams {}
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WgslCompilationParams {
+    pub profile: WgslCapabilityProfile,
+    // Features enabled on top of the profile.
+    pub push_constants: bool,
+    pub subgroups: bool,
+    pub ray_query: bool,
+    pub validation_flags: Vec<WgslValidationFlag>,
+}
+
+impl Default for WgslCompilationParams {
+    fn default() -> Self {
+        Self {
+            profile: WgslCapabilityProfile::default(),
+            push_constants: false,
+            subgroups: false,
+            ray_query: false,
+            validation_flags: WgslValidationFlag::all(),
+        }
+    }
+}
 
 /// Parameters for includes.
 #[derive(Default, Debug, Clone)]
@@ -409,11 +708,26 @@ pub struct ShaderContextParams {
 pub struct ShaderCompilationParams {
     pub entry_point: Option<String>,
     pub shader_stage: Option<ShaderStage>,
+    pub stage_mappings: Vec<ShaderStageMapping>, // Used to deduce stage of files when not set. First match is used.
     pub hlsl: HlslCompilationParams,
     pub glsl: GlslCompilationParams,
     pub wgsl: WgslCompilationParams,
 }
 
+impl ShaderCompilationParams {
+    /// Get the first stage mapping matching the file name.
+    /// Mappings are ignored if the stage is already set, by a variant for example.
+    pub fn get_stage_mapping(&self, file_name: &str) -> Option<&ShaderStageMapping> {
+        match self.shader_stage {
+            Some(_) => None,
+            None => self
+                .stage_mappings
+                .iter()
+                .find(|stage_mapping| stage_mapping.is_match(file_name)),
+        }
+    }
+}
+
 /// Generic parameters passed to validation and inspection.
 #[derive(Default, Debug, Clone)]
 pub struct ShaderParams {
@@ -1,7 +1,7 @@
 //! Error handling for this crate.
 use core::fmt;
 
-use crate::position::ShaderFileRange;
+use crate::{position::ShaderFileRange, shader::ShaderStage};
 
 /// Severity of a diagnostic
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -49,12 +49,88 @@ impl ShaderDiagnosticSeverity {
     }
 }
 
+/// Tool which emitted a diagnostic
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ShaderDiagnosticSource {
+    Dxc,
+    Glslang,
+    Naga,
+    ShaderSense,
+}
+impl fmt::Display for ShaderDiagnosticSource {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ShaderDiagnosticSource::Dxc => write!(f, "dxc"),
+            ShaderDiagnosticSource::Glslang => write!(f, "glslang"),
+            ShaderDiagnosticSource::Naga => write!(f, "naga"),
+            ShaderDiagnosticSource::ShaderSense => write!(f, "shader-sense"),
+        }
+    }
+}
+
+/// A location related to a diagnostic, such as a secondary label of the error.
+#[derive(Debug, Clone)]
+pub struct ShaderDiagnosticRelatedInformation {
+    pub message: String,
+    pub range: ShaderFileRange,
+}
+/// An edit suggested by the compiler to fix a diagnostic.
+#[derive(Debug, Clone)]
+pub struct ShaderDiagnosticFix {
+    pub range: ShaderFileRange, // Empty range for insertion.
+    pub new_text: String,
+}
+/// Entry point being validated when a diagnostic was emitted.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ShaderDiagnosticEntryPoint {
+    pub label: String,
+    pub stage: ShaderStage,
+}
 /// A diagnostic returned by validation
 #[derive(Debug, Clone)]
 pub struct ShaderDiagnostic {
     pub severity: ShaderDiagnosticSeverity,
     pub error: String,
     pub range: ShaderFileRange,
+    pub related_information: Vec<ShaderDiagnosticRelatedInformation>,
+    pub code: Option<String>, // Identifier of the diagnostic, such as warning flag.
+    pub source: ShaderDiagnosticSource,
+    pub fixes: Vec<ShaderDiagnosticFix>, // Alternative edits fixing the diagnostic.
+    pub entry_points: Vec<ShaderDiagnosticEntryPoint>, // Entry points emitting it, when validating all entry points of a file.
+}
+impl ShaderDiagnostic {
+    /// Create a diagnostic emitted by shader-sense, without any additional information.
+    pub fn new(severity: ShaderDiagnosticSeverity, error: String, range: ShaderFileRange) -> Self {
+        Self {
+            severity,
+            error,
+            range,
+            related_information: Vec::new(),
+            code: None,
+            source: ShaderDiagnosticSource::ShaderSense,
+            fixes: Vec::new(),
+            entry_points: Vec::new(),
+        }
+    }
+    pub fn with_code(mut self, code: Option<String>) -> Self {
+        self.code = code;
+        self
+    }
+    pub fn with_source(mut self, source: ShaderDiagnosticSource) -> Self {
+        self.source = source;
+        self
+    }
+    pub fn with_related_information(
+        mut self,
+        related_information: Vec<ShaderDiagnosticRelatedInformation>,
+    ) -> Self {
+        self.related_information = related_information;
+        self
+    }
+    pub fn with_fixes(mut self, fixes: Vec<ShaderDiagnosticFix>) -> Self {
+        self.fixes = fixes;
+        self
+    }
 }
 /// A list of diagnostic returned by validation
 #[derive(Debug, Default, Clone)]
@@ -69,6 +145,7 @@ pub enum ShaderError {
     NoSymbol,
     ParseSymbolError(String),
     SymbolQueryError(String, ShaderFileRange),
+    InvalidRename(String),
     IoErr(std::io::Error),
     InternalErr(String),
 }
@@ -77,15 +154,15 @@ impl ShaderError {
     /// Convert an error into a diagnostic if its supported
     pub fn into_diagnostic(&self, severity: ShaderDiagnosticSeverity) -> Option<ShaderDiagnostic> {
         match self {
-            ShaderError::SymbolQueryError(message, range) => Some(ShaderDiagnostic {
-                error: format!(
+            ShaderError::SymbolQueryError(message, range) => Some(ShaderDiagnostic::new(
+                severity.clone(),
+                format!(
                     "Symbol Query {}, symbol provider may be impacted: {}",
                     severity.to_string(),
                     message
                 ),
-                severity: severity,
-                range: range.clone(),
-            }),
+                range.clone(),
+            )),
             _ => None,
         }
     }
@@ -116,6 +193,7 @@ impl fmt::Display for ShaderError {
             ShaderError::SymbolQueryError(err, range) => {
                 write!(f, "SymbolQueryError: {} at {:?}", err, range)
             }
+            ShaderError::InvalidRename(err) => write!(f, "Invalid rename: {}", err),
         }
     }
 }
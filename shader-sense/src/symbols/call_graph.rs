@@ -0,0 +1,102 @@
+//! Call graph built from call expressions found in symbols
+use crate::{
+    position::ShaderFileRange,
+    symbols::{
+        symbol_list::ShaderSymbolListRef,
+        symbols::{ShaderSymbol, ShaderSymbolMode, ShaderSymbolType},
+    },
+};
+
+/// A function calling or called by another one, along with the range of every call.
+#[derive(Debug, Clone)]
+pub struct ShaderCall<'a> {
+    pub function: &'a ShaderSymbol,
+    pub ranges: Vec<ShaderFileRange>, // Range of the call expressions label.
+}
+
+#[derive(Debug, Clone)]
+struct ShaderCallEdge<'a> {
+    caller: &'a ShaderSymbol,
+    callee: &'a ShaderSymbol,
+    range: ShaderFileRange,
+}
+
+/// Graph of all calls between functions of a symbol list.
+/// The list should contain all symbols of the include tree to resolve calls across files.
+#[derive(Debug, Clone, Default)]
+pub struct ShaderCallGraph<'a> {
+    edges: Vec<ShaderCallEdge<'a>>,
+}
+
+impl<'a> ShaderCallGraph<'a> {
+    pub fn new(symbol_list: &'a ShaderSymbolListRef<'a>) -> Self {
+        let mut edges = Vec::new();
+        for call_expression in &symbol_list.call_expression {
+            let runtime = match &call_expression.mode {
+                ShaderSymbolMode::Runtime(runtime) => runtime,
+                _ => continue,
+            };
+            // Caller is the function whose body contains the call.
+            let caller = symbol_list.functions.iter().find(|function| {
+                function.mode.map_runtime().is_some_and(|function_runtime| {
+                    function_runtime.file_path == runtime.file_path
+                        && function_runtime
+                            .scope
+                            .as_ref()
+                            .is_some_and(|scope| scope.contain_bounds(&runtime.range))
+                })
+            });
+            let caller = match caller {
+                Some(caller) => *caller,
+                None => continue, // Call outside of a function body, such as a global initializer.
+            };
+            let call_position = runtime.range.end.clone_into_file(runtime.file_path.clone());
+            for callee in symbol_list.find_symbols_at(&call_expression.label, &call_position) {
+                if callee.is_type(ShaderSymbolType::Functions) {
+                    edges.push(ShaderCallEdge {
+                        caller,
+                        callee,
+                        range: runtime.range.clone_into_file(runtime.file_path.clone()),
+                    });
+                }
+            }
+        }
+        Self { edges }
+    }
+    /// Get all functions calling the given function.
+    pub fn get_incoming_calls(&self, function: &ShaderSymbol) -> Vec<ShaderCall<'a>> {
+        Self::group_calls(
+            self.edges
+                .iter()
+                .filter(|edge| edge.callee.is_same_symbol(function))
+                .map(|edge| (edge.caller, &edge.range)),
+        )
+    }
+    /// Get all functions called by the given function.
+    pub fn get_outgoing_calls(&self, function: &ShaderSymbol) -> Vec<ShaderCall<'a>> {
+        Self::group_calls(
+            self.edges
+                .iter()
+                .filter(|edge| edge.caller.is_same_symbol(function))
+                .map(|edge| (edge.callee, &edge.range)),
+        )
+    }
+    fn group_calls<'b>(
+        calls: impl Iterator<Item = (&'a ShaderSymbol, &'b ShaderFileRange)>,
+    ) -> Vec<ShaderCall<'a>> {
+        let mut grouped_calls: Vec<ShaderCall<'a>> = Vec::new();
+        for (function, range) in calls {
+            match grouped_calls
+                .iter_mut()
+                .find(|call| call.function.is_same_symbol(function))
+            {
+                Some(call) => call.ranges.push(range.clone()),
+                None => grouped_calls.push(ShaderCall {
+                    function,
+                    ranges: vec![range.clone()],
+                }),
+            }
+        }
+        grouped_calls
+    }
+}
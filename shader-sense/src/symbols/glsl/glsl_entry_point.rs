@@ -0,0 +1,70 @@
+use std::path::Path;
+
+use crate::{
+    shader::{ShaderParams, ShaderStage},
+    shader_error::ShaderError,
+    symbols::{
+        shader_module::{ShaderModule, ShaderSymbols},
+        symbol_parser::SymbolEntryPointFinder,
+        symbols::{ShaderEntryPoint, ShaderSymbolMode},
+    },
+};
+
+/// GLSL has a single entry point per file, whose stage is deduced from its environment.
+pub struct GlslEntryPointFinder {}
+
+impl SymbolEntryPointFinder for GlslEntryPointFinder {
+    fn find_entry_points(
+        &self,
+        shader_module: &ShaderModule,
+        shader_symbols: &ShaderSymbols,
+        shader_params: &ShaderParams,
+        _include_callback: &mut dyn FnMut(&Path) -> Option<String>,
+    ) -> Result<Vec<ShaderEntryPoint>, ShaderError> {
+        let compilation_params = &shader_params.compilation;
+        let file_name = match shader_module.file_path.file_name() {
+            Some(file_name) => file_name.to_string_lossy().to_string(),
+            None => return Ok(Vec::new()),
+        };
+        // Same order as validator: variant, then #pragma shader_stage, then user mappings, then file extension.
+        let pragma_stage = match compilation_params.shader_stage {
+            Some(_) => None,
+            None => shader_symbols.get_preprocessor().shader_stage,
+        };
+        let stage_mapping = match pragma_stage {
+            Some(_) => None,
+            None => compilation_params.get_stage_mapping(&file_name),
+        };
+        let stage = match compilation_params
+            .shader_stage
+            .or(pragma_stage)
+            .or(stage_mapping.map(|stage_mapping| stage_mapping.stage))
+            .or_else(|| ShaderStage::from_file_name(&file_name))
+        {
+            Some(stage) => stage,
+            None => return Ok(Vec::new()), // Header file.
+        };
+        let entry_point = compilation_params
+            .entry_point
+            .as_deref()
+            .or(stage_mapping.and_then(|stage_mapping| stage_mapping.entry_point.as_deref()))
+            .unwrap_or("main");
+        let local_symbols = shader_symbols.get_local_symbols();
+        Ok(local_symbols
+            .functions
+            .iter()
+            .filter(|function| function.label == entry_point)
+            .filter_map(|function| match &function.mode {
+                ShaderSymbolMode::Runtime(runtime) if runtime.scope.is_some() => {
+                    Some(ShaderEntryPoint {
+                        label: function.label.clone(),
+                        stage,
+                        range: runtime.range.clone_into_file(runtime.file_path.clone()),
+                    })
+                }
+                _ => None,
+            })
+            .take(1)
+            .collect())
+    }
+}
@@ -2,6 +2,7 @@ use std::path::Path;
 
 use crate::{
     position::{ShaderFileRange, ShaderRange},
+    shader::ShaderStage,
     symbols::{
         prepocessor::{
             ShaderPreprocessor, ShaderPreprocessorContext, ShaderPreprocessorDefine,
@@ -15,6 +16,7 @@ pub fn get_glsl_preprocessor_parser() -> Vec<Box<dyn SymbolTreePreprocessorParse
     vec![
         Box::new(GlslIncludeTreePreprocessorParser {}),
         Box::new(GlslDefineTreePreprocessorParser {}),
+        Box::new(GlslDirectiveTreePreprocessorParser {}),
     ]
 }
 struct GlslIncludeTreePreprocessorParser {}
@@ -58,11 +60,19 @@ struct GlslDefineTreePreprocessorParser {}
 
 impl SymbolTreePreprocessorParser for GlslDefineTreePreprocessorParser {
     fn get_query(&self) -> String {
-        r#"(preproc_def
-            (#define)
-            name: (identifier) @define.label
-            value: (preproc_arg)? @define.value
-        )"#
+        r#"[
+            (preproc_def
+                (#define)
+                name: (identifier) @define.label
+                value: (preproc_arg)? @define.value
+            )
+            (preproc_function_def
+                (#define)
+                name: (identifier) @define.label
+                parameters: (preproc_params) @define.parameters
+                value: (preproc_arg)? @define.value
+            )
+        ]"#
         .into()
     }
     fn process_match(
@@ -77,16 +87,87 @@ impl SymbolTreePreprocessorParser for GlslDefineTreePreprocessorParser {
         let range =
             ShaderFileRange::from(file_path.into(), ShaderRange::from(identifier_node.range()));
         let name = get_name(shader_content, identifier_node).into();
-        let value = if symbol_match.captures.len() > 1 {
-            Some(get_name(shader_content, symbol_match.captures[1].node).trim())
-        } else {
-            None
-        };
+        // Optional captures, check their kind.
+        let value = symbol_match.captures[1..]
+            .iter()
+            .find(|capture| capture.node.kind() == "preproc_arg")
+            .map(|capture| get_name(shader_content, capture.node).trim());
+        let parameters = symbol_match.captures[1..]
+            .iter()
+            .find(|capture| capture.node.kind() == "preproc_params")
+            .map(|capture| {
+                get_name(shader_content, capture.node)
+                    .trim_start_matches('(')
+                    .trim_end_matches(')')
+                    .split(',')
+                    .map(|parameter| parameter.trim().to_string())
+                    .filter(|parameter| !parameter.is_empty())
+                    .collect::<Vec<String>>()
+            });
         // TODO: check exist & first one / last one. Need regions aswell... Duplicate with position as key ?
         symbols.defines.push(ShaderPreprocessorDefine::new(
             name,
             range,
             value.map(|s| s.into()),
+            parameters,
         ));
     }
 }
+struct GlslDirectiveTreePreprocessorParser {}
+
+impl SymbolTreePreprocessorParser for GlslDirectiveTreePreprocessorParser {
+    fn get_query(&self) -> String {
+        r#"(preproc_call
+            directive: (preproc_directive) @directive
+            argument: (preproc_arg) @argument
+        )"#
+        .into()
+    }
+    fn process_match(
+        &self,
+        symbol_match: &tree_sitter::QueryMatch,
+        _file_path: &Path,
+        shader_content: &str,
+        preprocessor: &mut ShaderPreprocessor,
+        _context: &mut ShaderPreprocessorContext,
+    ) {
+        let directive = get_name(shader_content, symbol_match.captures[0].node);
+        let argument = get_name(shader_content, symbol_match.captures[1].node).trim();
+        match directive {
+            // #version 450 core
+            "#version" => {
+                preprocessor.version = argument
+                    .split_whitespace()
+                    .next()
+                    .and_then(|version| version.parse::<u32>().ok());
+            }
+            // #extension GL_EXT_mesh_shader : require
+            "#extension" => {
+                if let Some((extension, behaviour)) = argument.split_once(':') {
+                    let extension = extension.trim();
+                    preprocessor
+                        .extensions
+                        .retain(|enabled_extension| enabled_extension != extension);
+                    match behaviour.trim() {
+                        "enable" | "require" | "warn" => {
+                            preprocessor.extensions.push(extension.into())
+                        }
+                        _ => {} // disable
+                    }
+                }
+            }
+            // #pragma shader_stage(vertex)
+            "#pragma" => {
+                if let Some(stage) = argument
+                    .strip_prefix("shader_stage")
+                    .map(|stage| stage.trim())
+                    .and_then(|stage| stage.strip_prefix('('))
+                    .and_then(|stage| stage.strip_suffix(')'))
+                {
+                    preprocessor.shader_stage = ShaderStage::from_pragma_name(stage);
+                }
+            }
+            _ => {}
+        }
+    }
+}
@@ -1,9 +1,11 @@
 //! Parser specific for GLSL
+mod glsl_entry_point;
 mod glsl_parser;
 mod glsl_preprocessor;
 mod glsl_regions;
 mod glsl_word;
 
+use glsl_entry_point::GlslEntryPointFinder;
 use glsl_parser::get_glsl_parsers;
 use glsl_preprocessor::get_glsl_preprocessor_parser;
 use glsl_regions::GlslRegionFinder;
@@ -17,8 +19,10 @@ pub(super) fn create_glsl_symbol_provider(
     SymbolProvider::new(
         tree_sitter_language,
         get_glsl_parsers(),
+        vec![],
         get_glsl_preprocessor_parser(),
         Box::new(GlslRegionFinder::new()),
         Box::new(GlslSymbolWordProvider::new()),
+        Box::new(GlslEntryPointFinder {}),
     )
 }
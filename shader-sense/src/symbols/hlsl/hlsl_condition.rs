@@ -0,0 +1,773 @@
+use std::collections::HashSet;
+
+use crate::{
+    position::{ShaderFileRange, ShaderPosition, ShaderRange},
+    shader_error::{ShaderDiagnostic, ShaderDiagnosticSeverity, ShaderError},
+    symbols::{prepocessor::ShaderPreprocessorContext, shader_module::ShaderModule},
+};
+
+// Evaluate #if & #elif conditions the way a C preprocessor would.
+// Macros are expanded from their text value, so defines can be expressions.
+// Arithmetic is done on 64 bits integers, signed unless an operand is unsigned.
+
+const MACRO_EXPANSION_DEPTH_LIMIT: usize = 64;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum TokenKind {
+    Number,
+    Identifier,
+    Punctuator,
+}
+
+#[derive(Debug, Clone)]
+struct Token {
+    kind: TokenKind,
+    text: String,
+    // Byte range in shader content. Expanded tokens use the range of the macro they come from.
+    start: usize,
+    end: usize,
+}
+
+struct ConditionError {
+    message: String,
+    start: usize,
+    end: usize,
+}
+
+impl ConditionError {
+    fn new(message: String, token: &Token) -> Self {
+        Self {
+            message,
+            start: token.start,
+            end: token.end,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct ConditionValue {
+    value: i64,
+    unsigned: bool,
+}
+
+impl ConditionValue {
+    fn signed(value: i64) -> Self {
+        Self {
+            value,
+            unsigned: false,
+        }
+    }
+    fn boolean(value: bool) -> Self {
+        Self::signed(value as i64)
+    }
+    fn is_true(&self) -> bool {
+        self.value != 0
+    }
+}
+
+fn tokenize(text: &str, start: usize, end: usize) -> Vec<Token> {
+    const PUNCTUATORS: [&str; 9] = ["<<", ">>", "<=", ">=", "==", "!=", "&&", "||", "##"];
+    let bytes = text.as_bytes();
+    let mut tokens = Vec::new();
+    let mut offset = start;
+    while offset < end {
+        let c = bytes[offset];
+        let token_start = offset;
+        if c.is_ascii_whitespace() || c == b'\\' {
+            // Line continuation are just whitespace here.
+            offset += 1;
+            continue;
+        } else if text[offset..end].starts_with("//") {
+            break;
+        } else if text[offset..end].starts_with("/*") {
+            offset = match text[offset + 2..end].find("*/") {
+                Some(comment_end) => offset + 2 + comment_end + 2,
+                None => end,
+            };
+            continue;
+        }
+        let kind = if c.is_ascii_digit() {
+            while offset < end
+                && (bytes[offset].is_ascii_alphanumeric()
+                    || bytes[offset] == b'_'
+                    || bytes[offset] == b'.')
+            {
+                offset += 1;
+            }
+            TokenKind::Number
+        } else if c.is_ascii_alphabetic() || c == b'_' {
+            while offset < end && (bytes[offset].is_ascii_alphanumeric() || bytes[offset] == b'_') {
+                offset += 1;
+            }
+            TokenKind::Identifier
+        } else {
+            match PUNCTUATORS
+                .iter()
+                .find(|punctuator| text[offset..end].starts_with(*punctuator))
+            {
+                Some(punctuator) => offset += punctuator.len(),
+                None => offset += text[offset..end].chars().next().unwrap().len_utf8(),
+            }
+            TokenKind::Punctuator
+        };
+        tokens.push(Token {
+            kind,
+            text: text[token_start..offset].into(),
+            start: token_start,
+            end: offset,
+        });
+    }
+    tokens
+}
+
+// Join tokens back into text, without spaces around parenthesis.
+fn join_tokens(tokens: &[Token]) -> String {
+    let mut text = String::new();
+    for (index, token) in tokens.iter().enumerate() {
+        let glued =
+            index == 0 || tokens[index - 1].text == "(" || matches!(token.text.as_str(), ")" | ",");
+        if !glued {
+            text.push(' ');
+        }
+        text.push_str(&token.text);
+    }
+    text
+}
+
+fn to_file_range(
+    shader_module: &ShaderModule,
+    start: usize,
+    end: usize,
+) -> Result<ShaderFileRange, ShaderError> {
+    let content = shader_module.content.as_str();
+    Ok(ShaderFileRange::from(
+        shader_module.file_path.clone(),
+        ShaderRange::new(
+            ShaderPosition::from_byte_offset(content, start)?,
+            ShaderPosition::from_byte_offset(content, end)?,
+        ),
+    ))
+}
+
+fn parse_number(token: &Token) -> Result<(ConditionValue, bool), ConditionError> {
+    // Strip integer suffixes.
+    let number = token.text.trim_end_matches(['u', 'U', 'l', 'L']);
+    let unsigned =
+        number.len() != token.text.len() && token.text[number.len()..].contains(['u', 'U']);
+    let (digits, radix) = if let Some(hexa) = number
+        .strip_prefix("0x")
+        .or_else(|| number.strip_prefix("0X"))
+    {
+        (hexa, 16)
+    } else if let Some(binary) = number
+        .strip_prefix("0b")
+        .or_else(|| number.strip_prefix("0B"))
+    {
+        (binary, 2)
+    } else if number.len() > 1 && number.starts_with('0') {
+        (&number[1..], 8)
+    } else {
+        (number, 10)
+    };
+    if digits.contains(['.', 'e', 'E']) && radix == 10 {
+        return Err(ConditionError::new(
+            format!(
+                "Floating point literal {} in preprocessor expression",
+                token.text
+            ),
+            token,
+        ));
+    }
+    match u64::from_str_radix(digits, radix) {
+        Ok(value) => {
+            // Literal not fitting in a signed integer is unsigned.
+            let too_large = !unsigned && value > i64::MAX as u64;
+            Ok((
+                ConditionValue {
+                    value: value as i64,
+                    unsigned: unsigned || too_large,
+                },
+                too_large && radix == 10,
+            ))
+        }
+        Err(err) => Err(ConditionError::new(
+            format!("Invalid integer literal {}: {}", token.text, err),
+            token,
+        )),
+    }
+}
+
+fn binary_precedence(operator: &str) -> Option<u32> {
+    match operator {
+        "||" => Some(1),
+        "&&" => Some(2),
+        "|" => Some(3),
+        "^" => Some(4),
+        "&" => Some(5),
+        "==" | "!=" => Some(6),
+        "<" | ">" | "<=" | ">=" => Some(7),
+        "<<" | ">>" => Some(8),
+        "+" | "-" => Some(9),
+        "*" | "/" | "%" => Some(10),
+        _ => None,
+    }
+}
+
+pub struct HlslConditionEvaluator<'a> {
+    content: &'a str,
+    context: &'a ShaderPreprocessorContext,
+    tokens: Vec<Token>,
+    position: usize,
+    end: usize, // End of the condition, for errors at the end of the expression.
+    warnings: Vec<ConditionError>,
+}
+
+impl<'a> HlslConditionEvaluator<'a> {
+    /// Evaluate a condition node, pushing warnings encountered while evaluating it.
+    pub fn evaluate(
+        shader_module: &ShaderModule,
+        node: tree_sitter::Node,
+        context: &ShaderPreprocessorContext,
+        diagnostics: &mut Vec<ShaderDiagnostic>,
+    ) -> Result<bool, ShaderError> {
+        let content = shader_module.content.as_str();
+        let mut evaluator = HlslConditionEvaluator {
+            content,
+            context,
+            tokens: Vec::new(),
+            position: 0,
+            end: node.end_byte(),
+            warnings: Vec::new(),
+        };
+        let result = evaluator.evaluate_range(node.start_byte(), node.end_byte());
+        for warning in evaluator.warnings {
+            diagnostics.push(ShaderDiagnostic::new(
+                ShaderDiagnosticSeverity::Warning,
+                warning.message,
+                to_file_range(shader_module, warning.start, warning.end)?,
+            ));
+        }
+        match result {
+            Ok(value) => Ok(value.is_true()),
+            Err(err) => Err(ShaderError::SymbolQueryError(
+                err.message,
+                to_file_range(shader_module, err.start, err.end)?,
+            )),
+        }
+    }
+    /// Expand the macro invocation found between start & end, returning the expanded text.
+    pub fn expand_invocation(
+        shader_module: &ShaderModule,
+        start: usize,
+        end: usize,
+        context: &ShaderPreprocessorContext,
+    ) -> Result<String, ShaderError> {
+        let content = shader_module.content.as_str();
+        let evaluator = HlslConditionEvaluator {
+            content,
+            context,
+            tokens: Vec::new(),
+            position: 0,
+            end,
+            warnings: Vec::new(),
+        };
+        let tokens = tokenize(content, start, end);
+        match evaluator.expand(tokens, &mut HashSet::new(), 0) {
+            Ok(tokens) => Ok(join_tokens(&tokens)),
+            Err(err) => Err(ShaderError::SymbolQueryError(
+                err.message,
+                to_file_range(shader_module, err.start, err.end)?,
+            )),
+        }
+    }
+    fn evaluate_range(
+        &mut self,
+        start: usize,
+        end: usize,
+    ) -> Result<ConditionValue, ConditionError> {
+        let tokens = tokenize(self.content, start, end);
+        self.tokens = self.expand(tokens, &mut HashSet::new(), 0)?;
+        self.position = 0;
+        if self.tokens.is_empty() {
+            return Err(ConditionError {
+                message: "Preprocessor condition is empty once expanded".into(),
+                start,
+                end,
+            });
+        }
+        let value = self.parse_expression(true)?;
+        match self.peek() {
+            Some(token) => Err(ConditionError::new(
+                format!("Unexpected token {} in preprocessor expression", token.text),
+                token,
+            )),
+            None => Ok(value),
+        }
+    }
+    // Expand macros & defined operator.
+    fn expand(
+        &self,
+        tokens: Vec<Token>,
+        expanding: &mut HashSet<String>,
+        depth: usize,
+    ) -> Result<Vec<Token>, ConditionError> {
+        let mut expanded = Vec::new();
+        let mut iter = tokens.into_iter().peekable();
+        while let Some(token) = iter.next() {
+            if token.kind != TokenKind::Identifier {
+                expanded.push(token);
+            } else if token.text == "defined" {
+                let parenthesized = iter.peek().map(|t| t.text == "(").unwrap_or(false);
+                if parenthesized {
+                    iter.next();
+                }
+                let name = match iter.next() {
+                    Some(name) if name.kind == TokenKind::Identifier => name,
+                    _ => {
+                        return Err(ConditionError::new(
+                            "Macro name missing after defined".into(),
+                            &token,
+                        ))
+                    }
+                };
+                let mut end = name.end;
+                if parenthesized {
+                    match iter.next() {
+                        Some(close) if close.text == ")" => end = end.max(close.end),
+                        _ => {
+                            return Err(ConditionError::new(
+                                "Missing ')' after defined".into(),
+                                &name,
+                            ))
+                        }
+                    }
+                }
+                let is_defined = expanding.contains(&name.text)
+                    || self.context.get_define_value(&name.text).is_some();
+                expanded.push(Token {
+                    kind: TokenKind::Number,
+                    text: if is_defined { "1" } else { "0" }.into(),
+                    start: token.start,
+                    end: end.max(token.end),
+                });
+            } else if expanding.contains(&token.text) {
+                // Self referencing macro is not expanded again.
+                expanded.push(token);
+            } else if let Some((value, parameters)) = self.context.get_define(&token.text) {
+                let has_arguments = iter.peek().map(|t| t.text == "(").unwrap_or(false);
+                if parameters.is_some() && !has_arguments {
+                    // Function-like macro without arguments is not a macro invocation.
+                    expanded.push(token);
+                    continue;
+                }
+                if depth >= MACRO_EXPANSION_DEPTH_LIMIT {
+                    return Err(ConditionError::new(
+                        format!(
+                            "Macro expansion limit reached while expanding {}",
+                            token.text
+                        ),
+                        &token,
+                    ));
+                }
+                // Expanded tokens point to the macro in the condition.
+                let value_tokens: Vec<Token> = tokenize(value, 0, value.len())
+                    .into_iter()
+                    .map(|value_token| Token {
+                        start: token.start,
+                        end: token.end,
+                        ..value_token
+                    })
+                    .collect();
+                let value_tokens = match parameters {
+                    Some(parameters) => {
+                        let arguments = Self::collect_arguments(&token, &mut iter)?;
+                        self.substitute(
+                            &token,
+                            value_tokens,
+                            parameters,
+                            arguments,
+                            expanding,
+                            depth,
+                        )?
+                    }
+                    None => value_tokens,
+                };
+                let value_tokens = Self::paste(&token, value_tokens);
+                expanding.insert(token.text.clone());
+                let mut value_expanded = self.expand(value_tokens, expanding, depth + 1)?;
+                expanding.remove(&token.text);
+                expanded.append(&mut value_expanded);
+            } else {
+                expanded.push(token);
+            }
+        }
+        Ok(expanded)
+    }
+    // Collect arguments of a function-like macro invocation, iterator being on the opening parenthesis.
+    fn collect_arguments(
+        token: &Token,
+        iter: &mut impl Iterator<Item = Token>,
+    ) -> Result<Vec<Vec<Token>>, ConditionError> {
+        iter.next(); // Opening parenthesis
+        let mut arguments = vec![Vec::new()];
+        let mut depth = 0;
+        for argument_token in iter.by_ref() {
+            match argument_token.text.as_str() {
+                ")" if depth == 0 => return Ok(arguments),
+                "," if depth == 0 => {
+                    arguments.push(Vec::new());
+                    continue;
+                }
+                "(" => depth += 1,
+                ")" => depth -= 1,
+                _ => {}
+            }
+            arguments.last_mut().unwrap().push(argument_token);
+        }
+        Err(ConditionError::new(
+            format!("Unterminated argument list invoking macro {}", token.text),
+            token,
+        ))
+    }
+    // Replace parameters of a function-like macro by their arguments.
+    fn substitute(
+        &self,
+        token: &Token,
+        value_tokens: Vec<Token>,
+        parameters: &[String],
+        mut arguments: Vec<Vec<Token>>,
+        expanding: &mut HashSet<String>,
+        depth: usize,
+    ) -> Result<Vec<Token>, ConditionError> {
+        let variadic = parameters
+            .last()
+            .map(|parameter| parameter.ends_with("..."))
+            .unwrap_or(false);
+        let names: Vec<&str> = parameters
+            .iter()
+            .map(|parameter| match parameter.as_str() {
+                "..." => "__VA_ARGS__",
+                parameter => parameter.trim_end_matches("..."),
+            })
+            .collect();
+        if parameters.is_empty() && arguments.len() == 1 && arguments[0].is_empty() {
+            arguments.clear();
+        }
+        if variadic && arguments.len() > parameters.len() {
+            // Extra arguments are part of the variadic one.
+            let extra = arguments.split_off(parameters.len());
+            let last = arguments.last_mut().unwrap();
+            for argument in extra {
+                last.push(Token {
+                    kind: TokenKind::Punctuator,
+                    text: ",".into(),
+                    start: token.start,
+                    end: token.end,
+                });
+                last.extend(argument);
+            }
+        }
+        let expected_count = parameters.len();
+        if arguments.len() != expected_count && !(variadic && arguments.len() + 1 == expected_count)
+        {
+            return Err(ConditionError::new(
+                format!(
+                    "Macro {} expects {} arguments, but {} were given",
+                    token.text,
+                    expected_count,
+                    arguments.len()
+                ),
+                token,
+            ));
+        }
+        arguments.resize(expected_count, Vec::new());
+        let mut substituted = Vec::new();
+        for (index, value_token) in value_tokens.iter().enumerate() {
+            let parameter_index = match value_token.kind {
+                TokenKind::Identifier => names.iter().position(|name| *name == value_token.text),
+                _ => None,
+            };
+            match parameter_index {
+                Some(parameter_index) => {
+                    // Arguments are expanded before substitution, unless pasted.
+                    let is_pasted = (index > 0 && value_tokens[index - 1].text == "##")
+                        || value_tokens
+                            .get(index + 1)
+                            .map(|t| t.text == "##")
+                            .unwrap_or(false);
+                    let argument = arguments[parameter_index].clone();
+                    if is_pasted {
+                        substituted.extend(argument);
+                    } else {
+                        substituted.extend(self.expand(argument, expanding, depth + 1)?);
+                    }
+                }
+                None => substituted.push(value_token.clone()),
+            }
+        }
+        Ok(substituted)
+    }
+    // Handle token pasting operator.
+    fn paste(token: &Token, tokens: Vec<Token>) -> Vec<Token> {
+        let mut pasted: Vec<Token> = Vec::new();
+        let mut iter = tokens.into_iter();
+        while let Some(current) = iter.next() {
+            if current.text != "##" {
+                pasted.push(current);
+                continue;
+            }
+            let lhs = pasted.pop().map(|t| t.text).unwrap_or_default();
+            let rhs = iter.next().map(|t| t.text).unwrap_or_default();
+            let text = format!("{}{}", lhs, rhs);
+            pasted.extend(
+                tokenize(&text, 0, text.len())
+                    .into_iter()
+                    .map(|pasted_token| Token {
+                        start: token.start,
+                        end: token.end,
+                        ..pasted_token
+                    }),
+            );
+        }
+        pasted
+    }
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.position)
+    }
+    fn next(&mut self) -> Result<Token, ConditionError> {
+        match self.tokens.get(self.position) {
+            Some(token) => {
+                self.position += 1;
+                Ok(token.clone())
+            }
+            None => Err(ConditionError {
+                message: "Unexpected end of preprocessor expression".into(),
+                start: self.end,
+                end: self.end,
+            }),
+        }
+    }
+    fn expect(&mut self, text: &str) -> Result<Token, ConditionError> {
+        let token = self.next()?;
+        if token.text == text {
+            Ok(token)
+        } else {
+            Err(ConditionError::new(
+                format!("Expected '{}' but found '{}'", text, token.text),
+                &token,
+            ))
+        }
+    }
+    fn warn(&mut self, evaluate: bool, message: &str, token: &Token) {
+        // Unevaluated branches do not trigger warnings.
+        if evaluate {
+            self.warnings
+                .push(ConditionError::new(message.into(), token));
+        }
+    }
+    fn parse_expression(&mut self, evaluate: bool) -> Result<ConditionValue, ConditionError> {
+        let condition = self.parse_binary(1, evaluate)?;
+        if self.peek().map(|t| t.text == "?").unwrap_or(false) {
+            self.next()?;
+            let lhs = self.parse_expression(evaluate && condition.is_true())?;
+            self.expect(":")?;
+            let rhs = self.parse_expression(evaluate && !condition.is_true())?;
+            let value = if condition.is_true() { lhs } else { rhs };
+            Ok(ConditionValue {
+                value: value.value,
+                unsigned: lhs.unsigned || rhs.unsigned,
+            })
+        } else {
+            Ok(condition)
+        }
+    }
+    fn parse_binary(
+        &mut self,
+        min_precedence: u32,
+        evaluate: bool,
+    ) -> Result<ConditionValue, ConditionError> {
+        let mut lhs = self.parse_unary(evaluate)?;
+        while let Some(operator) = self.peek().cloned() {
+            let precedence = match binary_precedence(&operator.text) {
+                Some(precedence) if precedence >= min_precedence => precedence,
+                _ => break,
+            };
+            self.next()?;
+            // Short circuit logical operators.
+            let evaluate_rhs = match operator.text.as_str() {
+                "&&" => evaluate && lhs.is_true(),
+                "||" => evaluate && !lhs.is_true(),
+                _ => evaluate,
+            };
+            let rhs = self.parse_binary(precedence + 1, evaluate_rhs)?;
+            lhs = self.apply_binary(&operator, lhs, rhs, evaluate)?;
+        }
+        Ok(lhs)
+    }
+    fn parse_unary(&mut self, evaluate: bool) -> Result<ConditionValue, ConditionError> {
+        let token = self.next()?;
+        match token.kind {
+            TokenKind::Number => {
+                let (value, too_large) = parse_number(&token)?;
+                if too_large {
+                    self.warn(
+                        evaluate,
+                        "Integer literal is too large for a signed integer, interpreting as unsigned",
+                        &token,
+                    );
+                }
+                Ok(value)
+            }
+            TokenKind::Identifier => {
+                if self.peek().map(|t| t.text == "(").unwrap_or(false) {
+                    return Err(ConditionError::new(
+                        format!(
+                            "Function-like macro {} cannot be evaluated in preprocessor expression",
+                            token.text
+                        ),
+                        &token,
+                    ));
+                }
+                // Remaining identifiers are undefined macros, which evaluate to 0.
+                Ok(ConditionValue::boolean(token.text == "true"))
+            }
+            TokenKind::Punctuator => match token.text.as_str() {
+                "(" => {
+                    let value = self.parse_expression(evaluate)?;
+                    self.expect(")")?;
+                    Ok(value)
+                }
+                "!" => Ok(ConditionValue::boolean(
+                    !self.parse_unary(evaluate)?.is_true(),
+                )),
+                "~" => {
+                    let value = self.parse_unary(evaluate)?;
+                    Ok(ConditionValue {
+                        value: !value.value,
+                        unsigned: value.unsigned,
+                    })
+                }
+                "+" => self.parse_unary(evaluate),
+                "-" => {
+                    let value = self.parse_unary(evaluate)?;
+                    if !value.unsigned && value.value == i64::MIN {
+                        self.warn(
+                            evaluate,
+                            "Integer overflow in preprocessor expression",
+                            &token,
+                        );
+                    }
+                    Ok(ConditionValue {
+                        value: value.value.wrapping_neg(),
+                        unsigned: value.unsigned,
+                    })
+                }
+                _ => Err(ConditionError::new(
+                    format!("Unexpected token {} in preprocessor expression", token.text),
+                    &token,
+                )),
+            },
+        }
+    }
+    fn apply_binary(
+        &mut self,
+        operator: &Token,
+        lhs: ConditionValue,
+        rhs: ConditionValue,
+        evaluate: bool,
+    ) -> Result<ConditionValue, ConditionError> {
+        // Usual arithmetic conversions: unsigned if any operand is.
+        let unsigned = lhs.unsigned || rhs.unsigned;
+        let (l, r) = (lhs.value, rhs.value);
+        let arithmetic = |this: &mut Self,
+                          checked: fn(i64, i64) -> Option<i64>,
+                          wrapping: fn(i64, i64) -> i64| {
+            let value = wrapping(l, r);
+            if !unsigned && checked(l, r).is_none() {
+                this.warn(
+                    evaluate,
+                    "Integer overflow in preprocessor expression",
+                    operator,
+                );
+            }
+            ConditionValue { value, unsigned }
+        };
+        let compare = |predicate: fn(std::cmp::Ordering) -> bool| {
+            let ordering = if unsigned {
+                (l as u64).cmp(&(r as u64))
+            } else {
+                l.cmp(&r)
+            };
+            ConditionValue::boolean(predicate(ordering))
+        };
+        Ok(match operator.text.as_str() {
+            "||" => ConditionValue::boolean(lhs.is_true() || rhs.is_true()),
+            "&&" => ConditionValue::boolean(lhs.is_true() && rhs.is_true()),
+            "|" => ConditionValue {
+                value: l | r,
+                unsigned,
+            },
+            "^" => ConditionValue {
+                value: l ^ r,
+                unsigned,
+            },
+            "&" => ConditionValue {
+                value: l & r,
+                unsigned,
+            },
+            "==" => ConditionValue::boolean(l == r),
+            "!=" => ConditionValue::boolean(l != r),
+            "<" => compare(|o| o.is_lt()),
+            ">" => compare(|o| o.is_gt()),
+            "<=" => compare(|o| o.is_le()),
+            ">=" => compare(|o| o.is_ge()),
+            "<<" | ">>" => {
+                // Result has the type of the left operand.
+                if !(0..64).contains(&r) {
+                    self.warn(evaluate, "Shift count is out of range", operator);
+                }
+                let shift = r as u32;
+                let value = match (operator.text.as_str(), lhs.unsigned) {
+                    ("<<", _) => l.wrapping_shl(shift),
+                    (_, true) => (l as u64).wrapping_shr(shift) as i64,
+                    (_, false) => l.wrapping_shr(shift),
+                };
+                ConditionValue {
+                    value,
+                    unsigned: lhs.unsigned,
+                }
+            }
+            "+" => arithmetic(self, i64::checked_add, i64::wrapping_add),
+            "-" => arithmetic(self, i64::checked_sub, i64::wrapping_sub),
+            "*" => arithmetic(self, i64::checked_mul, i64::wrapping_mul),
+            "/" | "%" => {
+                if r == 0 {
+                    if evaluate {
+                        return Err(ConditionError::new(
+                            "Division by zero in preprocessor expression".into(),
+                            operator,
+                        ));
+                    }
+                    ConditionValue { value: 0, unsigned }
+                } else if unsigned {
+                    let (l, r) = (l as u64, r as u64);
+                    let value = if operator.text == "/" { l / r } else { l % r };
+                    ConditionValue {
+                        value: value as i64,
+                        unsigned,
+                    }
+                } else if operator.text == "/" {
+                    arithmetic(self, i64::checked_div, i64::wrapping_div)
+                } else {
+                    arithmetic(self, i64::checked_rem, i64::wrapping_rem)
+                }
+            }
+            _ => {
+                return Err(ConditionError::new(
+                    format!("Binary operator unhandled for {}", operator.text),
+                    operator,
+                ))
+            }
+        })
+    }
+}
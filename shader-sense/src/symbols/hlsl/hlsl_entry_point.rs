@@ -0,0 +1,294 @@
+use std::{collections::HashMap, ops::Range, path::Path, str::FromStr};
+
+use tree_sitter::Node;
+
+use crate::{
+    shader::{ShaderParams, ShaderStage},
+    shader_error::ShaderError,
+    symbols::{
+        shader_module::{ShaderModule, ShaderSymbols},
+        symbol_list::ShaderSymbolListRef,
+        symbol_parser::SymbolEntryPointFinder,
+        symbols::{ShaderEntryPoint, ShaderParameter, ShaderSymbolData, ShaderSymbolMode},
+    },
+};
+
+pub struct HlslEntryPointFinder {
+    attribute_regex: regex::Regex,
+    semantic_regex: regex::Regex,
+    dispatch_mesh_regex: regex::Regex,
+}
+
+impl HlslEntryPointFinder {
+    pub fn new() -> Self {
+        Self {
+            // [shader("vertex")], [numthreads(8, 8, 1)], [earlydepthstencil]
+            attribute_regex: regex::Regex::new(r#"\[\s*(\w+)\s*(?:\(([^\)\]]*)\))?\s*\]"#).unwrap(),
+            // : SV_Position, allowing array declaration before it.
+            semantic_regex: regex::Regex::new(r"^\s*(?:\[[^\]]*\]\s*)*:\s*(\w+)").unwrap(),
+            // DispatchMesh(1, 1, 1, payload), only called by amplification shaders.
+            dispatch_mesh_regex: regex::Regex::new(r"\bDispatchMesh\s*\(").unwrap(),
+        }
+    }
+    // Get the content with comments & inactive regions blanked, keeping offsets intact.
+    // Attributes & semantics are matched on it, so that disabled ones are ignored.
+    fn get_active_content(
+        shader_module: &ShaderModule,
+        shader_symbols: &ShaderSymbols,
+    ) -> Result<String, ShaderError> {
+        fn find_comments(node: Node, comments: &mut Vec<Range<usize>>) {
+            if node.kind().contains("comment") {
+                comments.push(node.byte_range());
+                return;
+            }
+            let mut cursor = node.walk();
+            for child in node.children(&mut cursor) {
+                find_comments(child, comments);
+            }
+        }
+        let content = &shader_module.content;
+        let mut blanked_ranges = Vec::new();
+        find_comments(shader_module.tree.root_node(), &mut blanked_ranges);
+        for region in &shader_symbols.get_preprocessor().regions {
+            if !region.is_active {
+                blanked_ranges.push(
+                    region.range.start.to_byte_offset(content)?
+                        ..region.range.end.to_byte_offset(content)?,
+                );
+            }
+        }
+        let mut bytes = content.clone().into_bytes();
+        for range in blanked_ranges {
+            for byte in &mut bytes[range] {
+                if *byte != b'\n' {
+                    *byte = b' ';
+                }
+            }
+        }
+        // Ranges should be on char boundaries, so multi bytes chars are entirely blanked.
+        String::from_utf8(bytes).map_err(|err| {
+            ShaderError::InternalErr(format!(
+                "Failed to blank inactive content of {}: {}",
+                shader_module.file_path.display(),
+                err
+            ))
+        })
+    }
+    // Get the attributes declared before the function, lowercased, with their arguments.
+    fn get_attributes(&self, content: &str, label_start: usize) -> HashMap<String, String> {
+        // Attributes are between the previous declaration and the function label.
+        let prefix_start = content[..label_start]
+            .rfind([';', '}', '{'])
+            .map_or(0, |offset| offset + 1);
+        self.attribute_regex
+            .captures_iter(&content[prefix_start..label_start])
+            .map(|capture| {
+                (
+                    capture[1].to_lowercase(),
+                    capture
+                        .get(2)
+                        .map_or("", |arguments| arguments.as_str())
+                        .trim()
+                        .to_string(),
+                )
+            })
+            .collect()
+    }
+    // Get the semantic declared right after the given position, before any terminator.
+    fn get_semantic(&self, content: &str, offset: usize, terminators: &[char]) -> Option<String> {
+        let end = content[offset..]
+            .find(terminators)
+            .map_or(content.len(), |end| offset + end);
+        self.semantic_regex
+            .captures(&content[offset..end])
+            .map(|capture| capture[1].to_uppercase())
+    }
+    // Get semantics of members of the given struct, if it is declared in this module.
+    fn get_struct_semantics(
+        &self,
+        shader_module: &ShaderModule,
+        content: &str,
+        symbols: &ShaderSymbolListRef,
+        ty: &str,
+    ) -> Result<Vec<String>, ShaderError> {
+        let ty_symbol = match symbols.find_type_symbol(ty) {
+            Some(ty_symbol) => ty_symbol,
+            None => return Ok(Vec::new()),
+        };
+        let is_local = match &ty_symbol.mode {
+            ShaderSymbolMode::Runtime(runtime) => runtime.file_path == shader_module.file_path,
+            _ => false,
+        };
+        match &ty_symbol.data {
+            ShaderSymbolData::Struct { members, .. } if is_local => {
+                let mut semantics = Vec::new();
+                for member in members {
+                    if let Some(semantic) =
+                        self.get_parameter_semantic(content, &member.parameters, &[';'])?
+                    {
+                        semantics.push(semantic);
+                    }
+                }
+                Ok(semantics)
+            }
+            _ => Ok(Vec::new()),
+        }
+    }
+    fn get_parameter_semantic(
+        &self,
+        content: &str,
+        parameter: &ShaderParameter,
+        terminators: &[char],
+    ) -> Result<Option<String>, ShaderError> {
+        match &parameter.range {
+            Some(range) => {
+                let offset = range.end.to_byte_offset(content)?;
+                Ok(self.get_semantic(content, offset, terminators))
+            }
+            None => Ok(None),
+        }
+    }
+    fn get_shader_attribute_stage(value: &str) -> Option<ShaderStage> {
+        match value.trim_matches('"').to_lowercase().as_str() {
+            "intersection" => Some(ShaderStage::Intersect),
+            value => ShaderStage::from_str(value).ok(),
+        }
+    }
+    fn get_semantic_stage(inputs: &[String], outputs: &[String]) -> Option<ShaderStage> {
+        let has_semantic = |semantics: &[String], prefixes: &[&str]| {
+            semantics
+                .iter()
+                .any(|semantic| prefixes.iter().any(|prefix| semantic.starts_with(prefix)))
+        };
+        if has_semantic(
+            outputs,
+            &["SV_TARGET", "SV_DEPTH", "SV_COVERAGE", "SV_STENCILREF"],
+        ) || has_semantic(inputs, &["SV_ISFRONTFACE", "SV_SAMPLEINDEX"])
+        {
+            Some(ShaderStage::Fragment)
+        } else if has_semantic(
+            inputs,
+            &[
+                "SV_DISPATCHTHREADID",
+                "SV_GROUPID",
+                "SV_GROUPTHREADID",
+                "SV_GROUPINDEX",
+            ],
+        ) {
+            Some(ShaderStage::Compute)
+        } else if has_semantic(inputs, &["SV_DOMAINLOCATION"]) {
+            Some(ShaderStage::TesselationEvaluation)
+        } else if has_semantic(inputs, &["SV_OUTPUTCONTROLPOINTID"]) {
+            Some(ShaderStage::TesselationControl)
+        } else if has_semantic(inputs, &["SV_GSINSTANCEID"]) {
+            Some(ShaderStage::Geometry)
+        } else if has_semantic(inputs, &["SV_VERTEXID", "SV_INSTANCEID"])
+            || has_semantic(outputs, &["SV_POSITION"])
+        {
+            Some(ShaderStage::Vertex)
+        } else {
+            None
+        }
+    }
+}
+
+impl SymbolEntryPointFinder for HlslEntryPointFinder {
+    fn find_entry_points(
+        &self,
+        shader_module: &ShaderModule,
+        shader_symbols: &ShaderSymbols,
+        _shader_params: &ShaderParams,
+        _include_callback: &mut dyn FnMut(&Path) -> Option<String>,
+    ) -> Result<Vec<ShaderEntryPoint>, ShaderError> {
+        let active_content = Self::get_active_content(shader_module, shader_symbols)?;
+        let content = active_content.as_str();
+        let all_symbols = shader_symbols.get_all_symbols();
+        let mut entry_points = Vec::new();
+        for function in shader_symbols.get_local_symbols().functions {
+            let (runtime, signature) = match (&function.mode, &function.data) {
+                (
+                    ShaderSymbolMode::Runtime(runtime),
+                    ShaderSymbolData::Functions { signatures },
+                ) => (runtime, &signatures[0]),
+                _ => continue,
+            };
+            // Entry points are global functions with a body.
+            let scope = match &runtime.scope {
+                Some(scope) if runtime.scope_stack.is_empty() => scope,
+                _ => continue,
+            };
+            let label_start = runtime.range.start.to_byte_offset(content)?;
+            let label_end = runtime.range.end.to_byte_offset(content)?;
+            let scope_start = scope.start.to_byte_offset(content)?;
+            let scope_end = scope.end.to_byte_offset(content)?;
+            let attributes = self.get_attributes(content, label_start);
+            let stage = if let Some(shader) = attributes.get("shader") {
+                // Library entry point, stage is explicit.
+                match Self::get_shader_attribute_stage(shader) {
+                    Some(stage) => Some(stage),
+                    None => continue, // Unsupported stage such as node.
+                }
+            } else if attributes.contains_key("maxvertexcount") {
+                Some(ShaderStage::Geometry)
+            } else if attributes.contains_key("patchconstantfunc")
+                || attributes.contains_key("outputcontrolpoints")
+            {
+                Some(ShaderStage::TesselationControl)
+            } else if attributes.contains_key("domain") {
+                Some(ShaderStage::TesselationEvaluation)
+            } else if attributes.contains_key("numthreads") {
+                if attributes.contains_key("outputtopology") {
+                    Some(ShaderStage::Mesh)
+                } else if self
+                    .dispatch_mesh_regex
+                    .is_match(&content[scope_start..scope_end])
+                {
+                    // Amplification shaders dispatch meshes from their own body.
+                    Some(ShaderStage::Task)
+                } else {
+                    Some(ShaderStage::Compute)
+                }
+            } else {
+                // No attributes, rely on system value semantics.
+                let mut inputs = Vec::new();
+                for parameter in &signature.parameters {
+                    if let Some(semantic) =
+                        self.get_parameter_semantic(content, parameter, &[',', ')'])?
+                    {
+                        inputs.push(semantic);
+                    }
+                    inputs.extend(self.get_struct_semantics(
+                        shader_module,
+                        content,
+                        &all_symbols,
+                        &parameter.ty,
+                    )?);
+                }
+                let mut outputs = self.get_struct_semantics(
+                    shader_module,
+                    content,
+                    &all_symbols,
+                    &signature.returnType,
+                )?;
+                // Return semantic follow the parameter list.
+                let declarator = &content[label_end..scope_start];
+                if let Some(parameters_end) = declarator.rfind(')') {
+                    if let Some(semantic) =
+                        self.get_semantic(content, label_end + parameters_end + 1, &['{'])
+                    {
+                        outputs.push(semantic);
+                    }
+                }
+                Self::get_semantic_stage(&inputs, &outputs)
+            };
+            if let Some(stage) = stage {
+                entry_points.push(ShaderEntryPoint {
+                    label: function.label.clone(),
+                    stage,
+                    range: runtime.range.clone_into_file(runtime.file_path.clone()),
+                });
+            }
+        }
+        Ok(entry_points)
+    }
+}
@@ -280,9 +280,7 @@ impl SymbolTreeParser for HlslVariableTreeParser {
             r#"({}declaration
             (qualifiers)?
             type: [
-                (template_type
-                    name: (type_identifier) @variable.type
-                )
+                (template_type) @variable.type
                 (identifier) @variable.type
                 (type_identifier) @variable.type
                 (primitive_type) @variable.type
@@ -587,8 +585,18 @@ mod hlsl_parser_tests {
             ) => {
                 assert!(t1 == t2, "Mismatching link")
             }
-            (ShaderSymbolData::Macro { value: v1 }, ShaderSymbolData::Macro { value: v2 }) => {
-                assert!(v1 == v2, "Mismatching macro")
+            (
+                ShaderSymbolData::Macro {
+                    value: v1,
+                    parameters: p1,
+                },
+                ShaderSymbolData::Macro {
+                    value: v2,
+                    parameters: p2,
+                },
+            ) => {
+                assert!(v1 == v2, "Mismatching macro");
+                assert!(p1 == p2, "Mismatching macro parameters")
             }
             (ShaderSymbolData::Enum { values: v1 }, ShaderSymbolData::Enum { values: v2 }) => {
                 assert!(v1.len() == v2.len(), "Invalid enum");
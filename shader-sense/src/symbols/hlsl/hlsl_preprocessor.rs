@@ -85,15 +85,15 @@ impl SymbolTreePreprocessorParser for HlslIncludeTreePreprocessorParser {
                 ));
             }
             None => {
-                preprocessor.diagnostics.push(ShaderDiagnostic {
-                    severity: ShaderDiagnosticSeverity::Warning,
-                    error: format!(
+                preprocessor.diagnostics.push(ShaderDiagnostic::new(
+                    ShaderDiagnosticSeverity::Warning,
+                    format!(
                         "Failed to find include {} in file {}. Symbol provider might be impacted.",
                         relative_path,
                         file_path.display()
                     ),
                     range,
-                });
+                ));
             }
         }
     }
@@ -102,11 +102,19 @@ struct HlslDefineTreePreprocessorParser {}
 
 impl SymbolTreePreprocessorParser for HlslDefineTreePreprocessorParser {
     fn get_query(&self) -> String {
-        r#"(preproc_def
-            (#define)
-            name: (identifier) @define.label
-            value: (preproc_arg)? @define.value
-        )"#
+        r#"[
+            (preproc_def
+                (#define)
+                name: (identifier) @define.label
+                value: (preproc_arg)? @define.value
+            )
+            (preproc_function_def
+                (#define)
+                name: (identifier) @define.label
+                parameters: (preproc_params) @define.parameters
+                value: (preproc_arg)? @define.value
+            )
+        ]"#
         .into()
     }
     fn process_match(
@@ -121,16 +129,29 @@ impl SymbolTreePreprocessorParser for HlslDefineTreePreprocessorParser {
         let range =
             ShaderFileRange::from(file_path.into(), ShaderRange::from(identifier_node.range()));
         let name = get_name(shader_content, identifier_node).into();
-        let value = if symbol_match.captures.len() > 1 {
-            Some(get_name(shader_content, symbol_match.captures[1].node).trim())
-        } else {
-            None
-        };
+        // Optional captures, check their kind.
+        let value = symbol_match.captures[1..]
+            .iter()
+            .find(|capture| capture.node.kind() == "preproc_arg")
+            .map(|capture| get_name(shader_content, capture.node).trim());
+        let parameters = symbol_match.captures[1..]
+            .iter()
+            .find(|capture| capture.node.kind() == "preproc_params")
+            .map(|capture| {
+                get_name(shader_content, capture.node)
+                    .trim_start_matches('(')
+                    .trim_end_matches(')')
+                    .split(',')
+                    .map(|parameter| parameter.trim().to_string())
+                    .filter(|parameter| !parameter.is_empty())
+                    .collect::<Vec<String>>()
+            });
         // TODO: check exist & first one / last one. Need regions aswell... Duplicate with position as key ?
         symbols.defines.push(ShaderPreprocessorDefine::new(
             name,
             range,
             value.map(|s| s.into()),
+            parameters,
         ));
     }
 }
@@ -1,9 +1,7 @@
-use std::num::ParseIntError;
-
 use tree_sitter::{Query, QueryCursor, StreamingIterator};
 
 use crate::{
-    position::{ShaderFilePosition, ShaderFileRange, ShaderPosition, ShaderRange},
+    position::{ShaderFileRange, ShaderPosition, ShaderRange},
     shader::ShaderCompilationParams,
     shader_error::{ShaderDiagnostic, ShaderDiagnosticSeverity, ShaderError},
     symbols::{
@@ -17,6 +15,8 @@ use crate::{
     },
 };
 
+use super::hlsl_condition::HlslConditionEvaluator;
+
 // Some nice query resources
 // https://davisvaughan.github.io/r-tree-sitter/reference/query-matches-and-captures.html
 // https://parsiya.net/blog/knee-deep-tree-sitter-queries/
@@ -69,24 +69,6 @@ macro_rules! assert_field_name {
         }
     };
 }
-macro_rules! assert_node_kind {
-    ($file_path:expr, $cursor:expr, $value:expr) => {
-        if $cursor.node().kind() != $value {
-            return Err(ShaderError::SymbolQueryError(
-                format!(
-                    "Unexpected query node kind \"{}\" (at {}:{})",
-                    $cursor.node().kind(),
-                    file!(),
-                    line!(),
-                ),
-                ShaderFileRange::from(
-                    $file_path.clone(),
-                    ShaderRange::from($cursor.node().range()),
-                ),
-            ));
-        }
-    };
-}
 // Better API design:
 /*struct SymbolTreeCursor {}
 struct SymbolTreeCursorIter {
@@ -116,250 +98,9 @@ impl HlslSymbolRegionFinder {
         .unwrap();
         Self { query_if }
     }
-    fn parse_number(number: &str) -> Result<i32, ParseIntError> {
-        if number.starts_with("0x") && number.len() > 2 {
-            let prefixed_number = number
-                .strip_prefix("0x")
-                .unwrap_or(number.strip_prefix("0X").unwrap_or(number));
-            i32::from_str_radix(prefixed_number, 16)
-        } else if number.starts_with("0") && number.len() > 1 {
-            let prefixed_number = number.strip_prefix("0").unwrap_or(number);
-            i32::from_str_radix(prefixed_number, 8)
-        } else {
-            number.parse::<i32>()
-        }
-    }
-    fn get_define_as_i32_depth(
-        context: &ShaderPreprocessorContext,
-        name: &str,
-        position: &ShaderFilePosition,
-        depth: u32,
-    ) -> Result<i32, ShaderError> {
-        if depth == 0 {
-            Err(ShaderError::SymbolQueryError(
-                format!("Failed to parse number_literal {}", name),
-                ShaderFileRange::from(
-                    position.file_path.clone(),
-                    ShaderRange::new(position.position.clone(), position.position.clone()),
-                ),
-            ))
-        } else if name.contains(" ") {
-            // Here we try to detect expression that cannot be parsed, such as expression (#define MACRO (MACRO0 + MACRO1)).
-            // TODO: We should store a proxy tree that is used to query over it for solving them.
-            Err(ShaderError::SymbolQueryError(
-                format!("Macro expression solving not implemented ({}).", name),
-                ShaderFileRange::from(
-                    position.file_path.clone(),
-                    ShaderRange::new(position.position.clone(), position.position.clone()),
-                ),
-            ))
-        } else {
-            // Here we recurse define value cuz a define might just be an alias for another define.
-            // If we dont manage to parse it as a number, parse it as another define.
-            match context.get_define_value(name) {
-                Some(value) => match Self::parse_number(value.as_str()) {
-                    Ok(parsed_value) => Ok(parsed_value),
-                    Err(_) => {
-                        Self::get_define_as_i32_depth(&context, value.as_str(), position, depth - 1)
-                    }
-                },
-                None => Ok(0), // Return false instead of error
-            }
-        }
-    }
-    fn get_define_as_i32(
-        context: &ShaderPreprocessorContext,
-        name: &str,
-        position: &ShaderFilePosition,
-    ) -> Result<i32, ShaderError> {
-        match context.get_define_value(name) {
-            Some(value) => match value.parse::<i32>() {
-                Ok(parsed_value) => Ok(parsed_value),
-                Err(_) => {
-                    // Recurse result up to 10 times for macro that define other macro.
-                    Self::get_define_as_i32_depth(&context, value.as_str(), &position, 10)
-                }
-            },
-            None => Ok(0), // Return false instead of error
-        }
-    }
     fn is_define_defined(context: &ShaderPreprocessorContext, name: &str) -> i32 {
         context.get_define_value(name).is_some() as i32
     }
-    fn resolve_condition(
-        cursor: tree_sitter::TreeCursor,
-        shader_module: &ShaderModule,
-        context: &ShaderPreprocessorContext,
-    ) -> Result<i32, ShaderError> {
-        let mut cursor = cursor;
-        match cursor.node().kind() {
-            "preproc_defined" => {
-                // check if macro is defined.
-                let _ = r#"condition: (preproc_defined
-                    "defined"
-                    "("?
-                    (identifier) @identifier
-                    ")"?
-                )"#;
-                assert_tree_sitter!(shader_module.file_path, cursor.goto_first_child());
-                assert_node_kind!(shader_module.file_path, cursor, "defined");
-                assert_tree_sitter!(shader_module.file_path, cursor.goto_next_sibling());
-                // paranthesis not mandatory...
-                if cursor.node().kind() == "(" {
-                    assert_tree_sitter!(shader_module.file_path, cursor.goto_next_sibling());
-                }
-                assert_node_kind!(shader_module.file_path, cursor, "identifier");
-                let condition_macro = get_name(&shader_module.content, cursor.node());
-                Ok(Self::is_define_defined(context, condition_macro))
-            }
-            "number_literal" => {
-                // As simple as it is, but need to be warry of hexa or octal values.
-                let _ = r#"condition: (number_literal)"#;
-                let number_str = get_name(&shader_module.content, cursor.node());
-                let parsed_number = Self::parse_number(number_str);
-                match parsed_number {
-                    Ok(value) => Ok(value),
-                    Err(_) => Err(ShaderError::SymbolQueryError(
-                        format!("Failed to parse number_literal {}", number_str),
-                        ShaderFileRange::from(
-                            shader_module.file_path.clone(),
-                            ShaderRange::from(cursor.node().range()),
-                        ),
-                    )),
-                }
-            }
-            "identifier" => {
-                // An identifier is simply a macro.
-                let _ = r#"condition: (identifier)"#;
-                let condition_macro = get_name(&shader_module.content, cursor.node());
-                let value = Self::get_define_as_i32(
-                    context,
-                    condition_macro,
-                    &ShaderFilePosition::from(
-                        shader_module.file_path.clone(),
-                        ShaderPosition::from(cursor.node().start_position()),
-                    ),
-                )?;
-                Ok(value)
-            }
-            "binary_expression" => {
-                // A binary expression might compare two expression or identifier. Recurse them.
-                let _ = r#"condition: (binary_expression
-                    left:(_) @identifier.or.expression
-                    operator:(_) @binary.operator
-                    right(_)  @identifier.or.expression
-                )"#;
-                assert_tree_sitter!(shader_module.file_path, cursor.goto_first_child());
-                assert_field_name!(shader_module.file_path, cursor, "left");
-                let left_condition =
-                    Self::resolve_condition(cursor.clone(), shader_module, context)?;
-                assert_tree_sitter!(shader_module.file_path, cursor.goto_next_sibling());
-                assert_field_name!(shader_module.file_path, cursor, "operator");
-                let operator = cursor.node().kind();
-                assert_tree_sitter!(shader_module.file_path, cursor.goto_next_sibling());
-                assert_field_name!(shader_module.file_path, cursor, "right");
-                let right_condition =
-                    Self::resolve_condition(cursor.clone(), shader_module, context)?;
-                match operator {
-                    "&&" => Ok(((left_condition != 0) && (right_condition != 0)) as i32),
-                    "||" => Ok(((left_condition != 0) || (right_condition != 0)) as i32),
-                    "|" => Ok(((left_condition != 0) | (right_condition != 0)) as i32),
-                    "&" => Ok(((left_condition != 0) & (right_condition != 0)) as i32),
-                    "^" => Ok(left_condition ^ right_condition),
-                    "==" => Ok((left_condition == right_condition) as i32),
-                    "!=" => Ok((left_condition != right_condition) as i32),
-                    "<" => Ok((left_condition < right_condition) as i32),
-                    ">" => Ok((left_condition > right_condition) as i32),
-                    "<=" => Ok((left_condition <= right_condition) as i32),
-                    ">=" => Ok((left_condition >= right_condition) as i32),
-                    ">>" => Ok(left_condition >> right_condition),
-                    "<<" => Ok(left_condition << right_condition),
-                    "+" => Ok(left_condition + right_condition),
-                    "-" => Ok(left_condition - right_condition),
-                    "*" => Ok(left_condition * right_condition),
-                    "/" => Ok(left_condition / right_condition),
-                    "%" => Ok(left_condition % right_condition),
-                    value => Err(ShaderError::SymbolQueryError(
-                        format!("Binary operator unhandled for {}", value),
-                        ShaderFileRange::from(
-                            shader_module.file_path.clone(),
-                            ShaderRange::from(cursor.node().range()),
-                        ),
-                    )),
-                }
-            }
-            "unary_expression" => {
-                let _ = r#"condition: (unary_expression 
-                    operator: ["!" "-" "+" "~"] @operator
-                    argument: (identifier) @identifier
-                )"#;
-                // Operator = !~-+
-                assert_tree_sitter!(shader_module.file_path, cursor.goto_first_child());
-                assert_field_name!(shader_module.file_path, cursor, "operator");
-                let operator = cursor.node().kind();
-                assert_node_kind!(shader_module.file_path, cursor, "!");
-                assert_tree_sitter!(shader_module.file_path, cursor.goto_next_sibling());
-                assert_field_name!(shader_module.file_path, cursor, "argument");
-                let value = Self::resolve_condition(cursor.clone(), shader_module, context)?;
-                match operator {
-                    "!" => Ok(!(value != 0) as i32), // Comparing as bool
-                    "+" => Ok(value),
-                    "-" => Ok(-value),
-                    "~" => Ok(!value),
-                    value => Err(ShaderError::SymbolQueryError(
-                        format!("Unary operator unhandled for {}", value),
-                        ShaderFileRange::from(
-                            shader_module.file_path.clone(),
-                            ShaderRange::from(cursor.node().range()),
-                        ),
-                    )),
-                }
-            }
-            "parenthesized_expression" => {
-                // This expression children is an anonymous condition.
-                let _ = r#"condition: (parenthesized_expression 
-                    "("
-                    (_) @anykindofexpression
-                    ")"
-                )"#;
-                assert_tree_sitter!(shader_module.file_path, cursor.goto_first_child());
-                assert_tree_sitter!(shader_module.file_path, cursor.goto_next_sibling());
-                Self::resolve_condition(cursor, shader_module, context)
-            }
-            "call_expression" => {
-                // This expression is a function call
-                let _ = r#"condition: (call_expression 
-                    function: (identifier) @function.name
-                    arguments: (argument_list
-                        "("
-                        (
-                            (identifier) @argument
-                            (",")?
-                        )*
-                        ")"
-                    )
-                )"#;
-                // TODO: should solve this complex expression, simply ignoring it for now.
-                Err(ShaderError::SymbolQueryError(
-                    format!(
-                        "Call expression solving not implemented ({}).",
-                        get_name(&shader_module.content, cursor.node())
-                    ),
-                    ShaderFileRange::from(
-                        shader_module.file_path.clone(),
-                        ShaderRange::from(cursor.node().range()),
-                    ),
-                ))
-            }
-            value => Err(ShaderError::SymbolQueryError(
-                format!("Condition unhandled for {}", value),
-                ShaderFileRange::from(
-                    shader_module.file_path.clone(),
-                    ShaderRange::from(cursor.node().range()),
-                ),
-            )),
-        }
-    }
 }
 impl SymbolRegionFinder for HlslSymbolRegionFinder {
     fn query_regions_in_node<'a>(
@@ -451,11 +192,11 @@ impl SymbolRegionFinder for HlslSymbolRegionFinder {
                         Err(err) => match err {
                             ShaderError::SymbolQueryError(message, shader_range) => {
                                 // Include not found or limit reached.
-                                preprocessor.diagnostics.push(ShaderDiagnostic {
-                                    severity: ShaderDiagnosticSeverity::Warning,
-                                    error: message,
-                                    range: shader_range,
-                                });
+                                preprocessor.diagnostics.push(ShaderDiagnostic::new(
+                                    ShaderDiagnosticSeverity::Warning,
+                                    message,
+                                    shader_range,
+                                ));
                             }
                             err => Err(err)?, // Propagate the error.
                         },
@@ -496,12 +237,13 @@ impl SymbolRegionFinder for HlslSymbolRegionFinder {
                         assert_field_name!(shader_module.file_path, cursor, "condition");
                         let position = ShaderPosition::from(cursor.node().range().end_point);
                         (
-                            match HlslSymbolRegionFinder::resolve_condition(
-                                cursor.clone(),
+                            match HlslConditionEvaluator::evaluate(
                                 shader_module,
+                                cursor.node(),
                                 context,
+                                &mut preprocessor.diagnostics,
                             ) {
-                                Ok(value) => value,
+                                Ok(value) => value as i32,
                                 Err(err) => {
                                     match err.into_diagnostic(ShaderDiagnosticSeverity::Warning) {
                                         Some(diagnostic) => {
@@ -527,12 +269,13 @@ impl SymbolRegionFinder for HlslSymbolRegionFinder {
                             if found_active_region {
                                 0
                             } else {
-                                match HlslSymbolRegionFinder::resolve_condition(
-                                    cursor.clone(),
+                                match HlslConditionEvaluator::evaluate(
                                     shader_module,
+                                    cursor.node(),
                                     context,
+                                    &mut preprocessor.diagnostics,
                                 ) {
-                                    Ok(value) => value,
+                                    Ok(value) => value as i32,
                                     Err(err) => match err
                                         .into_diagnostic(ShaderDiagnosticSeverity::Warning)
                                     {
@@ -697,11 +440,11 @@ impl SymbolRegionFinder for HlslSymbolRegionFinder {
                 Err(err) => match err {
                     ShaderError::SymbolQueryError(message, shader_range) => {
                         // Include not found or limit reached.
-                        preprocessor.diagnostics.push(ShaderDiagnostic {
-                            severity: ShaderDiagnosticSeverity::Warning,
-                            error: message,
-                            range: shader_range,
-                        });
+                        preprocessor.diagnostics.push(ShaderDiagnostic::new(
+                            ShaderDiagnosticSeverity::Warning,
+                            message,
+                            shader_range,
+                        ));
                     }
                     err => Err(err)?, // Propagate the error.
                 },
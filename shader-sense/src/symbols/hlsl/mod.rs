@@ -1,4 +1,6 @@
 //! Parser specific for HLSL
+mod hlsl_condition;
+mod hlsl_entry_point;
 mod hlsl_parser;
 mod hlsl_preprocessor;
 mod hlsl_regions;
@@ -8,6 +10,8 @@ use hlsl_parser::get_hlsl_parsers;
 use hlsl_preprocessor::get_hlsl_preprocessor_parser;
 
 // For glsl
+pub use hlsl_condition::HlslConditionEvaluator;
+pub use hlsl_entry_point::HlslEntryPointFinder;
 pub use hlsl_regions::HlslSymbolRegionFinder;
 pub use hlsl_word::HlslSymbolWordProvider;
 
@@ -19,9 +23,11 @@ pub(super) fn create_hlsl_symbol_provider(
     SymbolProvider::new(
         tree_sitter_language,
         get_hlsl_parsers(),
+        vec![],
         get_hlsl_preprocessor_parser(),
         Box::new(HlslSymbolRegionFinder::new(&tree_sitter_language)),
         Box::new(hlsl_word::HlslSymbolWordProvider {}),
+        Box::new(HlslEntryPointFinder::new()),
     )
 }
 
@@ -57,6 +63,166 @@ mod tests {
         test_regions::<GlslShadingLanguageTag>(shader_module_parser, symbol_provider);
     }
 
+    #[test]
+    fn test_hlsl_conditions() {
+        let shader_module_parser = ShaderModuleParser::from_shading_language(ShadingLanguage::Hlsl);
+        let symbol_provider = SymbolProvider::from_shading_language(ShadingLanguage::Hlsl);
+        test_conditions::<HlslShadingLanguageTag>(shader_module_parser, symbol_provider);
+    }
+    #[test]
+    fn test_glsl_conditions() {
+        let shader_module_parser = ShaderModuleParser::from_shading_language(ShadingLanguage::Glsl);
+        let symbol_provider = SymbolProvider::from_shading_language(ShadingLanguage::Glsl);
+        test_conditions::<GlslShadingLanguageTag>(shader_module_parser, symbol_provider);
+    }
+
+    fn test_conditions<T: ShadingLanguageTag>(
+        mut shader_module_parser: ShaderModuleParser,
+        symbol_provider: SymbolProvider,
+    ) {
+        let file_path = Path::new("./test/hlsl/conditions.hlsl");
+        let shader_content = std::fs::read_to_string(file_path).unwrap();
+        let shader_module = shader_module_parser
+            .create_module(file_path, &shader_content)
+            .unwrap();
+        let symbols = symbol_provider
+            .query_symbols(
+                &shader_module,
+                ShaderParams::default(),
+                &mut default_include_callback::<T>,
+                None,
+            )
+            .unwrap();
+        let expected_active = vec![true, true, false, true, false, false, true, false];
+        let active = symbols
+            .preprocessor
+            .regions
+            .iter()
+            .map(|region| region.is_active)
+            .collect::<Vec<bool>>();
+        assert!(
+            active == expected_active,
+            "Expecting regions {:?}, found {:?}",
+            expected_active,
+            active
+        );
+        // Overflow warning on '+' & division by zero on '/'.
+        let diagnostic_ranges = symbols
+            .preprocessor
+            .diagnostics
+            .iter()
+            .map(|diagnostic| diagnostic.range.range.clone())
+            .collect::<Vec<ShaderRange>>();
+        assert!(
+            diagnostic_ranges
+                == vec![
+                    ShaderRange::new(ShaderPosition::new(19, 23), ShaderPosition::new(19, 24)),
+                    ShaderRange::new(ShaderPosition::new(27, 6), ShaderPosition::new(27, 7)),
+                ],
+            "Unexpected diagnostics {:#?}",
+            symbols.preprocessor.diagnostics
+        );
+    }
+
+    #[test]
+    fn test_hlsl_function_macros() {
+        let file_path = Path::new("./test/hlsl/function-macros.hlsl");
+        let shader_content = std::fs::read_to_string(file_path).unwrap();
+        let mut shader_module_parser =
+            ShaderModuleParser::from_shading_language(ShadingLanguage::Hlsl);
+        let symbol_provider = SymbolProvider::from_shading_language(ShadingLanguage::Hlsl);
+        let shader_module = shader_module_parser
+            .create_module(file_path, &shader_content)
+            .unwrap();
+        let symbols = symbol_provider
+            .query_symbols(
+                &shader_module,
+                ShaderParams::default(),
+                &mut default_include_callback::<HlslShadingLanguageTag>,
+                None,
+            )
+            .unwrap();
+        let add = symbols
+            .preprocessor
+            .defines
+            .iter()
+            .find(|define| define.get_name() == "ADD")
+            .unwrap();
+        assert!(add.get_parameters() == Some(&vec!["a".into(), "b".into()]));
+        assert!(add.get_value() == Some(&"((a) + (b))".into()));
+        let function_like = symbols
+            .preprocessor
+            .defines
+            .iter()
+            .find(|define| define.get_name() == "FUNCTION_LIKE")
+            .unwrap();
+        assert!(function_like.get_parameters() == Some(&vec![]));
+        let expected_active = vec![true, true, true, true, false];
+        let active = symbols
+            .preprocessor
+            .regions
+            .iter()
+            .map(|region| region.is_active)
+            .collect::<Vec<bool>>();
+        assert!(
+            active == expected_active,
+            "Expecting regions {:?}, found {:?}",
+            expected_active,
+            active
+        );
+        assert!(
+            symbols.preprocessor.diagnostics.is_empty(),
+            "Unexpected diagnostics {:#?}",
+            symbols.preprocessor.diagnostics
+        );
+    }
+
+    #[test]
+    fn test_hlsl_macro_expansion() {
+        let file_path = Path::new("./test/hlsl/function-macros.hlsl");
+        let shader_content = std::fs::read_to_string(file_path).unwrap();
+        let mut shader_module_parser =
+            ShaderModuleParser::from_shading_language(ShadingLanguage::Hlsl);
+        let symbol_provider = SymbolProvider::from_shading_language(ShadingLanguage::Hlsl);
+        let shader_module = shader_module_parser
+            .create_module(file_path, &shader_content)
+            .unwrap();
+        let symbols = symbol_provider
+            .query_symbols(
+                &shader_module,
+                ShaderParams::default(),
+                &mut default_include_callback::<HlslShadingLanguageTag>,
+                None,
+            )
+            .unwrap();
+        // TWICE(ADD(1, 2))
+        let expansion = symbol_provider
+            .expand_macro_at_position(&shader_module, &symbols, &ShaderPosition::new(28, 15))
+            .unwrap()
+            .unwrap();
+        assert!(expansion.name == "TWICE");
+        assert!(
+            expansion.range.range
+                == ShaderRange::new(ShaderPosition::new(28, 14), ShaderPosition::new(28, 30))
+        );
+        assert!(
+            expansion.expansion == "((((1) + (2))) + (((1) + (2))))",
+            "Unexpected expansion {}",
+            expansion.expansion
+        );
+        // ADD(1, 2)
+        let expansion = symbol_provider
+            .expand_macro_at_position(&shader_module, &symbols, &ShaderPosition::new(28, 21))
+            .unwrap()
+            .unwrap();
+        assert!(expansion.expansion == "((1) + (2))");
+        // Not a macro
+        assert!(symbol_provider
+            .expand_macro_at_position(&shader_module, &symbols, &ShaderPosition::new(28, 10))
+            .unwrap()
+            .is_none());
+    }
+
     fn test_regions<T: ShadingLanguageTag>(
         mut shader_module_parser: ShaderModuleParser,
         symbol_provider: SymbolProvider,
@@ -1,5 +1,6 @@
 //! Handle symbol inspection with tree-sitter
 
+pub mod call_graph;
 mod glsl;
 mod hlsl;
 pub mod intrinsics;
@@ -10,12 +11,13 @@ pub mod symbol_list;
 mod symbol_parser;
 pub mod symbol_provider;
 pub mod symbols;
+pub mod type_resolver;
 mod wgsl;
 
 #[cfg(test)]
 mod tests {
     use std::{
-        collections::HashSet,
+        collections::{HashMap, HashSet},
         path::{Path, PathBuf},
     };
 
@@ -25,13 +27,19 @@ mod tests {
         include::IncludeHandler,
         position::{ShaderFilePosition, ShaderFileRange, ShaderPosition},
         shader::{
-            GlslShadingLanguageTag, HlslShadingLanguageTag, ShaderCompilationParams, ShaderParams,
-            ShaderStage, ShadingLanguage, ShadingLanguageTag, WgslShadingLanguageTag,
+            GlslShadingLanguageTag, GlslTargetClient, HlslShadingLanguageTag,
+            ShaderCompilationParams, ShaderContextParams, ShaderFilePattern, ShaderParams,
+            ShaderStage, ShaderStageMapping, ShadingLanguage, ShadingLanguageTag,
+            WgslShadingLanguageTag,
         },
         shader_error::ShaderError,
         symbols::{
-            intrinsics::ShaderIntrinsics, shader_module_parser::ShaderModuleParser,
-            symbol_list::ShaderSymbolList, symbols::ShaderSymbolData,
+            call_graph::{ShaderCall, ShaderCallGraph},
+            intrinsics::ShaderIntrinsics,
+            shader_module_parser::ShaderModuleParser,
+            symbol_list::ShaderSymbolList,
+            symbols::ShaderSymbolData,
+            type_resolver::ShaderTypeResolver,
         },
     };
 
@@ -214,7 +222,56 @@ mod tests {
             )
             .unwrap();
         let symbols = symbols.get_all_symbols();
-        assert!(symbols.functions.is_empty());
+        assert!(!symbols.functions.is_empty());
+    }
+    #[test]
+    fn symbols_wgsl_imports() {
+        // Module is composed before being parsed, and symbols are kept while it fails to parse.
+        let file_path = Path::new("./test/wgsl/imports/main.wgsl");
+        let shader_content = std::fs::read_to_string(file_path).unwrap();
+        let mut shader_module_parser =
+            ShaderModuleParser::from_shading_language(ShadingLanguage::Wgsl);
+        let symbol_provider = SymbolProvider::from_shading_language(ShadingLanguage::Wgsl);
+        let shader_params = ShaderParams {
+            context: ShaderContextParams {
+                defines: HashMap::from([("USE_FOG".into(), "".into())]),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let shader_module = shader_module_parser
+            .create_module(file_path, &shader_content)
+            .unwrap();
+        let symbols = symbol_provider
+            .query_symbols(
+                &shader_module,
+                shader_params.clone(),
+                &mut default_include_callback::<WgslShadingLanguageTag>,
+                None,
+            )
+            .unwrap();
+        let all_symbols = symbols.get_all_symbols();
+        assert!(all_symbols.functions.iter().any(|f| f.label == "shade"));
+        assert!(all_symbols.functions.iter().any(|f| f.label == "main"));
+        assert!(all_symbols.variables.iter().any(|v| v.label == "color"));
+        // Imported symbols are not declared in this module.
+        assert!(!all_symbols.functions.iter().any(|f| f.label == "diffuse"));
+        let shader_module = shader_module_parser
+            .create_module(
+                file_path,
+                &shader_content.replace("return color;", "return"),
+            )
+            .unwrap();
+        let symbols = symbol_provider
+            .query_symbols(
+                &shader_module,
+                shader_params,
+                &mut default_include_callback::<WgslShadingLanguageTag>,
+                Some(symbols),
+            )
+            .unwrap();
+        let all_symbols = symbols.get_all_symbols();
+        assert!(all_symbols.functions.iter().any(|f| f.label == "shade"));
     }
     #[test]
     fn symbol_scope_glsl_ok() {
@@ -311,6 +368,321 @@ mod tests {
             .is_none());
     }
     #[test]
+    fn symbols_references_ok() {
+        let file_path = Path::new("./test/hlsl/include-level.hlsl");
+        let shader_content = std::fs::read_to_string(file_path).unwrap();
+        let mut shader_module_parser =
+            ShaderModuleParser::from_shading_language(ShadingLanguage::Hlsl);
+        let symbol_provider = SymbolProvider::from_shading_language(ShadingLanguage::Hlsl);
+        let shader_module = shader_module_parser
+            .create_module(file_path, &shader_content)
+            .unwrap();
+        let symbols = symbol_provider
+            .query_symbols(
+                &shader_module,
+                ShaderParams::default(),
+                &mut default_include_callback::<HlslShadingLanguageTag>,
+                None,
+            )
+            .unwrap();
+        let symbol_list = symbols.get_all_symbols();
+        let get_references = |line: u32, pos: u32, include_declaration: bool| {
+            let word = symbol_provider
+                .get_word_range_at_position(&shader_module, &ShaderPosition::new(line, pos))
+                .unwrap();
+            let matching_symbols = word.find_symbol_from_parent(file_path.into(), &symbol_list);
+            assert!(matching_symbols.len() == 1, "{:#?}", matching_symbols);
+            symbol_provider
+                .query_references(
+                    &shader_module,
+                    &symbols,
+                    &symbol_list,
+                    &matching_symbols[0],
+                    include_declaration,
+                    &mut default_include_callback::<HlslShadingLanguageTag>,
+                )
+                .unwrap()
+                .into_iter()
+                .map(|reference| {
+                    (
+                        reference
+                            .file_path
+                            .file_name()
+                            .unwrap()
+                            .to_string_lossy()
+                            .to_string(),
+                        reference.range.start.line,
+                    )
+                })
+                .collect::<Vec<(String, u32)>>()
+        };
+        // Function declared in an include and called from another one.
+        let references = get_references(7, 20, false);
+        assert!(
+            references == vec![("include-level.hlsl".into(), 7), ("level0.hlsl".into(), 8)],
+            "{:#?}",
+            references
+        );
+        let references = get_references(7, 20, true);
+        assert!(
+            references
+                == vec![
+                    ("level1.hlsl".into(), 5),
+                    ("include-level.hlsl".into(), 7),
+                    ("level0.hlsl".into(), 8)
+                ],
+            "{:#?}",
+            references
+        );
+        // Global variable shadowed by a local one.
+        let references = get_references(5, 19, true);
+        assert!(
+            references == vec![("level0.hlsl".into(), 5), ("include-level.hlsl".into(), 5),],
+            "{:#?}",
+            references
+        );
+    }
+    #[test]
+    fn symbols_rename_ok() {
+        fn rename(
+            file_path: &Path,
+            position: ShaderPosition,
+            new_label: &str,
+        ) -> Result<Vec<(String, Vec<u32>)>, ShaderError> {
+            let shader_content = std::fs::read_to_string(file_path).unwrap();
+            let mut shader_module_parser =
+                ShaderModuleParser::from_shading_language(ShadingLanguage::Hlsl);
+            let symbol_provider = SymbolProvider::from_shading_language(ShadingLanguage::Hlsl);
+            let shader_module = shader_module_parser
+                .create_module(file_path, &shader_content)
+                .unwrap();
+            let symbols = symbol_provider
+                .query_symbols(
+                    &shader_module,
+                    ShaderParams::default(),
+                    &mut default_include_callback::<HlslShadingLanguageTag>,
+                    None,
+                )
+                .unwrap();
+            let mut symbol_list = symbols.get_all_symbols();
+            symbol_list.append(
+                ShaderIntrinsics::get(ShadingLanguage::Hlsl)
+                    .get_intrinsics_symbol(&ShaderCompilationParams::default()),
+            );
+            let word = symbol_provider
+                .get_word_range_at_position(&shader_module, &position)
+                .unwrap();
+            let matching_symbols = word.find_symbol_from_parent(file_path.into(), &symbol_list);
+            assert!(matching_symbols.len() == 1, "{:#?}", matching_symbols);
+            Ok(symbol_provider
+                .query_rename(
+                    &shader_module,
+                    &symbols,
+                    &symbol_list,
+                    &matching_symbols[0],
+                    new_label,
+                    &mut default_include_callback::<HlslShadingLanguageTag>,
+                )?
+                .into_iter()
+                .map(|edit| {
+                    (
+                        edit.file_path
+                            .file_name()
+                            .unwrap()
+                            .to_string_lossy()
+                            .to_string(),
+                        edit.ranges.iter().map(|range| range.start.line).collect(),
+                    )
+                })
+                .collect())
+        }
+        let include_level = Path::new("./test/hlsl/include-level.hlsl");
+        // Function renamed across includes.
+        let edits = rename(include_level, ShaderPosition::new(7, 20), "methodRenamed").unwrap();
+        assert!(
+            edits
+                == vec![
+                    ("level1.hlsl".into(), vec![5]),
+                    ("include-level.hlsl".into(), vec![7]),
+                    ("level0.hlsl".into(), vec![8]),
+                ],
+            "{:#?}",
+            edits
+        );
+        // Collision with a global function, an intrinsic & invalid identifier.
+        for new_label in ["methodLevel0", "lerp", "0method"] {
+            let result = rename(include_level, ShaderPosition::new(7, 20), new_label);
+            assert!(
+                matches!(result, Err(ShaderError::InvalidRename(_))),
+                "Rename to {} should fail: {:#?}",
+                new_label,
+                result
+            );
+        }
+        // Collision with a local variable visible at a reference.
+        let result = rename(include_level, ShaderPosition::new(5, 19), "level");
+        assert!(matches!(result, Err(ShaderError::InvalidRename(_))));
+        // Struct member renamed, colliding with another member.
+        let struct_file = Path::new("./test/hlsl/struct.hlsl");
+        let edits = rename(struct_file, ShaderPosition::new(21, 20), "value").unwrap();
+        assert!(
+            edits == vec![("struct.hlsl".into(), vec![6, 21])],
+            "{:#?}",
+            edits
+        );
+        let result = rename(struct_file, ShaderPosition::new(21, 20), "test");
+        assert!(matches!(result, Err(ShaderError::InvalidRename(_))));
+    }
+    #[test]
+    fn symbols_call_graph_ok() {
+        let file_path = Path::new("./test/hlsl/include-level.hlsl");
+        let shader_content = std::fs::read_to_string(file_path).unwrap();
+        let mut shader_module_parser =
+            ShaderModuleParser::from_shading_language(ShadingLanguage::Hlsl);
+        let symbol_provider = SymbolProvider::from_shading_language(ShadingLanguage::Hlsl);
+        let shader_module = shader_module_parser
+            .create_module(file_path, &shader_content)
+            .unwrap();
+        let symbols = symbol_provider
+            .query_symbols(
+                &shader_module,
+                ShaderParams::default(),
+                &mut default_include_callback::<HlslShadingLanguageTag>,
+                None,
+            )
+            .unwrap();
+        let symbol_list = symbols.get_all_symbols();
+        let call_graph = ShaderCallGraph::new(&symbol_list);
+        let get_calls = |calls: Vec<ShaderCall>| -> Vec<(String, Vec<u32>)> {
+            calls
+                .iter()
+                .map(|call| {
+                    (
+                        call.function.label.clone(),
+                        call.ranges
+                            .iter()
+                            .map(|range| range.range.start.line)
+                            .collect(),
+                    )
+                })
+                .collect()
+        };
+        let method_level1 = symbol_list.find_function_symbol("methodLevel1").unwrap();
+        let incoming_calls = get_calls(call_graph.get_incoming_calls(method_level1));
+        assert!(
+            incoming_calls
+                == vec![
+                    ("compute".into(), vec![7]),
+                    ("methodLevel0".into(), vec![8])
+                ],
+            "{:#?}",
+            incoming_calls
+        );
+        assert!(call_graph.get_outgoing_calls(method_level1).is_empty());
+        let compute = symbol_list.find_function_symbol("compute").unwrap();
+        let outgoing_calls = get_calls(call_graph.get_outgoing_calls(compute));
+        assert!(
+            outgoing_calls
+                == vec![
+                    ("methodLevel0".into(), vec![6]),
+                    ("methodLevel1".into(), vec![7])
+                ],
+            "{:#?}",
+            outgoing_calls
+        );
+        assert!(call_graph.get_incoming_calls(compute).is_empty());
+    }
+    #[test]
+    fn symbols_type_resolver_ok() {
+        let file_path = Path::new("./test/hlsl/expression-type.hlsl");
+        let shader_content = std::fs::read_to_string(file_path).unwrap();
+        let mut shader_module_parser =
+            ShaderModuleParser::from_shading_language(ShadingLanguage::Hlsl);
+        let symbol_provider = SymbolProvider::from_shading_language(ShadingLanguage::Hlsl);
+        let shader_module = shader_module_parser
+            .create_module(file_path, &shader_content)
+            .unwrap();
+        let symbols = symbol_provider
+            .query_symbols(
+                &shader_module,
+                ShaderParams::default(),
+                &mut default_include_callback::<HlslShadingLanguageTag>,
+                None,
+            )
+            .unwrap();
+        let mut symbol_list = symbols.get_all_symbols();
+        symbol_list.append(
+            ShaderIntrinsics::get(ShadingLanguage::Hlsl)
+                .get_intrinsics_symbol(&ShaderCompilationParams::default()),
+        );
+        let type_resolver = ShaderTypeResolver::new(&shader_module, &symbol_list);
+        let get_field = |position: ShaderPosition| -> (String, String) {
+            let word = symbol_provider
+                .get_word_range_at_position(&shader_module, &position)
+                .unwrap();
+            let matching_symbols = type_resolver.find_word_symbols(&word);
+            assert!(matching_symbols.len() == 1, "{:#?}", matching_symbols);
+            match &matching_symbols[0].data {
+                ShaderSymbolData::Parameter { context, ty, .. } => (context.clone(), ty.clone()),
+                ShaderSymbolData::Method {
+                    context,
+                    signatures,
+                } => (context.clone(), signatures[0].returnType.clone()),
+                data => panic!("Unexpected symbol data {:#?}", data),
+            }
+        };
+        // Function return
+        assert!(get_field(ShaderPosition::new(26, 28)) == ("Light".into(), "float3".into()));
+        // Templated buffer element
+        assert!(get_field(ShaderPosition::new(27, 26)) == ("Light".into(), "float3".into()));
+        // Array element
+        assert!(get_field(ShaderPosition::new(28, 30)) == ("Light".into(), "float3".into()));
+        // Intrinsic method & swizzle of its return
+        assert!(get_field(ShaderPosition::new(29, 20)) == ("Texture2D".into(), "float4".into()));
+        assert!(get_field(ShaderPosition::new(29, 45)) == ("float4".into(), "float3".into()));
+        // Method return
+        assert!(get_field(ShaderPosition::new(30, 35)) == ("Light".into(), "float".into()));
+        assert!(get_field(ShaderPosition::new(31, 42)) == ("float3".into(), "float3".into()));
+        // Intrinsic method returning the template argument
+        assert!(get_field(ShaderPosition::new(32, 30)) == ("Light".into(), "float".into()));
+        // Template arguments are kept in declared type.
+        match symbol_list
+            .find_symbols("lights")
+            .first()
+            .map(|symbol| &symbol.data)
+        {
+            Some(ShaderSymbolData::Variables { ty, .. }) => {
+                assert!(ty == "StructuredBuffer<Light>", "{}", ty)
+            }
+            data => panic!("Unexpected symbol data {:#?}", data),
+        }
+        // Expression preceding a member access, as used by completion.
+        let ty = type_resolver
+            .resolve_expression_at(&ShaderPosition::new(29, 43))
+            .unwrap();
+        assert!(ty.ty == "float4", "{:#?}", ty);
+        let members: Vec<String> = type_resolver
+            .find_members(&ty)
+            .into_iter()
+            .map(|member| member.label)
+            .collect();
+        assert!(members == vec!["x", "y", "z", "w"], "{:#?}", members);
+        let ty = type_resolver
+            .resolve_expression_at(&ShaderPosition::new(28, 28))
+            .unwrap();
+        assert!(ty.ty == "Light" && ty.count.is_none(), "{:#?}", ty);
+        let members: Vec<String> = type_resolver
+            .find_members(&ty)
+            .into_iter()
+            .map(|member| member.label)
+            .collect();
+        assert!(
+            members == vec!["color", "intensity", "getColor"],
+            "{:#?}",
+            members
+        );
+    }
+    #[test]
     fn test_position_conversion() {
         fn test_to_byte_offset(
             shader_content: &str,
@@ -411,4 +783,249 @@ mod tests {
             "clip() should not be available from vertex shader."
         );
     }
+    #[test]
+    fn test_glsl_intrinsic_directives() {
+        fn get_compilation_params(file_path: &Path) -> ShaderCompilationParams {
+            let shader_content = std::fs::read_to_string(file_path).unwrap();
+            let mut shader_module_parser =
+                ShaderModuleParser::from_shading_language(ShadingLanguage::Glsl);
+            let symbol_provider = SymbolProvider::from_shading_language(ShadingLanguage::Glsl);
+            let shader_module = shader_module_parser
+                .create_module(file_path, &shader_content)
+                .unwrap();
+            let symbols = symbol_provider
+                .query_symbols(
+                    &shader_module,
+                    ShaderParams::default(),
+                    &mut default_include_callback::<GlslShadingLanguageTag>,
+                    None,
+                )
+                .unwrap();
+            symbols
+                .get_preprocessor()
+                .get_compilation_params(&ShaderCompilationParams::default())
+        }
+        let intrinsics = ShaderIntrinsics::get(ShadingLanguage::Glsl);
+        // Old version without extension
+        let compilation_params = get_compilation_params(Path::new("./test/glsl/version.frag.glsl"));
+        assert!(compilation_params.glsl.version == Some(330));
+        let intrinsics_330 = intrinsics.get_intrinsics_symbol(&compilation_params);
+        assert!(
+            intrinsics_330.find_symbol("atomicAdd").is_none(),
+            "atomicAdd() should not be available in glsl 330."
+        );
+        assert!(
+            intrinsics_330.find_symbol("SetMeshOutputsEXT").is_none(),
+            "SetMeshOutputsEXT() should not be available without extension."
+        );
+        // Core builtins are available from their oldest variant, not their double one.
+        for label in ["dot", "mix", "texture", "abs", "gl_PointSize"] {
+            assert!(
+                intrinsics_330.find_symbol(label).is_some(),
+                "{} should be available in glsl 330.",
+                label
+            );
+        }
+        // Recent version with extension
+        let compilation_params = get_compilation_params(Path::new("./test/glsl/extension.glsl"));
+        assert!(compilation_params.glsl.extensions == vec!["GL_EXT_mesh_shader".to_string()]);
+        let intrinsics_460 = intrinsics.get_intrinsics_symbol(&compilation_params);
+        assert!(
+            intrinsics_460.find_symbol("atomicAdd").is_some(),
+            "atomicAdd() should be available in glsl 460."
+        );
+        assert!(
+            intrinsics_460.find_symbol("SetMeshOutputsEXT").is_some(),
+            "SetMeshOutputsEXT() should be available with extension."
+        );
+        // ES header without version, relying on the ES client.
+        let mut compilation_params = get_compilation_params(Path::new("./test/glsl/es100.glsl"));
+        compilation_params.glsl.client = GlslTargetClient::OpenGLES100;
+        assert!(compilation_params.glsl.is_opengl_es());
+        let intrinsics_es100 = intrinsics.get_intrinsics_symbol(&compilation_params);
+        for label in ["dot", "mix", "sampler2D", "gl_FragCoord"] {
+            assert!(
+                intrinsics_es100.find_symbol(label).is_some(),
+                "{} should be available in glsl es 100.",
+                label
+            );
+        }
+        for label in ["texture", "uint", "atomicAdd", "dvec2", "sampler1D"] {
+            assert!(
+                intrinsics_es100.find_symbol(label).is_none(),
+                "{} should not be available in glsl es 100.",
+                label
+            );
+        }
+        // ES version declared, ES versions are not mapped to desktop ones.
+        compilation_params.glsl.version = Some(310);
+        let intrinsics_es310 = intrinsics.get_intrinsics_symbol(&compilation_params);
+        for label in [
+            "texture",
+            "atomicAdd",
+            "imageStore",
+            "gl_GlobalInvocationID",
+        ] {
+            assert!(
+                intrinsics_es310.find_symbol(label).is_some(),
+                "{} should be available in glsl es 310.",
+                label
+            );
+        }
+        for label in ["fma", "textureQueryLod", "dvec2"] {
+            assert!(
+                intrinsics_es310.find_symbol(label).is_none(),
+                "{} should not be available in glsl es 310.",
+                label
+            );
+        }
+        // Stage declared with pragma
+        let compilation_params = get_compilation_params(Path::new("./test/glsl/pragma-stage.glsl"));
+        assert!(compilation_params.shader_stage == Some(ShaderStage::Compute));
+    }
+    #[test]
+    fn symbols_entry_points_ok() {
+        fn get_entry_points<T: ShadingLanguageTag>(
+            file_path: &Path,
+            shader_params: &ShaderParams,
+        ) -> Vec<(String, ShaderStage)> {
+            let shader_content = std::fs::read_to_string(file_path).unwrap();
+            let mut shader_module_parser =
+                ShaderModuleParser::from_shading_language(T::get_language());
+            let symbol_provider = SymbolProvider::from_shading_language(T::get_language());
+            let shader_module = shader_module_parser
+                .create_module(file_path, &shader_content)
+                .unwrap();
+            let symbols = symbol_provider
+                .query_symbols(
+                    &shader_module,
+                    shader_params.clone(),
+                    &mut default_include_callback::<T>,
+                    None,
+                )
+                .unwrap();
+            symbol_provider
+                .query_entry_points(
+                    &shader_module,
+                    &symbols,
+                    shader_params,
+                    &mut crate::validator::validator::default_include_callback,
+                )
+                .unwrap()
+                .into_iter()
+                .map(|entry_point| (entry_point.label, entry_point.stage))
+                .collect()
+        }
+        #[rustfmt::skip] // Keep them inline
+        let stages = vec![
+            ("graphics.hlsl", "VSMain", ShaderStage::Vertex),
+            ("graphics.hlsl", "HSMain", ShaderStage::TesselationControl),
+            ("graphics.hlsl", "DSMain", ShaderStage::TesselationEvaluation),
+            ("graphics.hlsl", "GSMain", ShaderStage::Geometry),
+            ("graphics.hlsl", "PSMain", ShaderStage::Fragment),
+            ("compute.hlsl", "CSMain", ShaderStage::Compute),
+            ("mesh.hlsl", "ASMain", ShaderStage::Task),
+            ("mesh.hlsl", "MSMain", ShaderStage::Mesh),
+            ("raytracing.hlsl", "RayGenMain", ShaderStage::RayGeneration),
+            ("raytracing.hlsl", "IntersectionMain", ShaderStage::Intersect),
+            ("raytracing.hlsl", "MissMain", ShaderStage::Miss),
+        ];
+        for (file_name, entry_point, shader_stage) in stages {
+            let entry_points = get_entry_points::<HlslShadingLanguageTag>(
+                &Path::new("./test/hlsl/stages/").join(file_name),
+                &ShaderParams::default(),
+            );
+            assert!(
+                entry_points.contains(&(entry_point.into(), shader_stage)),
+                "Missing entry point {} for stage {:?}: {:#?}",
+                entry_point,
+                shader_stage,
+                entry_points
+            );
+        }
+        // Patch constant function is not an entry point.
+        let entry_points = get_entry_points::<HlslShadingLanguageTag>(
+            Path::new("./test/hlsl/stages/graphics.hlsl"),
+            &ShaderParams::default(),
+        );
+        assert!(entry_points.len() == 5, "{:#?}", entry_points);
+        // Attributes in comments or inactive regions are ignored.
+        let entry_points = get_entry_points::<HlslShadingLanguageTag>(
+            Path::new("./test/hlsl/stages/compute.hlsl"),
+            &ShaderParams::default(),
+        );
+        assert!(
+            entry_points == vec![("CSMain".into(), ShaderStage::Compute)],
+            "{:#?}",
+            entry_points
+        );
+        // Amplification shaders are only detected from their own body.
+        let entry_points = get_entry_points::<HlslShadingLanguageTag>(
+            Path::new("./test/hlsl/stages/task.hlsl"),
+            &ShaderParams::default(),
+        );
+        assert!(
+            entry_points
+                == vec![
+                    ("ASMain".into(), ShaderStage::Task),
+                    ("CSMain".into(), ShaderStage::Compute)
+                ],
+            "{:#?}",
+            entry_points
+        );
+        // GLSL stage from pragma, mapping or extension.
+        let entry_points = get_entry_points::<GlslShadingLanguageTag>(
+            Path::new("./test/glsl/pragma-stage.glsl"),
+            &ShaderParams::default(),
+        );
+        assert!(entry_points == vec![("main".into(), ShaderStage::Compute)]);
+        let entry_points = get_entry_points::<GlslShadingLanguageTag>(
+            Path::new("./test/glsl/stage-mapping_cs.glsl"),
+            &ShaderParams {
+                compilation: ShaderCompilationParams {
+                    stage_mappings: vec![ShaderStageMapping::new(
+                        ShaderFilePattern::Glob("*_cs.glsl".into()),
+                        ShaderStage::Compute,
+                        Some("csMain".into()),
+                    )],
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+        );
+        assert!(entry_points == vec![("csMain".into(), ShaderStage::Compute)]);
+        let entry_points = get_entry_points::<GlslShadingLanguageTag>(
+            Path::new("./test/glsl/ok.frag.glsl"),
+            &ShaderParams::default(),
+        );
+        assert!(entry_points == vec![("main".into(), ShaderStage::Fragment)]);
+        // WGSL stage from attributes.
+        let entry_points = get_entry_points::<WgslShadingLanguageTag>(
+            Path::new("./test/wgsl/stages/graphics.wgsl"),
+            &ShaderParams::default(),
+        );
+        assert!(
+            entry_points
+                == vec![
+                    ("VSMain".into(), ShaderStage::Vertex),
+                    ("PSMain".into(), ShaderStage::Fragment)
+                ]
+        );
+        // WGSL module is composed with its imports.
+        let entry_points = get_entry_points::<WgslShadingLanguageTag>(
+            Path::new("./test/wgsl/imports/main.wgsl"),
+            &ShaderParams {
+                context: ShaderContextParams {
+                    defines: HashMap::from([("USE_FOG".into(), "".into())]),
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+        );
+        assert!(
+            entry_points == vec![("main".into(), ShaderStage::Fragment)],
+            "{:#?}",
+            entry_points
+        );
+    }
 }
@@ -8,7 +8,7 @@ use std::{
 use crate::{
     include::IncludeHandler,
     position::{ShaderFileRange, ShaderRange},
-    shader::ShaderContextParams,
+    shader::{ShaderCompilationParams, ShaderContextParams, ShaderStage},
     shader_error::ShaderDiagnostic,
     symbols::{
         shader_module::ShaderSymbols,
@@ -52,6 +52,7 @@ impl ShaderPreprocessorContext {
                     requirement: None,
                     data: ShaderSymbolData::Macro {
                         value: value.clone(),
+                        parameters: None,
                     },
                     mode: ShaderSymbolMode::RuntimeContext(ShaderSymbolRuntimeContext::new()),
                 })
@@ -65,6 +66,28 @@ impl ShaderPreprocessorContext {
             depth: 0,
         }
     }
+    /// Get the context params of this context, with defines declared so far.
+    pub fn get_context_params(&self) -> ShaderContextParams {
+        ShaderContextParams {
+            defines: self
+                .defines
+                .iter()
+                .filter_map(|define| match &define.data {
+                    ShaderSymbolData::Macro { value, .. } => {
+                        Some((define.label.clone(), value.clone()))
+                    }
+                    _ => None,
+                })
+                .collect(),
+            includes: self
+                .include_handler
+                .get_includes()
+                .iter()
+                .cloned()
+                .collect(),
+            path_remapping: self.include_handler.get_path_remapping().clone(),
+        }
+    }
     pub fn mark_dirty(&mut self, file_path: PathBuf) {
         self.dirty_files.insert(file_path);
     }
@@ -107,9 +130,15 @@ impl ShaderPreprocessorContext {
                         lhs_symbol.label == rhs_symbol.label
                             && match (&lhs_symbol.data, &rhs_symbol.data) {
                                 (
-                                    ShaderSymbolData::Macro { value: l_value },
-                                    ShaderSymbolData::Macro { value: r_value },
-                                ) => l_value == r_value,
+                                    ShaderSymbolData::Macro {
+                                        value: l_value,
+                                        parameters: l_parameters,
+                                    },
+                                    ShaderSymbolData::Macro {
+                                        value: r_value,
+                                        parameters: r_parameters,
+                                    },
+                                ) => l_value == r_value && l_parameters == r_parameters,
                                 _ => false,
                             }
                     })
@@ -143,11 +172,15 @@ impl ShaderPreprocessorContext {
             || context.dirty_files.contains(file_path)
     }
     pub fn get_define_value(&self, name: &str) -> Option<String> {
+        self.get_define(name).map(|(value, _)| value.clone())
+    }
+    /// Get the value of a define along with its parameters if it is a function-like macro.
+    pub fn get_define(&self, name: &str) -> Option<(&String, Option<&Vec<String>>)> {
         self.defines
             .iter()
             .find(|symbol| *symbol.label == *name)
             .map(|symbol| match &symbol.data {
-                ShaderSymbolData::Macro { value } => value.clone(),
+                ShaderSymbolData::Macro { value, parameters } => (value, parameters.as_ref()),
                 _ => panic!("Expected ShaderSymbolData::Macro"),
             })
     }
@@ -156,6 +189,14 @@ impl ShaderPreprocessorContext {
     }
 }
 
+/// Macro invocation once expanded with the defines available at its position.
+#[derive(Debug, Clone)]
+pub struct ShaderMacroExpansion {
+    pub name: String,
+    pub range: ShaderFileRange, // Range of the invocation, including its arguments.
+    pub expansion: String,
+}
+
 #[derive(Debug, Clone)]
 pub struct ShaderPreprocessorInclude {
     // TODO: move cache to symbol data
@@ -185,9 +226,17 @@ pub struct ShaderPreprocessor {
     pub regions: Vec<ShaderRegion>,
     pub diagnostics: Vec<ShaderDiagnostic>, // preprocessor errors
     pub mode: ShaderPreprocessorMode,
+    pub version: Option<u32>,    // Version declared with #version directive.
+    pub extensions: Vec<String>, // Extensions enabled with #extension directive.
+    pub shader_stage: Option<ShaderStage>, // Stage declared with #pragma shader_stage directive.
 }
 impl ShaderPreprocessorDefine {
-    pub fn new(name: String, range: ShaderFileRange, value: Option<String>) -> Self {
+    pub fn new(
+        name: String,
+        range: ShaderFileRange,
+        value: Option<String>,
+        parameters: Option<Vec<String>>,
+    ) -> Self {
         Self {
             symbol: ShaderSymbol {
                 label: name.clone(),
@@ -197,6 +246,7 @@ impl ShaderPreprocessorDefine {
                         Some(value) => value.clone(),
                         None => "".into(),
                     },
+                    parameters,
                 },
                 mode: ShaderSymbolMode::Runtime(ShaderSymbolRuntime::global(
                     range.file_path,
@@ -216,7 +266,19 @@ impl ShaderPreprocessorDefine {
     }
     pub fn get_value(&self) -> Option<&String> {
         match &self.symbol.data {
-            ShaderSymbolData::Macro { value } => Some(value),
+            ShaderSymbolData::Macro {
+                value,
+                parameters: _,
+            } => Some(value),
+            _ => None,
+        }
+    }
+    pub fn get_parameters(&self) -> Option<&Vec<String>> {
+        match &self.symbol.data {
+            ShaderSymbolData::Macro {
+                value: _,
+                parameters,
+            } => parameters.as_ref(),
             _ => None,
         }
     }
@@ -271,7 +333,26 @@ impl ShaderPreprocessor {
             regions: Vec::new(),
             diagnostics: Vec::new(),
             mode: ShaderPreprocessorMode::default(),
+            version: None,
+            extensions: Vec::new(),
+            shader_stage: None,
+        }
+    }
+    /// Get compilation params updated with the directives declared in this file.
+    pub fn get_compilation_params(
+        &self,
+        shader_compilation_params: &ShaderCompilationParams,
+    ) -> ShaderCompilationParams {
+        let mut shader_compilation_params = shader_compilation_params.clone();
+        if let Some(version) = self.version {
+            shader_compilation_params.glsl.version = Some(version);
+            shader_compilation_params.glsl.extensions = self.extensions.clone();
+        }
+        // Stage from variant has priority over the one declared in file.
+        if shader_compilation_params.shader_stage.is_none() {
+            shader_compilation_params.shader_stage = self.shader_stage;
         }
+        shader_compilation_params
     }
     pub fn preprocess_symbols<'a>(
         &'a self,
@@ -8,6 +8,7 @@ use std::{
 use tree_sitter::{Tree, TreeCursor};
 
 use crate::{
+    position::{ShaderPosition, ShaderRange},
     shader::ShaderContextParams,
     symbols::symbol_list::{ShaderSymbolList, ShaderSymbolListRef},
 };
@@ -61,6 +62,38 @@ impl ShaderSymbols {
     pub fn get_context(&self) -> &ShaderPreprocessorContext {
         &self.preprocessor.context
     }
+    /// Get the context with all defines available at the given position of this file.
+    pub fn get_context_at(&self, position: &ShaderPosition) -> ShaderPreprocessorContext {
+        let mut context = self.preprocessor.context.clone();
+        self.append_defines_before(Some(position), &mut context);
+        context
+    }
+    // Append defines declared before position, along with the ones of included files.
+    fn append_defines_before(
+        &self,
+        position: Option<&ShaderPosition>,
+        context: &mut ShaderPreprocessorContext,
+    ) {
+        let is_before = |range: &ShaderRange| match position {
+            Some(position) => range.end <= *position,
+            None => true,
+        };
+        for include in &self.preprocessor.includes {
+            if is_before(include.get_range()) {
+                if let Some(cache) = &include.cache {
+                    cache.append_defines_before(None, context);
+                }
+            }
+        }
+        context.append_defines(
+            self.preprocessor
+                .defines
+                .iter()
+                .filter(|define| is_before(define.get_range()))
+                .cloned()
+                .collect(),
+        );
+    }
     // TODO: should abstract this.
     pub fn get_preprocessor(&self) -> &ShaderPreprocessor {
         &self.preprocessor
@@ -193,7 +193,12 @@ impl<'a> ShaderSymbolListRef<'a> {
             .find(|s| s.label == *label)
             .map(|s| *s)
     }
+    /// Find the type symbol of a type label, ignoring its template arguments.
     pub fn find_type_symbol(&'a self, label: &str) -> Option<&'a ShaderSymbol> {
+        let label = match label.split_once('<') {
+            Some((label, _template)) => label.trim(),
+            None => label,
+        };
         self.types.iter().find(|s| s.label == *label).map(|s| *s)
     }
     pub fn filter<P: Fn(ShaderSymbolType, &ShaderSymbol) -> bool>(
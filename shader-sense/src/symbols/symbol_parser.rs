@@ -5,7 +5,7 @@ use tree_sitter::{Node, QueryMatch};
 
 use crate::{
     position::{ShaderPosition, ShaderRange},
-    shader::ShaderCompilationParams,
+    shader::{ShaderCompilationParams, ShaderParams},
     shader_error::ShaderError,
     symbols::{
         symbol_list::{ShaderSymbolList, ShaderSymbolListRef},
@@ -17,7 +17,7 @@ use super::{
     prepocessor::{ShaderPreprocessor, ShaderPreprocessorContext, ShaderRegion},
     shader_module::{ShaderModule, ShaderSymbols},
     symbol_provider::{SymbolIncludeCallback, SymbolProvider},
-    symbols::{ShaderScope, ShaderSymbol},
+    symbols::{ShaderEntryPoint, ShaderScope, ShaderSymbol},
 };
 
 pub(super) fn get_name<'a>(shader_content: &'a str, node: Node) -> &'a str {
@@ -74,6 +74,11 @@ impl<'a> ShaderSymbolListBuilder<'a> {
             self.shader_symbol_list.functions.push(shader_symbol);
         }
     }
+    pub fn add_constant(&mut self, shader_symbol: ShaderSymbol) {
+        if (self.filter_callback)(&shader_symbol) {
+            self.shader_symbol_list.constants.push(shader_symbol);
+        }
+    }
     pub fn get_shader_symbol_list(self) -> ShaderSymbolList {
         self.shader_symbol_list
     }
@@ -312,6 +317,18 @@ pub trait SymbolTreeParser {
     }
 }
 
+pub trait SymbolModuleParser {
+    // Process the whole module & convert it to symbols.
+    // Used when symbols are extracted from a compiler frontend rather than tree-sitter queries.
+    // Return an error if the module failed to parse, so that previous symbols can be kept.
+    fn process_module(
+        &self,
+        shader_module: &ShaderModule,
+        shader_params: &ShaderParams,
+        symbols: &mut ShaderSymbolListBuilder,
+    ) -> Result<(), ShaderError>;
+}
+
 pub trait SymbolRegionFinder {
     fn query_regions_in_node<'a>(
         &self,
@@ -348,3 +365,15 @@ pub trait SymbolWordProvider {
         position: &ShaderPosition,
     ) -> Result<ShaderWordRange, ShaderError>;
 }
+
+pub trait SymbolEntryPointFinder {
+    // Find the entry points declared in the module, using the symbols queried from it.
+    // Include callback is used to read modules which are not included through the preprocessor.
+    fn find_entry_points(
+        &self,
+        shader_module: &ShaderModule,
+        shader_symbols: &ShaderSymbols,
+        shader_params: &ShaderParams,
+        include_callback: &mut dyn FnMut(&Path) -> Option<String>,
+    ) -> Result<Vec<ShaderEntryPoint>, ShaderError>;
+}
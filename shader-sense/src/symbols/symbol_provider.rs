@@ -1,5 +1,5 @@
 //! Main entry point to inspect symbols from a file
-use std::{cell::RefCell, rc::Rc};
+use std::{cell::RefCell, collections::HashSet, path::Path, rc::Rc};
 
 use tree_sitter::{Query, QueryCursor, StreamingIterator};
 
@@ -8,36 +8,43 @@ use crate::{
     shader::{ShaderCompilationParams, ShaderParams, ShadingLanguage, ShadingLanguageTag},
     shader_error::{ShaderDiagnostic, ShaderDiagnosticSeverity, ShaderError},
     symbols::{
-        glsl::create_glsl_symbol_provider, hlsl::create_hlsl_symbol_provider,
-        symbol_parser::ShaderWordRange, wgsl::create_wgsl_symbol_provider,
+        glsl::create_glsl_symbol_provider,
+        hlsl::{create_hlsl_symbol_provider, HlslConditionEvaluator},
+        symbol_parser::ShaderWordRange,
+        wgsl::create_wgsl_symbol_provider,
     },
 };
 
 use super::{
     prepocessor::{
-        ShaderPreprocessor, ShaderPreprocessorContext, ShaderPreprocessorDefine,
-        ShaderPreprocessorInclude, ShaderPreprocessorMode,
+        ShaderMacroExpansion, ShaderPreprocessor, ShaderPreprocessorContext,
+        ShaderPreprocessorDefine, ShaderPreprocessorInclude, ShaderPreprocessorMode, ShaderRegion,
     },
     shader_module::{ShaderModule, ShaderModuleHandle, ShaderSymbols},
     shader_module_parser::ShaderModuleParser,
-    symbol_list::ShaderSymbolList,
+    symbol_list::{ShaderSymbolList, ShaderSymbolListRef},
     symbol_parser::{
-        ShaderSymbolListBuilder, SymbolRegionFinder, SymbolTreeParser,
-        SymbolTreePreprocessorParser, SymbolWordProvider,
+        ShaderSymbolListBuilder, SymbolEntryPointFinder, SymbolModuleParser, SymbolRegionFinder,
+        SymbolTreeParser, SymbolTreePreprocessorParser, SymbolWordProvider,
+    },
+    symbols::{
+        ShaderEntryPoint, ShaderRenameEdit, ShaderScope, ShaderSymbol, ShaderSymbolData,
+        ShaderSymbolMode,
     },
-    symbols::{ShaderScope, ShaderSymbol},
 };
 
 /// A symbol provider is responsible of querying a file using tree-sitter AST in order to find all [`ShaderSymbol`] and return them to user as a [`ShaderSymbolList`]
 /// It performs on a [`ShaderModule`] which need to be created by a [`ShaderModuleParser`]
 pub struct SymbolProvider {
     symbol_parsers: Vec<(Box<dyn SymbolTreeParser>, tree_sitter::Query)>,
+    module_parsers: Vec<Box<dyn SymbolModuleParser>>,
     scope_query: Query,
     error_query: Query,
 
     preprocessor_parsers: Vec<(Box<dyn SymbolTreePreprocessorParser>, tree_sitter::Query)>,
     region_finder: Box<dyn SymbolRegionFinder>,
     word_provider: Box<dyn SymbolWordProvider>,
+    entry_point_finder: Box<dyn SymbolEntryPointFinder>,
 }
 
 pub type SymbolIncludeCallback<'a> =
@@ -77,9 +84,11 @@ impl SymbolProvider {
     pub(crate) fn new(
         language: &tree_sitter::Language,
         parsers: Vec<Box<dyn SymbolTreeParser>>,
+        module_parsers: Vec<Box<dyn SymbolModuleParser>>,
         preprocessor_parsers: Vec<Box<dyn SymbolTreePreprocessorParser>>,
         region_finder: Box<dyn SymbolRegionFinder>,
         word_provider: Box<dyn SymbolWordProvider>,
+        entry_point_finder: Box<dyn SymbolEntryPointFinder>,
     ) -> Self {
         let scope_query = r#"(compound_statement
             "{"? @scope.start
@@ -95,6 +104,7 @@ impl SymbolProvider {
                     (e, query)
                 })
                 .collect(),
+            module_parsers,
             scope_query: tree_sitter::Query::new(language, scope_query).unwrap(),
             error_query: tree_sitter::Query::new(language, error_query).unwrap(),
             preprocessor_parsers: preprocessor_parsers
@@ -107,6 +117,7 @@ impl SymbolProvider {
                 .collect(),
             region_finder: region_finder,
             word_provider,
+            entry_point_finder,
         }
     }
     pub fn query_file_scopes(&self, shader_module: &ShaderModule) -> Vec<ShaderScope> {
@@ -150,8 +161,12 @@ impl SymbolProvider {
         context: &mut ShaderPreprocessorContext,
         shader_params: &ShaderCompilationParams,
         include_callback: &'a mut SymbolIncludeCallback<'a>,
-        old_symbols: Option<ShaderSymbols>,
+        mut old_symbols: Option<ShaderSymbols>,
     ) -> Result<ShaderSymbols, ShaderError> {
+        // Preprocessor only reuse old preprocessor, keep old symbols in case module fail to parse.
+        let old_symbol_list = old_symbols
+            .as_mut()
+            .map(|old_symbols| std::mem::take(&mut old_symbols.symbol_list));
         // Either we create it from context, or we store it in context (no need to store 2 ref to it).
         let preprocessor = self.query_preprocessor(
             shader_module,
@@ -164,7 +179,14 @@ impl SymbolProvider {
             ShaderSymbolList::default() // if once, no symbols.
         } else {
             // TODO: should not always need to recompute this.
-            self.query_file_symbols(shader_module, &shader_params)?
+            self.query_file_symbols(
+                shader_module,
+                &ShaderParams {
+                    context: context.get_context_params(),
+                    compilation: shader_params.clone(),
+                },
+                old_symbol_list,
+            )?
         };
         Ok(ShaderSymbols {
             preprocessor,
@@ -318,16 +340,15 @@ impl SymbolProvider {
                 shader_module.content.as_bytes(),
             );
             while let Some(symbol_match) = all_matches.next() {
-                preprocessor.diagnostics.push(ShaderDiagnostic {
-                    severity: ShaderDiagnosticSeverity::Warning,
-                    error:
-                        "Failed to parse this code. Some symbols might be missing from providers."
-                            .into(),
-                    range: ShaderFileRange::from(
+                preprocessor.diagnostics.push(ShaderDiagnostic::new(
+                    ShaderDiagnosticSeverity::Warning,
+                    "Failed to parse this code. Some symbols might be missing from providers."
+                        .into(),
+                    ShaderFileRange::from(
                         shader_module.file_path.clone(),
                         ShaderRange::from(symbol_match.captures[0].node.range()),
                     ),
-                });
+                ));
             }
             Ok(preprocessor)
         } else {
@@ -380,12 +401,13 @@ impl SymbolProvider {
     fn query_file_symbols(
         &self,
         shader_module: &ShaderModule,
-        shader_compilation_params: &ShaderCompilationParams,
+        shader_params: &ShaderParams,
+        old_symbol_list: Option<ShaderSymbolList>,
     ) -> Result<ShaderSymbolList, ShaderError> {
         let filter_symbol = |symbol: &ShaderSymbol| -> bool {
             // Dont filter inactive regions here on parsing, to avoid recomputing all symbols on regions update.
             match &symbol.requirement {
-                Some(requirement) => requirement.is_met(shader_compilation_params),
+                Some(requirement) => requirement.is_met(&shader_params.compilation),
                 None => true, // Not filtered
             }
         };
@@ -408,6 +430,17 @@ impl SymbolProvider {
                 );
             }
         }
+        for parser in &self.module_parsers {
+            if parser
+                .process_module(shader_module, shader_params, &mut symbol_list_builder)
+                .is_err()
+            {
+                // Module is being edited and fail to parse, keep last good symbols until it does.
+                if let Some(old_symbol_list) = old_symbol_list {
+                    return Ok(old_symbol_list);
+                }
+            }
+        }
         Ok(symbol_list_builder.get_shader_symbol_list())
     }
     pub fn get_word_range_at_position(
@@ -421,4 +454,319 @@ impl SymbolProvider {
             position,
         )
     }
+    /// Find all entry points declared in the module with their inferred stage.
+    /// Symbols must have been queried from the same module with [`SymbolProvider::query_symbols`].
+    /// Wgsl modules are composed with their imports, which are read through the callback.
+    pub fn query_entry_points(
+        &self,
+        shader_module: &ShaderModule,
+        shader_symbols: &ShaderSymbols,
+        shader_params: &ShaderParams,
+        include_callback: &mut dyn FnMut(&Path) -> Option<String>,
+    ) -> Result<Vec<ShaderEntryPoint>, ShaderError> {
+        self.entry_point_finder.find_entry_points(
+            shader_module,
+            shader_symbols,
+            shader_params,
+            include_callback,
+        )
+    }
+    /// Find all references to a symbol in the module and all its includes.
+    /// Symbol should have been found with [`ShaderWordRange::find_symbol_from_parent`] on the same symbol list.
+    /// Include modules are not stored in [`ShaderSymbols`], so they are requested through the callback.
+    /// Only the include tree of the module is searched: other modules including the file declaring
+    /// the symbol must be queried as well to get all its references.
+    pub fn query_references(
+        &self,
+        shader_module: &ShaderModule,
+        shader_symbols: &ShaderSymbols,
+        symbol_list: &ShaderSymbolListRef,
+        symbol: &ShaderSymbol,
+        include_declaration: bool,
+        include_callback: &mut SymbolIncludeCallback,
+    ) -> Result<Vec<ShaderFileRange>, ShaderError> {
+        let mut references = Vec::new();
+        self.find_references_in_module(
+            shader_module,
+            shader_symbols,
+            symbol_list,
+            symbol,
+            &mut references,
+        )?;
+        // Same file might be included multiple times, first one is the one not guarded.
+        let mut visited_files = HashSet::new();
+        visited_files.insert(shader_module.file_path.clone());
+        let mut result = Ok(());
+        shader_symbols.visit_includes(&mut |include| {
+            if result.is_err() || !visited_files.insert(include.get_absolute_path().to_path_buf()) {
+                return;
+            }
+            result = match include_callback(include) {
+                Ok(Some(include_module)) => self.find_references_in_module(
+                    &RefCell::borrow(&include_module),
+                    include.get_cache(),
+                    symbol_list,
+                    symbol,
+                    &mut references,
+                ),
+                Ok(None) => Ok(()),
+                Err(err) => Err(err),
+            };
+        });
+        result?;
+        // Member declarations cannot be resolved from their own range, so handle declaration separately.
+        let declaration = match &symbol.mode {
+            ShaderSymbolMode::Runtime(runtime) => {
+                Some(runtime.range.clone_into_file(runtime.file_path.clone()))
+            }
+            _ => None,
+        };
+        references.retain(|reference| Some(reference) != declaration.as_ref());
+        if include_declaration {
+            if let Some(declaration) = declaration {
+                references.insert(0, declaration);
+            }
+        }
+        Ok(references)
+    }
+    /// Check if a symbol can be renamed, which is only the case for symbols declared in shader files.
+    pub fn check_rename(&self, symbol: &ShaderSymbol) -> Result<(), ShaderError> {
+        match (&symbol.mode, &symbol.data) {
+            (_, ShaderSymbolData::Include { target: _ }) => Err(ShaderError::InvalidRename(
+                format!("Cannot rename include {}", symbol.label),
+            )),
+            (ShaderSymbolMode::Runtime(_), _) => Ok(()),
+            _ => Err(ShaderError::InvalidRename(format!(
+                "Cannot rename builtin symbol {}",
+                symbol.label
+            ))),
+        }
+    }
+    /// Rename a symbol along with all its references in the module and its includes.
+    /// Symbol list should contain intrinsics from [`crate::symbols::intrinsics::ShaderIntrinsics`] to detect collisions with them.
+    /// As with [`SymbolProvider::query_references`], edits only cover the include tree of the module.
+    pub fn query_rename(
+        &self,
+        shader_module: &ShaderModule,
+        shader_symbols: &ShaderSymbols,
+        symbol_list: &ShaderSymbolListRef,
+        symbol: &ShaderSymbol,
+        new_label: &str,
+        include_callback: &mut SymbolIncludeCallback,
+    ) -> Result<Vec<ShaderRenameEdit>, ShaderError> {
+        self.check_rename(symbol)?;
+        let is_identifier = new_label
+            .chars()
+            .next()
+            .is_some_and(|c| c.is_alphabetic() || c == '_')
+            && new_label.chars().all(|c| c.is_alphanumeric() || c == '_');
+        if !is_identifier {
+            return Err(ShaderError::InvalidRename(format!(
+                "{} is not a valid identifier",
+                new_label
+            )));
+        }
+        let references = self.query_references(
+            shader_module,
+            shader_symbols,
+            symbol_list,
+            symbol,
+            true,
+            include_callback,
+        )?;
+        match &symbol.data {
+            // Members are only accessible through their struct.
+            ShaderSymbolData::Parameter { context, .. }
+            | ShaderSymbolData::Method { context, .. } => {
+                if let Some(ShaderSymbolData::Struct {
+                    members, methods, ..
+                }) = symbol_list.find_type_symbol(context).map(|ty| &ty.data)
+                {
+                    if members.iter().any(|m| m.parameters.label == new_label)
+                        || methods.iter().any(|m| m.label == new_label)
+                    {
+                        return Err(ShaderError::InvalidRename(format!(
+                            "{} already has a member named {}",
+                            context, new_label
+                        )));
+                    }
+                }
+            }
+            _ => {
+                let is_global = |symbol: &ShaderSymbol| match &symbol.mode {
+                    ShaderSymbolMode::Runtime(runtime) => runtime.scope_stack.is_empty(),
+                    _ => true,
+                };
+                // Globals collide even when declared after the symbol.
+                let mut colliding_symbols: Vec<&ShaderSymbol> = if is_global(symbol) {
+                    symbol_list
+                        .find_symbols(new_label)
+                        .into_iter()
+                        .filter(|symbol| is_global(symbol))
+                        .collect()
+                } else {
+                    Vec::new()
+                };
+                for reference in &references {
+                    colliding_symbols.extend(
+                        symbol_list.find_symbols_at(new_label, &reference.start_as_file_position()),
+                    );
+                }
+                if let Some(colliding_symbol) = colliding_symbols.first() {
+                    return Err(ShaderError::InvalidRename(match &colliding_symbol.mode {
+                        ShaderSymbolMode::Runtime(runtime) => format!(
+                            "{} collides with symbol declared in {}:{}",
+                            new_label,
+                            runtime.file_path.display(),
+                            runtime.range.start.line + 1
+                        ),
+                        _ => format!("{} collides with a builtin symbol", new_label),
+                    }));
+                }
+            }
+        }
+        let mut edits: Vec<ShaderRenameEdit> = Vec::new();
+        for reference in references {
+            match edits
+                .iter_mut()
+                .find(|edit| edit.file_path == reference.file_path)
+            {
+                Some(edit) => edit.ranges.push(reference.range),
+                None => edits.push(ShaderRenameEdit {
+                    file_path: reference.file_path,
+                    ranges: vec![reference.range],
+                }),
+            }
+        }
+        Ok(edits)
+    }
+    fn find_references_in_module(
+        &self,
+        shader_module: &ShaderModule,
+        shader_symbols: &ShaderSymbols,
+        symbol_list: &ShaderSymbolListRef,
+        symbol: &ShaderSymbol,
+        references: &mut Vec<ShaderFileRange>,
+    ) -> Result<(), ShaderError> {
+        let content = shader_module.content.as_str();
+        let inactive_regions: Vec<&ShaderRegion> = shader_symbols
+            .get_preprocessor()
+            .regions
+            .iter()
+            .filter(|region| !region.is_active)
+            .collect();
+        let is_identifier = |c: char| c.is_alphanumeric() || c == '_';
+        for (start, label) in content.match_indices(symbol.label.as_str()) {
+            let end = start + label.len();
+            // Only match whole identifiers.
+            if content[..start]
+                .chars()
+                .next_back()
+                .is_some_and(is_identifier)
+                || content[end..].chars().next().is_some_and(is_identifier)
+            {
+                continue;
+            }
+            let is_comment = shader_module
+                .tree
+                .root_node()
+                .descendant_for_byte_range(start, end)
+                .is_some_and(|node| node.kind().contains("comment"));
+            if is_comment {
+                continue;
+            }
+            let range = ShaderRange::new(
+                ShaderPosition::from_byte_offset(content, start)?,
+                ShaderPosition::from_byte_offset(content, end)?,
+            );
+            if inactive_regions
+                .iter()
+                .any(|region| region.range.contain_bounds(&range))
+            {
+                continue;
+            }
+            let word = match self.get_word_range_at_position(shader_module, &range.start) {
+                Ok(word) => word,
+                Err(ShaderError::NoSymbol) => continue,
+                Err(err) => return Err(err),
+            };
+            if *word.get_range() != range {
+                continue; // Part of another word, such as an include path.
+            }
+            // When a symbol is shadowed, the innermost one is the one referenced.
+            let get_depth = |symbol: &ShaderSymbol| match &symbol.mode {
+                ShaderSymbolMode::Runtime(runtime) => runtime.scope_stack.len(),
+                _ => 0,
+            };
+            let matching_symbols =
+                word.find_symbol_from_parent(shader_module.file_path.clone(), symbol_list);
+            let depth = matching_symbols.iter().map(get_depth).max();
+            if matching_symbols.iter().any(|matching_symbol| {
+                Some(get_depth(matching_symbol)) == depth && matching_symbol.is_same_symbol(symbol)
+            }) {
+                references.push(range.into_file(shader_module.file_path.clone()));
+            }
+        }
+        Ok(())
+    }
+    /// Expand the macro invoked at position, using the defines available at this point.
+    /// Return None if there is no macro invocation at this position.
+    pub fn expand_macro_at_position(
+        &self,
+        shader_module: &ShaderModule,
+        shader_symbols: &ShaderSymbols,
+        position: &ShaderPosition,
+    ) -> Result<Option<ShaderMacroExpansion>, ShaderError> {
+        let word = match self.get_word_range_at_position(shader_module, position) {
+            Ok(word) => word,
+            Err(ShaderError::NoSymbol) => return Ok(None),
+            Err(err) => return Err(err),
+        };
+        let context = shader_symbols.get_context_at(&word.get_range().start);
+        let is_function_like = match context.get_define(word.get_word()) {
+            Some((_, parameters)) => parameters.is_some(),
+            None => return Ok(None),
+        };
+        let content = shader_module.content.as_str();
+        let start = word.get_range().start.to_byte_offset(content)?;
+        let mut end = word.get_range().end.to_byte_offset(content)?;
+        if is_function_like {
+            // Include arguments in invocation.
+            let bytes = content.as_bytes();
+            let mut offset = end;
+            while offset < bytes.len() && bytes[offset].is_ascii_whitespace() {
+                offset += 1;
+            }
+            if offset >= bytes.len() || bytes[offset] != b'(' {
+                return Ok(None); // Not an invocation.
+            }
+            let mut depth = 0;
+            while offset < bytes.len() {
+                match bytes[offset] {
+                    b'(' => depth += 1,
+                    b')' => depth -= 1,
+                    _ => {}
+                }
+                offset += 1;
+                if depth == 0 {
+                    break;
+                }
+            }
+            if depth != 0 {
+                return Ok(None); // Unterminated invocation.
+            }
+            end = offset;
+        }
+        let expansion =
+            HlslConditionEvaluator::expand_invocation(shader_module, start, end, &context)?;
+        Ok(Some(ShaderMacroExpansion {
+            name: word.get_word().into(),
+            range: ShaderFileRange::new(
+                shader_module.file_path.clone(),
+                ShaderPosition::from_byte_offset(content, start)?,
+                ShaderPosition::from_byte_offset(content, end)?,
+            ),
+            expansion,
+        }))
+    }
 }
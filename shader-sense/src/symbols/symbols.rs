@@ -4,8 +4,8 @@ use std::path::PathBuf;
 use serde::{Deserialize, Serialize};
 
 use crate::{
-    position::ShaderRange,
-    shader::{HlslShaderModel, HlslVersion, ShaderCompilationParams, ShaderStageMask},
+    position::{ShaderFileRange, ShaderRange},
+    shader::{HlslShaderModel, HlslVersion, ShaderCompilationParams, ShaderStage, ShaderStageMask},
 };
 
 #[derive(Debug, Default, Serialize, Deserialize, Clone)]
@@ -222,6 +222,8 @@ pub enum ShaderSymbolData {
     },
     Macro {
         value: String,
+        #[serde(default)]
+        parameters: Option<Vec<String>>, // None for object-like macro.
     },
 }
 
@@ -240,8 +242,9 @@ pub struct HlslRequirementParameter {
 #[derive(Default, Debug, Serialize, Deserialize, Clone)]
 pub struct GlslRequirementParameter {
     pub stages: Option<ShaderStageMask>,
-    pub min_version: Option<u32>,  // min glsl version
-    pub extension: Option<String>, // Extension required for this symbol.
+    pub min_version: Option<u32>,    // min glsl version
+    pub min_es_version: Option<u32>, // min glsl es version, None if desktop only.
+    pub extension: Option<String>,   // Extension required for this symbol.
 }
 #[allow(non_snake_case)] // for JSON
 #[derive(Default, Debug, Serialize, Deserialize, Clone)]
@@ -316,13 +319,31 @@ impl RequirementParameter {
                     },
                     None => true, // No requirements, dont care about stage set.
                 };
-                let is_version_ok = match &requirement.min_version {
-                    Some(_min_version) => true, // TODO: make this work.
-                    None => true,
+                // Without version, file is most likely an include, so rely on the version of the target client.
+                let version = shader_compilation_params.glsl.get_version();
+                let is_version_ok = if shader_compilation_params.glsl.is_opengl_es() {
+                    // ES versions cannot be compared to desktop ones.
+                    match (&requirement.min_es_version, &requirement.min_version) {
+                        (Some(min_es_version), _) => *min_es_version <= version,
+                        (None, Some(_)) => false, // Desktop only symbol.
+                        (None, None) => true,
+                    }
+                } else {
+                    match &requirement.min_version {
+                        Some(min_version) => *min_version <= version,
+                        None => true,
+                    }
                 };
-                let is_extension_ok = match &requirement.extension {
-                    Some(_extension) => true, // TODO: make this work.
-                    None => true,
+                let is_extension_ok = match (
+                    &requirement.extension,
+                    shader_compilation_params.glsl.version,
+                ) {
+                    (Some(extension), Some(_)) => shader_compilation_params
+                        .glsl
+                        .extensions
+                        .iter()
+                        .any(|enabled_extension| enabled_extension == extension),
+                    _ => true,
                 };
                 is_stage_ok && is_version_ok && is_extension_ok
             }
@@ -367,6 +388,21 @@ impl ShaderSymbolRuntime {
     }
 }
 
+/// Entry point declared in a shader module, with the stage it is inferred for.
+#[derive(Debug, Clone)]
+pub struct ShaderEntryPoint {
+    pub label: String,
+    pub stage: ShaderStage,
+    pub range: ShaderFileRange, // Range of the function label.
+}
+
+/// Edits to apply to a single file in order to rename a symbol.
+#[derive(Debug, Clone)]
+pub struct ShaderRenameEdit {
+    pub file_path: PathBuf,
+    pub ranges: Vec<ShaderRange>, // Ranges to replace with the new label.
+}
+
 #[derive(Debug, Clone)]
 pub struct ShaderSymbolRuntimeContext {}
 
@@ -478,6 +514,19 @@ impl ShaderSymbolType {
 }
 
 impl ShaderSymbol {
+    // Check if both symbols refer to the same declaration.
+    pub fn is_same_symbol(&self, other: &ShaderSymbol) -> bool {
+        self.label == other.label
+            && self.get_type() == other.get_type()
+            && match (&self.mode, &other.mode) {
+                (ShaderSymbolMode::Runtime(lhs), ShaderSymbolMode::Runtime(rhs)) => {
+                    lhs.file_path == rhs.file_path && lhs.range == rhs.range
+                }
+                (ShaderSymbolMode::RuntimeContext(_), ShaderSymbolMode::RuntimeContext(_)) => true,
+                (ShaderSymbolMode::Intrinsic(_), ShaderSymbolMode::Intrinsic(_)) => true,
+                _ => false,
+            }
+    }
     pub fn is_type(&self, ty: ShaderSymbolType) -> bool {
         match self.get_type() {
             Some(tty) => tty == ty,
@@ -522,7 +571,10 @@ impl ShaderSymbol {
             ShaderSymbolData::Functions { signatures: _ } => Some(ShaderSymbolType::Functions),
             ShaderSymbolData::Keyword {} => Some(ShaderSymbolType::Keyword),
             ShaderSymbolData::Include { target: _ } => Some(ShaderSymbolType::Include),
-            ShaderSymbolData::Macro { value: _ } => Some(ShaderSymbolType::Macros),
+            ShaderSymbolData::Macro {
+                value: _,
+                parameters: _,
+            } => Some(ShaderSymbolType::Macros),
         }
     }
     pub fn format(&self) -> String {
@@ -586,9 +638,15 @@ impl ShaderSymbol {
             ShaderSymbolData::Include { target: _ } => {
                 format!("#include \"{}\"", self.label)
             }
-            ShaderSymbolData::Macro { value } => {
-                format!("#define {} {}", self.label, value)
-            }
+            ShaderSymbolData::Macro { value, parameters } => match parameters {
+                Some(parameters) => format!(
+                    "#define {}({}) {}",
+                    self.label,
+                    parameters.join(", "),
+                    value
+                ),
+                None => format!("#define {} {}", self.label, value),
+            },
         }
     }
 }
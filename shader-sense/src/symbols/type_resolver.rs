@@ -0,0 +1,436 @@
+//! Lightweight type inference of expressions over the tree-sitter AST
+use tree_sitter::Node;
+
+use crate::{
+    position::{ShaderPosition, ShaderRange},
+    symbols::{
+        shader_module::ShaderModule,
+        symbol_list::ShaderSymbolListRef,
+        symbol_parser::{get_name, ShaderWordRange},
+        symbols::{
+            ShaderSymbol, ShaderSymbolArray, ShaderSymbolData, ShaderSymbolIntrinsic,
+            ShaderSymbolMode,
+        },
+    },
+};
+
+/// Type inferred for an expression.
+#[derive(Debug, Clone)]
+pub struct ShaderExpressionType {
+    pub ty: String,                       // Type label, without template arguments.
+    pub template: Option<String>,         // First template argument, such as buffer element.
+    pub count: Option<ShaderSymbolArray>, // Set if the expression is an array.
+}
+
+impl ShaderExpressionType {
+    pub fn new(ty: &str, count: Option<ShaderSymbolArray>) -> Self {
+        match (ty.find('<'), ty.rfind('>')) {
+            (Some(start), Some(end)) if start < end => Self {
+                ty: ty[..start].trim().into(),
+                template: ty[start + 1..end]
+                    .split(',')
+                    .next()
+                    .map(|template| template.trim().to_string())
+                    .filter(|template| !template.is_empty()),
+                count,
+            },
+            _ => Self {
+                ty: ty.trim().into(),
+                template: None,
+                count,
+            },
+        }
+    }
+}
+
+// Numeric types are intrinsics, but their components are not declared as members.
+enum ShaderNumericType {
+    Vector { scalar: String, size: u32 },           // floatN
+    Matrix { scalar: String, columns: u32 },        // floatRxC
+    GlslVector { prefix: &'static str, size: u32 }, // vecN, ivecN...
+    GlslMatrix { prefix: &'static str, rows: u32 }, // matN, matCxR, dmatN...
+}
+
+impl ShaderNumericType {
+    const SWIZZLE_SETS: [&'static str; 3] = ["xyzw", "rgba", "stpq"];
+
+    fn parse(ty: &str) -> Option<Self> {
+        fn parse_size(size: &str) -> Option<u32> {
+            size.parse::<u32>()
+                .ok()
+                .filter(|size| (1..=4).contains(size))
+        }
+        for prefix in ["", "i", "u", "b", "d"] {
+            if let Some(size) = ty
+                .strip_prefix(prefix)
+                .and_then(|ty| ty.strip_prefix("vec"))
+            {
+                return parse_size(size).map(|size| Self::GlslVector { prefix, size });
+            }
+        }
+        for prefix in ["", "d"] {
+            if let Some(size) = ty
+                .strip_prefix(prefix)
+                .and_then(|ty| ty.strip_prefix("mat"))
+            {
+                let rows = match size.split_once('x') {
+                    Some((_, rows)) => rows,
+                    None => size,
+                };
+                return parse_size(rows).map(|rows| Self::GlslMatrix { prefix, rows });
+            }
+        }
+        let scalar = ty.trim_end_matches(|c: char| c.is_ascii_digit() || c == 'x');
+        if scalar.is_empty() || !scalar.ends_with(|c: char| c.is_ascii_alphabetic()) {
+            return None;
+        }
+        match ty[scalar.len()..].split_once('x') {
+            Some((rows, columns)) => {
+                parse_size(rows)?;
+                Some(Self::Matrix {
+                    scalar: scalar.into(),
+                    columns: parse_size(columns)?,
+                })
+            }
+            None => Some(Self::Vector {
+                scalar: scalar.into(),
+                size: parse_size(&ty[scalar.len()..])?,
+            }),
+        }
+    }
+    fn get_vector_label(&self, size: u32) -> String {
+        match self {
+            Self::Vector { scalar, .. } | Self::Matrix { scalar, .. } => match size {
+                1 => scalar.clone(),
+                size => format!("{}{}", scalar, size),
+            },
+            Self::GlslVector { prefix, .. } | Self::GlslMatrix { prefix, .. } => match size {
+                1 => match *prefix {
+                    "i" => "int",
+                    "u" => "uint",
+                    "b" => "bool",
+                    "d" => "double",
+                    _ => "float",
+                }
+                .into(),
+                size => format!("{}vec{}", prefix, size),
+            },
+        }
+    }
+    fn get_size(&self) -> Option<u32> {
+        match self {
+            Self::Vector { size, .. } | Self::GlslVector { size, .. } => Some(*size),
+            Self::Matrix { .. } | Self::GlslMatrix { .. } => None,
+        }
+    }
+    // Type returned by subscript. HLSL matrices are indexed by row, GLSL ones by column.
+    fn get_element_type(&self) -> String {
+        match self {
+            Self::Vector { .. } | Self::GlslVector { .. } => self.get_vector_label(1),
+            Self::Matrix { columns, .. } => self.get_vector_label(*columns),
+            Self::GlslMatrix { rows, .. } => self.get_vector_label(*rows),
+        }
+    }
+    fn get_swizzle_type(&self, swizzle: &str) -> Option<String> {
+        let size = self.get_size()?;
+        if swizzle.is_empty() || swizzle.len() > 4 {
+            return None;
+        }
+        let is_valid = Self::SWIZZLE_SETS.iter().any(|set| {
+            swizzle
+                .chars()
+                .all(|c| set.find(c).is_some_and(|index| (index as u32) < size))
+        });
+        if is_valid {
+            Some(self.get_vector_label(swizzle.len() as u32))
+        } else {
+            None
+        }
+    }
+    fn get_components(&self) -> Vec<String> {
+        match self.get_size() {
+            Some(size) => Self::SWIZZLE_SETS[0][..size as usize]
+                .chars()
+                .map(|c| c.to_string())
+                .collect(),
+            None => Vec::new(),
+        }
+    }
+}
+
+/// Infer the type of expressions from the AST of a module and its symbols.
+/// The list should contain intrinsics to resolve their types and methods.
+pub struct ShaderTypeResolver<'a> {
+    shader_module: &'a ShaderModule,
+    symbol_list: &'a ShaderSymbolListRef<'a>,
+}
+
+impl<'a> ShaderTypeResolver<'a> {
+    const EXPRESSION_KINDS: [&'static str; 6] = [
+        "identifier",
+        "field_expression",
+        "call_expression",
+        "subscript_expression",
+        "parenthesized_expression",
+        "cast_expression",
+    ];
+
+    pub fn new(shader_module: &'a ShaderModule, symbol_list: &'a ShaderSymbolListRef<'a>) -> Self {
+        Self {
+            shader_module,
+            symbol_list,
+        }
+    }
+    /// Find symbols matching the word, resolving the type of its parent expression when it is a field.
+    /// Fallback to [`ShaderWordRange::find_symbol_from_parent`] when the type cannot be inferred.
+    pub fn find_word_symbols(&self, word: &ShaderWordRange) -> Vec<ShaderSymbol> {
+        if word.is_field() {
+            if let Some(symbols) = self.find_field_symbols(word.get_range()) {
+                return symbols;
+            }
+        }
+        word.find_symbol_from_parent(self.shader_module.file_path.clone(), self.symbol_list)
+    }
+    /// Infer the type of the expression ending at the given position, such as the one preceding a `.`.
+    pub fn resolve_expression_at(&self, position: &ShaderPosition) -> Option<ShaderExpressionType> {
+        let byte_offset = position.to_byte_offset(&self.shader_module.content).ok()?;
+        if byte_offset == 0 {
+            return None;
+        }
+        let mut node = self
+            .shader_module
+            .tree
+            .root_node()
+            .descendant_for_byte_range(byte_offset - 1, byte_offset)?;
+        // Climb up to the outermost expression ending here, such as a call from its closing parenthesis.
+        let mut expression = None;
+        while node.end_byte() == byte_offset {
+            if Self::EXPRESSION_KINDS.contains(&node.kind()) {
+                expression = Some(node);
+            } else if node.is_named()
+                && ![
+                    "argument_list",
+                    "field_identifier",
+                    "subscript_argument_list",
+                ]
+                .contains(&node.kind())
+            {
+                break;
+            }
+            node = match node.parent() {
+                Some(parent) => parent,
+                None => break,
+            };
+        }
+        self.resolve_expression(expression?)
+    }
+    /// Infer the type of an expression node.
+    pub fn resolve_expression(&self, node: Node) -> Option<ShaderExpressionType> {
+        let content = &self.shader_module.content;
+        match node.kind() {
+            "identifier" => self.get_symbol_type(self.find_symbol_at(node)?),
+            "parenthesized_expression" => self.resolve_expression(node.named_child(0)?),
+            "cast_expression" => Some(ShaderExpressionType::new(
+                get_name(content, node.child_by_field_name("type")?),
+                None,
+            )),
+            "call_expression" => {
+                let function = node.named_child(0)?;
+                if function.kind() == "field_expression" {
+                    // Method call
+                    let object_ty = self.resolve_expression(function.named_child(0)?)?;
+                    let label = get_name(content, function.child_by_field_name("field")?);
+                    self.find_member_symbols(&object_ty, label)
+                        .iter()
+                        .find_map(|method| match &method.data {
+                            ShaderSymbolData::Method { signatures, .. } => {
+                                let return_type = signatures.first()?.returnType.as_str();
+                                // Methods of templated intrinsics return their template argument.
+                                match &object_ty.template {
+                                    Some(template)
+                                        if self.get_template_parameter(&object_ty)
+                                            == Some(return_type) =>
+                                    {
+                                        Some(ShaderExpressionType::new(template, None))
+                                    }
+                                    _ => Some(ShaderExpressionType::new(return_type, None)),
+                                }
+                            }
+                            _ => None,
+                        })
+                } else {
+                    // Function call or constructor
+                    let symbol = self.find_symbol_at(function)?;
+                    match &symbol.data {
+                        ShaderSymbolData::Functions { signatures } => Some(
+                            ShaderExpressionType::new(&signatures.first()?.returnType, None),
+                        ),
+                        ShaderSymbolData::Types { .. } | ShaderSymbolData::Struct { .. } => {
+                            Some(ShaderExpressionType::new(&symbol.label, None))
+                        }
+                        _ => None,
+                    }
+                }
+            }
+            "subscript_expression" => {
+                let ty = self.resolve_expression(node.named_child(0)?)?;
+                if ty.count.is_some() {
+                    Some(ShaderExpressionType { count: None, ..ty })
+                } else if let Some(template) = &ty.template {
+                    // Buffers & textures return their template argument.
+                    Some(ShaderExpressionType::new(template, None))
+                } else {
+                    let element_ty = ShaderNumericType::parse(&ty.ty)?.get_element_type();
+                    Some(ShaderExpressionType::new(&element_ty, None))
+                }
+            }
+            "field_expression" => {
+                let ty = self.resolve_expression(node.named_child(0)?)?;
+                let label = get_name(content, node.child_by_field_name("field")?);
+                self.find_member_symbols(&ty, label)
+                    .iter()
+                    .find_map(|member| self.get_symbol_type(member))
+            }
+            _ => None,
+        }
+    }
+    /// Get all members and methods of a type, or the components of a vector.
+    pub fn find_members(&self, ty: &ShaderExpressionType) -> Vec<ShaderSymbol> {
+        if ty.count.is_some() {
+            return Vec::new(); // Arrays need to be indexed first.
+        }
+        match self.symbol_list.find_type_symbol(&ty.ty) {
+            Some(ShaderSymbol {
+                data:
+                    ShaderSymbolData::Struct {
+                        members, methods, ..
+                    },
+                mode,
+                ..
+            }) => {
+                let file_path = mode.map_runtime().map(|runtime| runtime.file_path.clone());
+                members
+                    .iter()
+                    .map(|member| member.as_symbol(file_path.clone()))
+                    .chain(
+                        methods
+                            .iter()
+                            .map(|method| method.as_symbol(file_path.clone())),
+                    )
+                    .collect()
+            }
+            _ => match ShaderNumericType::parse(&ty.ty) {
+                Some(numeric_ty) => numeric_ty
+                    .get_components()
+                    .iter()
+                    .filter_map(|component| {
+                        numeric_ty.get_swizzle_type(component).map(|swizzle_ty| {
+                            Self::get_swizzle_symbol(&ty.ty, component, swizzle_ty)
+                        })
+                    })
+                    .collect(),
+                None => Vec::new(),
+            },
+        }
+    }
+    fn find_member_symbols(&self, ty: &ShaderExpressionType, label: &str) -> Vec<ShaderSymbol> {
+        let members: Vec<ShaderSymbol> = self
+            .find_members(ty)
+            .into_iter()
+            .filter(|member| member.label == label)
+            .collect();
+        if !members.is_empty() || ty.count.is_some() {
+            return members;
+        }
+        match ShaderNumericType::parse(&ty.ty)
+            .and_then(|numeric_ty| numeric_ty.get_swizzle_type(label))
+        {
+            Some(swizzle_ty) => vec![Self::get_swizzle_symbol(&ty.ty, label, swizzle_ty)],
+            None => Vec::new(),
+        }
+    }
+    fn find_field_symbols(&self, range: &ShaderRange) -> Option<Vec<ShaderSymbol>> {
+        let content = &self.shader_module.content;
+        let node = self
+            .shader_module
+            .tree
+            .root_node()
+            .named_descendant_for_byte_range(
+                range.start.to_byte_offset(content).ok()?,
+                range.end.to_byte_offset(content).ok()?,
+            )?;
+        if node.kind() != "field_identifier" {
+            return None;
+        }
+        let field_expression = node
+            .parent()
+            .filter(|parent| parent.kind() == "field_expression")?;
+        let ty = self.resolve_expression(field_expression.named_child(0)?)?;
+        let symbols = self.find_member_symbols(&ty, get_name(content, node));
+        if symbols.is_empty() {
+            None
+        } else {
+            Some(symbols)
+        }
+    }
+    // Find the symbol of the given label node, picking the innermost declaration if shadowed.
+    fn find_symbol_at(&self, node: Node) -> Option<&'a ShaderSymbol> {
+        let position = ShaderRange::from(node.range())
+            .end
+            .clone_into_file(self.shader_module.file_path.clone());
+        self.symbol_list
+            .find_symbols_at(get_name(&self.shader_module.content, node), &position)
+            .into_iter()
+            .max_by_key(|symbol| {
+                symbol
+                    .mode
+                    .map_runtime()
+                    .map_or(0, |runtime| runtime.scope_stack.len())
+            })
+    }
+    fn get_symbol_type(&self, symbol: &ShaderSymbol) -> Option<ShaderExpressionType> {
+        match &symbol.data {
+            ShaderSymbolData::Variables { ty, count }
+            | ShaderSymbolData::Parameter { ty, count, .. } => {
+                Some(ShaderExpressionType::new(ty, count.clone()))
+            }
+            ShaderSymbolData::Constants { ty, .. } => Some(ShaderExpressionType::new(ty, None)),
+            ShaderSymbolData::Functions { signatures }
+            | ShaderSymbolData::Method { signatures, .. } => Some(ShaderExpressionType::new(
+                &signatures.first()?.returnType,
+                None,
+            )),
+            _ => None,
+        }
+    }
+    // Intrinsic types do not declare their template parameter, but their methods return it.
+    // Unlike other return types, it cannot be resolved to a type.
+    fn get_template_parameter(&self, ty: &ShaderExpressionType) -> Option<&'a str> {
+        match &self.symbol_list.find_type_symbol(&ty.ty)?.data {
+            ShaderSymbolData::Struct { methods, .. } => methods
+                .iter()
+                .map(|method| method.signature.returnType.as_str())
+                .find(|return_type| {
+                    *return_type != "void"
+                        && self.symbol_list.find_type_symbol(return_type).is_none()
+                        && ShaderNumericType::parse(return_type).is_none()
+                }),
+            _ => None,
+        }
+    }
+    fn get_swizzle_symbol(ty: &str, label: &str, swizzle_ty: String) -> ShaderSymbol {
+        ShaderSymbol {
+            label: label.into(),
+            requirement: None,
+            data: ShaderSymbolData::Parameter {
+                context: ty.into(),
+                ty: swizzle_ty,
+                count: None,
+            },
+            mode: ShaderSymbolMode::Intrinsic(ShaderSymbolIntrinsic::new(
+                format!("Swizzle of {} components.", ty),
+                None,
+            )),
+        }
+    }
+}
@@ -1,29 +1,15 @@
 //! Parser specific for WGSL
+mod wgsl_entry_point;
 mod wgsl_parser;
 mod wgsl_regions;
-use wgsl_parser::get_wgsl_parsers;
-use wgsl_regions::WgslRegionFinder;
-
-use crate::{
-    position::ShaderPosition, shader_error::ShaderError, symbols::symbol_parser::ShaderWordRange,
-};
+mod wgsl_word;
 
-use super::{
-    shader_module::ShaderModule, symbol_parser::SymbolWordProvider, symbol_provider::SymbolProvider,
-};
-
-struct WgslSymbolWordProvider {}
+use wgsl_entry_point::WgslEntryPointFinder;
+use wgsl_parser::{get_wgsl_module_parsers, get_wgsl_parsers};
+use wgsl_regions::WgslRegionFinder;
+use wgsl_word::WgslSymbolWordProvider;
 
-impl SymbolWordProvider for WgslSymbolWordProvider {
-    fn find_word_at_position_in_node(
-        &self,
-        _shader_module: &ShaderModule,
-        _node: tree_sitter::Node,
-        _position: &ShaderPosition,
-    ) -> Result<ShaderWordRange, ShaderError> {
-        return Err(ShaderError::NoSymbol);
-    }
-}
+use super::symbol_provider::SymbolProvider;
 
 pub(super) fn create_wgsl_symbol_provider(
     tree_sitter_language: &tree_sitter::Language,
@@ -31,8 +17,69 @@ pub(super) fn create_wgsl_symbol_provider(
     SymbolProvider::new(
         tree_sitter_language,
         get_wgsl_parsers(),
+        get_wgsl_module_parsers(),
         vec![],
         Box::new(WgslRegionFinder {}),
         Box::new(WgslSymbolWordProvider {}),
+        Box::new(WgslEntryPointFinder {}),
     )
 }
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use crate::{
+        position::ShaderPosition,
+        shader::ShadingLanguage,
+        symbols::{shader_module_parser::ShaderModuleParser, symbol_parser::SymbolWordProvider},
+    };
+
+    use super::WgslSymbolWordProvider;
+
+    #[test]
+    fn test_words() {
+        let file_path = "./test/wgsl/struct.wgsl";
+        let shader_content = std::fs::read_to_string(file_path).unwrap();
+        let word_provider = WgslSymbolWordProvider {};
+        let mut shader_module_parser =
+            ShaderModuleParser::from_shading_language(ShadingLanguage::Wgsl);
+        let shader_module = shader_module_parser
+            .create_module(Path::new(file_path), &shader_content)
+            .unwrap();
+
+        // surface
+        let word = word_provider
+            .find_word_at_position_in_node(
+                &shader_module,
+                shader_module.tree.root_node(),
+                &ShaderPosition::new(19, 22),
+            )
+            .unwrap();
+        assert!(word.get_word() == "surface");
+        assert!(word.get_parent().is_none());
+
+        // surface.materials[0].roughness
+        let word = word_provider
+            .find_word_at_position_in_node(
+                &shader_module,
+                shader_module.tree.root_node(),
+                &ShaderPosition::new(19, 45),
+            )
+            .unwrap();
+        assert!(word.get_word() == "roughness");
+        assert!(word.get_parent().unwrap().get_word() == "materials");
+        assert!(word.get_parent().unwrap().get_parent().unwrap().get_word() == "surface");
+
+        // get_surface().normal
+        let word = word_provider
+            .find_word_at_position_in_node(
+                &shader_module,
+                shader_module.tree.root_node(),
+                &ShaderPosition::new(20, 37),
+            )
+            .unwrap();
+        assert!(word.get_word() == "normal");
+        assert!(word.get_parent().unwrap().get_word() == "get_surface");
+    }
+}
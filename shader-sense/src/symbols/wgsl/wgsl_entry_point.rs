@@ -0,0 +1,67 @@
+use std::path::Path;
+
+use crate::{
+    shader::{ShaderParams, ShaderStage},
+    shader_error::ShaderError,
+    symbols::{
+        shader_module::{ShaderModule, ShaderSymbols},
+        symbol_parser::SymbolEntryPointFinder,
+        symbols::ShaderEntryPoint,
+    },
+    validator::wgsl_composer::{WgslComposer, WgslImportPathIndex},
+};
+
+use super::wgsl_parser::{find_declaration_label, to_shader_range};
+
+/// Entry points are declared with @vertex, @fragment & @compute attributes, which naga resolve for us.
+/// Module is composed with its imports first, as naga cannot parse it otherwise.
+pub struct WgslEntryPointFinder {}
+
+impl SymbolEntryPointFinder for WgslEntryPointFinder {
+    fn find_entry_points(
+        &self,
+        shader_module: &ShaderModule,
+        _shader_symbols: &ShaderSymbols,
+        shader_params: &ShaderParams,
+        include_callback: &mut dyn FnMut(&Path) -> Option<String>,
+    ) -> Result<Vec<ShaderEntryPoint>, ShaderError> {
+        let composed_shader = WgslComposer::new(
+            &shader_module.file_path,
+            shader_params,
+            &mut WgslImportPathIndex::default(),
+            include_callback,
+        )
+        .compose(&shader_module.file_path, &shader_module.content);
+        let composed_content = composed_shader.content.as_str();
+        let module = match naga::front::wgsl::parse_str(composed_content) {
+            Ok(module) => module,
+            Err(_) => return Ok(Vec::new()), // Reported by validator.
+        };
+        Ok(module
+            .entry_points
+            .iter()
+            .filter_map(|entry_point| {
+                let stage = match entry_point.stage {
+                    naga::ShaderStage::Vertex => ShaderStage::Vertex,
+                    naga::ShaderStage::Fragment => ShaderStage::Fragment,
+                    naga::ShaderStage::Compute => ShaderStage::Compute,
+                };
+                // Entry points do not have any span, look for them in whole composed content.
+                let label_range =
+                    find_declaration_label(composed_content, None, "fn", &entry_point.name)?;
+                let range = composed_shader
+                    .source_map
+                    .map_range(&to_shader_range(composed_content, &label_range)?)?;
+                // Entry points of imported modules are not the ones of this module.
+                if range.file_path != shader_module.file_path {
+                    return None;
+                }
+                Some(ShaderEntryPoint {
+                    label: entry_point.name.clone(),
+                    stage,
+                    range,
+                })
+            })
+            .collect())
+    }
+}
@@ -1,75 +1,629 @@
-use std::path::Path;
+use std::{ops::Range, path::Path, sync::LazyLock};
+
+use naga::{
+    valid::{Capabilities, FunctionInfo, ModuleInfo, ValidationFlags, Validator},
+    Expression, TypeInner,
+};
+use regex::Regex;
 
 use crate::{
-    position::ShaderRange,
+    position::{ShaderPosition, ShaderRange},
+    shader::ShaderParams,
+    shader_error::ShaderError,
     symbols::{
-        symbol_parser::{get_name, ShaderSymbolListBuilder, SymbolTreeParser},
+        shader_module::ShaderModule,
+        symbol_parser::{ShaderSymbolListBuilder, SymbolModuleParser, SymbolTreeParser},
         symbols::{
-            ShaderMember, ShaderParameter, ShaderScope, ShaderSymbol, ShaderSymbolData,
-            ShaderSymbolMode, ShaderSymbolRuntime,
+            ShaderMember, ShaderParameter, ShaderScope, ShaderSignature, ShaderSymbol,
+            ShaderSymbolData, ShaderSymbolMode, ShaderSymbolRuntime,
         },
     },
+    validator::wgsl_composer::{WgslComposedShader, WgslComposer, WgslImportPathIndex},
 };
 
 pub fn get_wgsl_parsers() -> Vec<Box<dyn SymbolTreeParser>> {
     vec![]
 }
 
-#[allow(dead_code)] // For now, dont pollute Wgsl as its not ready
-struct WgslStructTreeParser {}
-
-impl SymbolTreeParser for WgslStructTreeParser {
-    fn get_query(&self) -> String {
-        r#"(struct_declaration
-            name: (identifier) @struct.type
-            ((struct_member
-                (variable_identifier_declaration
-                    name: (identifier) @struct.param.type
-                    type: (type_declaration) @struct.param.decl
-                )
-            )(",")?)*
-        )"#
-        .into()
+pub fn get_wgsl_module_parsers() -> Vec<Box<dyn SymbolModuleParser>> {
+    vec![Box::new(WgslNagaModuleParser {})]
+}
+
+// Convert a byte range into a shader range.
+pub(super) fn to_shader_range(content: &str, range: &Range<usize>) -> Option<ShaderRange> {
+    Some(ShaderRange::new(
+        ShaderPosition::from_byte_offset(content, range.start).ok()?,
+        ShaderPosition::from_byte_offset(content, range.end).ok()?,
+    ))
+}
+
+// Label of declarations, introduced by their keyword. Template list is only used by var.
+static DECLARATION_REGEX: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"\b(fn|let|const|override|struct|alias|var)(?:\s*<[^>]*>)?\s+([A-Za-z_]\w*)\b")
+        .unwrap()
+});
+// Label of struct members and function parameters, followed by their type.
+static TYPED_LABEL_REGEX: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"\b([A-Za-z_]\w*)\s*:").unwrap());
+
+// Find the labels of declarations introduced by keyword, within span.
+fn find_declaration_labels<'a>(
+    content: &'a str,
+    span: Range<usize>,
+    keyword: &'a str,
+    label: &'a str,
+) -> impl Iterator<Item = Range<usize>> + 'a {
+    DECLARATION_REGEX
+        .captures_iter(&content[span.clone()])
+        .filter(move |c| &c[1] == keyword && &c[2] == label)
+        .filter_map(|c| c.get(2))
+        .map(move |m| span.start + m.start()..span.start + m.end())
+}
+
+// Find the label of a declaration introduced by keyword, within span if defined, or whole content.
+pub(super) fn find_declaration_label(
+    content: &str,
+    span: Option<Range<usize>>,
+    keyword: &str,
+    label: &str,
+) -> Option<Range<usize>> {
+    let span = span.unwrap_or(0..content.len());
+    find_declaration_labels(content, span, keyword, label).next()
+}
+
+// Find the label of a struct member or function parameter, which is followed by its type.
+fn find_typed_label(content: &str, span: Range<usize>, label: &str) -> Option<Range<usize>> {
+    TYPED_LABEL_REGEX
+        .captures_iter(&content[span.clone()])
+        .filter_map(|c| c.get(1))
+        .find(|m| m.as_str() == label)
+        .map(|m| span.start + m.start()..span.start + m.end())
+}
+
+// Find the content of the first block starting after offset, without its curly braces.
+fn find_block(content: &str, offset: usize) -> Option<Range<usize>> {
+    let bytes = content.as_bytes();
+    let start = offset + content[offset..].find('{')? + 1;
+    let mut depth = 1;
+    let mut index = start;
+    while index < bytes.len() {
+        match bytes[index] {
+            b'/' if bytes.get(index + 1) == Some(&b'/') => {
+                index += content[index..].find('\n').unwrap_or(content.len() - index);
+            }
+            b'/' if bytes.get(index + 1) == Some(&b'*') => {
+                index += content[index..].find("*/").unwrap_or(content.len() - index);
+            }
+            b'{' => depth += 1,
+            b'}' => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(start..index);
+                }
+            }
+            _ => {}
+        }
+        index += 1;
     }
-    fn process_match(
+    None
+}
+
+// Find the initializer of a declaration, after its label and before its semicolon.
+fn find_initializer(content: &str, span: Range<usize>) -> String {
+    let declaration = &content[span];
+    match declaration.find('=') {
+        Some(equal) => {
+            let value = &declaration[equal + 1..];
+            match value.find(';') {
+                Some(semicolon) => value[..semicolon].trim().into(),
+                None => value.trim().into(),
+            }
+        }
+        None => "".into(),
+    }
+}
+
+// Module composed with its imports, as naga cannot parse #import & #ifdef directives.
+struct WgslComposedModule<'a> {
+    file_path: &'a Path,
+    composed_shader: WgslComposedShader,
+}
+
+impl<'a> WgslComposedModule<'a> {
+    fn get_content(&self) -> &str {
+        &self.composed_shader.content
+    }
+    // Map a byte range of composed content to the module. None if it comes from an import.
+    fn map_range(&self, range: &Range<usize>) -> Option<ShaderRange> {
+        let file_range = self
+            .composed_shader
+            .source_map
+            .map_range(&to_shader_range(self.get_content(), range)?)?;
+        if file_range.file_path == self.file_path {
+            Some(file_range.range)
+        } else {
+            None
+        }
+    }
+}
+
+/// Extract symbols from the module parsed by naga, as there is no usable tree-sitter grammar for WGSL.
+/// Module is composed with its imports first, but only symbols declared in the module itself are returned.
+/// Naga does not store the range of labels, so they are looked up from the declaration spans.
+/// If the module fails to parse, an error is returned so that last symbols are kept, the validator is reporting the error.
+struct WgslNagaModuleParser {}
+
+impl WgslNagaModuleParser {
+    fn process_function(
         &self,
-        symbol_match: &tree_sitter::QueryMatch,
-        file_path: &Path,
-        shader_content: &str,
-        scopes: &Vec<ShaderScope>,
+        composed_module: &WgslComposedModule,
+        module: &naga::Module,
+        function: &naga::Function,
+        function_info: Option<&FunctionInfo>,
+        span: Option<Range<usize>>,
         symbols: &mut ShaderSymbolListBuilder,
     ) {
-        let label_node = symbol_match.captures[0].node;
-        let range = ShaderRange::from(label_node.range());
-        let scope_stack = self.compute_scope_stack(&scopes, &range);
-        let struct_name: String = get_name(shader_content, symbol_match.captures[0].node).into();
-        symbols.add_type(ShaderSymbol {
-            label: struct_name.clone(),
-            requirement: None,
-            data: ShaderSymbolData::Struct {
-                constructors: vec![], // Constructor in wgsl ?
-                members: symbol_match.captures[1..]
-                    .chunks(2)
-                    .map(|w| ShaderMember {
-                        context: struct_name.clone(),
-                        parameters: ShaderParameter {
-                            ty: get_name(shader_content, w[1].node).into(),
-                            label: get_name(shader_content, w[0].node).into(),
+        let content = composed_module.get_content();
+        let gctx = module.to_ctx();
+        let label = match &function.name {
+            Some(label) => label,
+            None => return,
+        };
+        let label_range = match find_declaration_label(content, span, "fn", label) {
+            Some(label_range) => label_range,
+            None => return,
+        };
+        let range = match composed_module.map_range(&label_range) {
+            Some(range) => range,
+            None => return,
+        };
+        let block = find_block(content, label_range.end);
+        let scope_range = block.as_ref().and_then(|b| composed_module.map_range(b));
+        let parameter_scope_stack: Vec<ShaderScope> = scope_range.iter().cloned().collect();
+        // Get parameters & add them as function scope variable.
+        let parameters = function
+            .arguments
+            .iter()
+            .enumerate()
+            .filter_map(|(index, argument)| {
+                let label = argument.name.clone()?;
+                let ty = argument.ty.to_wgsl(&gctx);
+                // Argument expressions span the argument label.
+                let argument_range = function
+                    .expressions
+                    .iter()
+                    .find(|(_, expression)| match expression {
+                        Expression::FunctionArgument(argument_index) => {
+                            *argument_index as usize == index
+                        }
+                        _ => false,
+                    })
+                    .and_then(|(handle, _)| function.expressions.get_span(handle).to_range())
+                    .and_then(|r| composed_module.map_range(&r));
+                if let Some(argument_range) = &argument_range {
+                    symbols.add_variable(ShaderSymbol {
+                        label: label.clone(),
+                        requirement: None,
+                        data: ShaderSymbolData::Variables {
+                            ty: ty.clone(),
                             count: None,
-                            description: "".into(),
-                            range: Some(ShaderRange::from(ShaderRange::from(w[0].node.range()))),
                         },
-                    })
-                    .collect(),
-                methods: vec![],
+                        mode: ShaderSymbolMode::Runtime(ShaderSymbolRuntime::variable(
+                            composed_module.file_path.into(),
+                            argument_range.clone(),
+                            parameter_scope_stack.clone(),
+                        )),
+                    });
+                }
+                Some(ShaderParameter {
+                    ty,
+                    label,
+                    count: None,
+                    description: "".into(),
+                    range: argument_range,
+                })
+            })
+            .collect::<Vec<ShaderParameter>>();
+        // Add local variables.
+        for (handle, local_variable) in function.local_variables.iter() {
+            let label = match &local_variable.name {
+                Some(label) => label,
+                None => continue,
+            };
+            let span = function.local_variables.get_span(handle).to_range();
+            if let Some(variable_range) = find_declaration_label(content, span, "var", label)
+                .and_then(|r| composed_module.map_range(&r))
+            {
+                symbols.add_variable(ShaderSymbol {
+                    label: label.clone(),
+                    requirement: None,
+                    data: ShaderSymbolData::Variables {
+                        ty: local_variable.ty.to_wgsl(&gctx),
+                        count: None,
+                    },
+                    mode: ShaderSymbolMode::Runtime(ShaderSymbolRuntime::variable(
+                        composed_module.file_path.into(),
+                        variable_range,
+                        parameter_scope_stack.clone(),
+                    )),
+                });
+            }
+        }
+        // Add let bindings. Naga only keeps their name on their initializer expression.
+        let scope_start = block.map(|b| b.start).unwrap_or(label_range.end);
+        for (handle, binding_label) in function.named_expressions.iter() {
+            if let Expression::FunctionArgument(_) = function.expressions[*handle] {
+                continue; // Already added as parameter.
+            }
+            let initializer_start = match function.expressions.get_span(*handle).to_range() {
+                Some(initializer) => initializer.start,
+                None => continue,
+            };
+            if initializer_start < scope_start {
+                continue;
+            }
+            // Last declaration before its initializer, as a binding might shadow another one.
+            if let Some(binding_range) = find_declaration_labels(
+                content,
+                scope_start..initializer_start,
+                "let",
+                binding_label,
+            )
+            .last()
+            .and_then(|r| composed_module.map_range(&r))
+            {
+                symbols.add_variable(ShaderSymbol {
+                    label: binding_label.clone(),
+                    requirement: None,
+                    data: ShaderSymbolData::Variables {
+                        // Type is only known once module has been validated.
+                        ty: function_info
+                            .map(|info| info[*handle].ty.to_wgsl(&gctx))
+                            .unwrap_or_default(),
+                        count: None,
+                    },
+                    mode: ShaderSymbolMode::Runtime(ShaderSymbolRuntime::variable(
+                        composed_module.file_path.into(),
+                        binding_range,
+                        parameter_scope_stack.clone(),
+                    )),
+                });
+            }
+        }
+        symbols.add_function(ShaderSymbol {
+            label: label.clone(),
+            requirement: None,
+            data: ShaderSymbolData::Functions {
+                signatures: vec![ShaderSignature {
+                    returnType: match &function.result {
+                        Some(result) => result.ty.to_wgsl(&gctx),
+                        None => "void".into(),
+                    },
+                    description: "".into(),
+                    parameters: parameters,
+                }],
             },
-            // TODO: compute scope
-            mode: ShaderSymbolMode::Runtime(ShaderSymbolRuntime::new(
-                file_path.into(),
+            mode: ShaderSymbolMode::Runtime(ShaderSymbolRuntime::owner(
+                composed_module.file_path.into(),
                 range,
-                None,
-                scope_stack,
+                scope_range,
             )),
         });
     }
+    fn process_types(
+        &self,
+        composed_module: &WgslComposedModule,
+        module: &naga::Module,
+        symbols: &mut ShaderSymbolListBuilder,
+    ) {
+        let content = composed_module.get_content();
+        let gctx = module.to_ctx();
+        for (handle, ty) in module.types.iter() {
+            // Only named types are declared by user.
+            let label = match &ty.name {
+                Some(label) => label,
+                None => continue,
+            };
+            let span = module.types.get_span(handle).to_range();
+            match &ty.inner {
+                TypeInner::Struct { members, span: _ } => {
+                    let label_range =
+                        match find_declaration_label(content, span.clone(), "struct", label) {
+                            Some(label_range) => label_range,
+                            None => continue, // Predeclared types, not in source.
+                        };
+                    let range = match composed_module.map_range(&label_range) {
+                        Some(range) => range,
+                        None => continue,
+                    };
+                    let members_span = find_block(content, label_range.end)
+                        .unwrap_or(label_range.end..content.len());
+                    let mut member_offset = members_span.start;
+                    let members = members
+                        .iter()
+                        .filter_map(|member| {
+                            let member_label = member.name.clone()?;
+                            let member_range = match find_typed_label(
+                                content,
+                                member_offset..members_span.end,
+                                &member_label,
+                            ) {
+                                Some(member_range) => {
+                                    member_offset = member_range.end;
+                                    composed_module.map_range(&member_range)
+                                }
+                                None => None,
+                            };
+                            Some(ShaderMember {
+                                context: label.clone(),
+                                parameters: ShaderParameter {
+                                    ty: member.ty.to_wgsl(&gctx),
+                                    label: member_label,
+                                    count: None,
+                                    description: "".into(),
+                                    // Member symbols require a range.
+                                    range: Some(member_range.unwrap_or(range.clone())),
+                                },
+                            })
+                        })
+                        .collect::<Vec<ShaderMember>>();
+                    symbols.add_type(ShaderSymbol {
+                        label: label.clone(),
+                        requirement: None,
+                        data: ShaderSymbolData::Struct {
+                            // Structs can be constructed from their members.
+                            constructors: vec![ShaderSignature {
+                                returnType: label.clone(),
+                                description: "".into(),
+                                parameters: members.iter().map(|m| m.parameters.clone()).collect(),
+                            }],
+                            members,
+                            methods: vec![],
+                        },
+                        mode: ShaderSymbolMode::Runtime(ShaderSymbolRuntime::global(
+                            composed_module.file_path.into(),
+                            range,
+                        )),
+                    });
+                }
+                _ => {
+                    // Other named types are aliases. They do not have any span.
+                    if let Some(range) = find_declaration_label(content, span, "alias", label)
+                        .and_then(|r| composed_module.map_range(&r))
+                    {
+                        symbols.add_type(ShaderSymbol {
+                            label: label.clone(),
+                            requirement: None,
+                            data: ShaderSymbolData::Types {
+                                constructors: vec![],
+                            },
+                            mode: ShaderSymbolMode::Runtime(ShaderSymbolRuntime::global(
+                                composed_module.file_path.into(),
+                                range,
+                            )),
+                        });
+                    }
+                }
+            }
+        }
+    }
+    fn process_globals(
+        &self,
+        composed_module: &WgslComposedModule,
+        module: &naga::Module,
+        symbols: &mut ShaderSymbolListBuilder,
+    ) {
+        let content = composed_module.get_content();
+        let gctx = module.to_ctx();
+        let mut add_constant =
+            |label: &String, span: Option<Range<usize>>, qualifier: &str, ty: String| {
+                if let Some(label_range) =
+                    find_declaration_label(content, span.clone(), qualifier, label)
+                {
+                    if let Some(range) = composed_module.map_range(&label_range) {
+                        let declaration_end = span.map(|s| s.end).unwrap_or(content.len());
+                        symbols.add_constant(ShaderSymbol {
+                            label: label.clone(),
+                            requirement: None,
+                            data: ShaderSymbolData::Constants {
+                                ty,
+                                qualifier: qualifier.into(),
+                                value: find_initializer(content, label_range.end..declaration_end),
+                            },
+                            mode: ShaderSymbolMode::Runtime(ShaderSymbolRuntime::global(
+                                composed_module.file_path.into(),
+                                range,
+                            )),
+                        });
+                    }
+                }
+            };
+        for (handle, constant) in module.constants.iter() {
+            if let Some(label) = &constant.name {
+                let span = module.constants.get_span(handle).to_range();
+                add_constant(label, span, "const", constant.ty.to_wgsl(&gctx));
+            }
+        }
+        for (handle, override_) in module.overrides.iter() {
+            if let Some(label) = &override_.name {
+                let span = module.overrides.get_span(handle).to_range();
+                add_constant(label, span, "override", override_.ty.to_wgsl(&gctx));
+            }
+        }
+        for (handle, global) in module.global_variables.iter() {
+            let label = match &global.name {
+                Some(label) => label,
+                None => continue,
+            };
+            let span = module.global_variables.get_span(handle).to_range();
+            if let Some(range) = find_declaration_label(content, span, "var", label)
+                .and_then(|r| composed_module.map_range(&r))
+            {
+                symbols.add_variable(ShaderSymbol {
+                    label: label.clone(),
+                    requirement: None,
+                    data: ShaderSymbolData::Variables {
+                        ty: global.ty.to_wgsl(&gctx),
+                        count: None,
+                    },
+                    mode: ShaderSymbolMode::Runtime(ShaderSymbolRuntime::global(
+                        composed_module.file_path.into(),
+                        range,
+                    )),
+                });
+            }
+        }
+    }
+}
+
+impl SymbolModuleParser for WgslNagaModuleParser {
+    fn process_module(
+        &self,
+        shader_module: &ShaderModule,
+        shader_params: &ShaderParams,
+        symbols: &mut ShaderSymbolListBuilder,
+    ) -> Result<(), ShaderError> {
+        let composed_module = WgslComposedModule {
+            file_path: &shader_module.file_path,
+            composed_shader: WgslComposer::new(
+                &shader_module.file_path,
+                shader_params,
+                &mut WgslImportPathIndex::default(),
+                &mut |path: &Path| std::fs::read_to_string(path).ok(),
+            )
+            .compose(&shader_module.file_path, &shader_module.content),
+        };
+        let module = naga::front::wgsl::parse_str(composed_module.get_content())
+            .map_err(|err| ShaderError::ParseSymbolError(err.to_string()))?;
+        // Validation resolves the type of expressions, required for let bindings.
+        let module_info: Option<ModuleInfo> =
+            Validator::new(ValidationFlags::empty(), Capabilities::all())
+                .validate(&module)
+                .ok();
+        self.process_types(&composed_module, &module, symbols);
+        self.process_globals(&composed_module, &module, symbols);
+        for (handle, function) in module.functions.iter() {
+            let span = module.functions.get_span(handle).to_range();
+            let function_info = module_info.as_ref().map(|info| &info[handle]);
+            self.process_function(
+                &composed_module,
+                &module,
+                function,
+                function_info,
+                span,
+                symbols,
+            );
+        }
+        for (index, entry_point) in module.entry_points.iter().enumerate() {
+            // Entry points do not have any span, look for them in whole composed content.
+            let function_info = module_info.as_ref().map(|info| info.get_entry_point(index));
+            self.process_function(
+                &composed_module,
+                &module,
+                &entry_point.function,
+                function_info,
+                None,
+                symbols,
+            );
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod wgsl_parser_tests {
+    use std::path::Path;
+
+    use crate::{
+        shader::ShaderParams,
+        symbols::{
+            shader_module_parser::ShaderModuleParser,
+            symbol_list::ShaderSymbolList,
+            symbol_parser::{ShaderSymbolListBuilder, SymbolModuleParser},
+            symbols::ShaderSymbolData,
+        },
+    };
+
+    use super::WgslNagaModuleParser;
+
+    fn parse(shader_content: &str) -> ShaderSymbolList {
+        let file_path = Path::new("dontcare");
+        let module = ShaderModuleParser::wgsl()
+            .create_module(file_path, shader_content)
+            .unwrap();
+        let mut symbol_list_builder = ShaderSymbolListBuilder::new(&|_| true);
+        WgslNagaModuleParser {}
+            .process_module(&module, &ShaderParams::default(), &mut symbol_list_builder)
+            .unwrap();
+        symbol_list_builder.get_shader_symbol_list()
+    }
+
+    #[test]
+    fn test_wgsl_symbols() {
+        let symbols = parse(
+            r#"
+alias Color = vec4<f32>;
+struct Light {
+    position: vec3<f32>,
+    color: Color,
+}
+const PI: f32 = 3.14159;
+override intensity: f32 = 1.0;
+@group(0) @binding(0) var<uniform> light: Light;
+fn shade(normal: vec3<f32>) -> Color {
+    let diffuse = dot(normal, light.position);
+    var factor = diffuse * intensity / PI;
+    return light.color * factor;
+}
+@fragment
+fn main() -> @location(0) vec4<f32> {
+    return shade(vec3<f32>(0.0, 1.0, 0.0));
+}"#,
+        );
+        let light = symbols.types.iter().find(|t| t.label == "Light").unwrap();
+        match &light.data {
+            ShaderSymbolData::Struct { members, .. } => {
+                assert!(members.len() == 2);
+                assert!(members[1].parameters.ty == "Color");
+                assert!(members[1].parameters.range.as_ref().unwrap().start.line == 4);
+            }
+            _ => panic!("Light should be a struct"),
+        }
+        assert!(symbols.types.iter().any(|t| t.label == "Color"));
+        let pi = symbols.constants.iter().find(|c| c.label == "PI").unwrap();
+        match &pi.data {
+            ShaderSymbolData::Constants {
+                ty,
+                qualifier,
+                value,
+            } => {
+                assert!(ty == "f32");
+                assert!(qualifier == "const");
+                assert!(value == "3.14159");
+            }
+            _ => panic!("PI should be a constant"),
+        }
+        assert!(symbols.constants.iter().any(|c| c.label == "intensity"));
+        assert!(symbols.variables.iter().any(|v| v.label == "light"));
+        assert!(symbols.variables.iter().any(|v| v.label == "normal"));
+        assert!(symbols.variables.iter().any(|v| v.label == "factor"));
+        let diffuse = symbols
+            .variables
+            .iter()
+            .find(|v| v.label == "diffuse")
+            .unwrap();
+        match &diffuse.data {
+            ShaderSymbolData::Variables { ty, .. } => assert!(ty == "f32"),
+            _ => panic!("diffuse should be a variable"),
+        }
+        let shade = symbols
+            .functions
+            .iter()
+            .find(|f| f.label == "shade")
+            .unwrap();
+        let runtime = shade.mode.map_runtime().unwrap();
+        assert!(runtime.range.start.line == 9 && runtime.range.start.pos == 3);
+        assert!(runtime.scope.is_some());
+        assert!(symbols.functions.iter().any(|f| f.label == "main"));
+    }
 }
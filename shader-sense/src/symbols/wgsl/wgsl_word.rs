@@ -0,0 +1,122 @@
+use tree_sitter::Node;
+
+use crate::{
+    position::{ShaderPosition, ShaderRange},
+    shader_error::ShaderError,
+    symbols::{
+        shader_module::ShaderModule,
+        symbol_parser::{ShaderWordRange, SymbolWordProvider},
+    },
+};
+
+/// As the tree is not reliable for WGSL, words are found directly from the content.
+pub struct WgslSymbolWordProvider {}
+
+fn is_identifier_char(c: u8) -> bool {
+    c.is_ascii_alphanumeric() || c == b'_'
+}
+
+// Skip whitespaces backward from offset.
+fn skip_whitespace_backward(bytes: &[u8], offset: usize) -> usize {
+    let mut offset = offset;
+    while offset > 0 && bytes[offset - 1].is_ascii_whitespace() {
+        offset -= 1;
+    }
+    offset
+}
+
+// Skip a balanced group such as [] or () backward, offset being after the closing character.
+fn skip_group_backward(bytes: &[u8], offset: usize, open: u8, close: u8) -> Option<usize> {
+    let mut depth = 0;
+    let mut offset = offset;
+    while offset > 0 {
+        offset -= 1;
+        if bytes[offset] == close {
+            depth += 1;
+        } else if bytes[offset] == open {
+            depth -= 1;
+            if depth == 0 {
+                return Some(offset);
+            }
+        }
+    }
+    None
+}
+
+impl WgslSymbolWordProvider {
+    fn get_word(
+        content: &str,
+        start: usize,
+        end: usize,
+        parent: Option<ShaderWordRange>,
+    ) -> Result<ShaderWordRange, ShaderError> {
+        Ok(ShaderWordRange::new(
+            content[start..end].into(),
+            ShaderRange::new(
+                ShaderPosition::from_byte_offset(content, start)?,
+                ShaderPosition::from_byte_offset(content, end)?,
+            ),
+            parent,
+        ))
+    }
+    // Find the word ending at offset, along with its parents.
+    fn find_word_ending_at(
+        content: &str,
+        end: usize,
+    ) -> Result<Option<ShaderWordRange>, ShaderError> {
+        let bytes = content.as_bytes();
+        let mut start = end;
+        while start > 0 && is_identifier_char(bytes[start - 1]) {
+            start -= 1;
+        }
+        if start == end || bytes[start].is_ascii_digit() {
+            return Ok(None);
+        }
+        let parent = Self::find_parent(content, start)?;
+        Ok(Some(Self::get_word(content, start, end, parent)?))
+    }
+    // Find the parent of a word starting at offset, if accessed as a field.
+    fn find_parent(content: &str, start: usize) -> Result<Option<ShaderWordRange>, ShaderError> {
+        let bytes = content.as_bytes();
+        let offset = skip_whitespace_backward(bytes, start);
+        if offset == 0 || bytes[offset - 1] != b'.' {
+            return Ok(None);
+        }
+        let mut offset = skip_whitespace_backward(bytes, offset - 1);
+        // Skip array accesses
+        while offset > 0 && bytes[offset - 1] == b']' {
+            offset = match skip_group_backward(bytes, offset, b'[', b']') {
+                Some(offset) => skip_whitespace_backward(bytes, offset),
+                None => return Ok(None),
+            };
+        }
+        // Skip call parameters, the function label will be the parent.
+        if offset > 0 && bytes[offset - 1] == b')' {
+            offset = match skip_group_backward(bytes, offset, b'(', b')') {
+                Some(offset) => skip_whitespace_backward(bytes, offset),
+                None => return Ok(None),
+            };
+        }
+        Self::find_word_ending_at(content, offset)
+    }
+}
+
+impl SymbolWordProvider for WgslSymbolWordProvider {
+    fn find_word_at_position_in_node(
+        &self,
+        shader_module: &ShaderModule,
+        _node: Node,
+        position: &ShaderPosition,
+    ) -> Result<ShaderWordRange, ShaderError> {
+        let content = &shader_module.content;
+        let bytes = content.as_bytes();
+        let mut end = position.to_byte_offset(content)?;
+        while end < bytes.len() && is_identifier_char(bytes[end]) {
+            end += 1;
+        }
+        match Self::find_word_ending_at(content, end)? {
+            Some(word) => Ok(word),
+            None => Err(ShaderError::NoSymbol),
+        }
+    }
+}
@@ -9,11 +9,21 @@ use std::{
 use crate::{
     include::IncludeHandler,
     position::{ShaderFileRange, ShaderPosition},
-    shader::{HlslShaderModel, HlslVersion, ShaderParams, ShaderStage},
-    shader_error::{ShaderDiagnostic, ShaderDiagnosticList, ShaderDiagnosticSeverity, ShaderError},
+    shader::{
+        HlslMatrixPacking, HlslShaderModel, HlslSpirvTargetEnv, HlslVersion, HlslVulkanLayout,
+        ShaderParams, ShaderStage,
+    },
+    shader_error::{
+        ShaderDiagnostic, ShaderDiagnosticFix, ShaderDiagnosticList,
+        ShaderDiagnosticRelatedInformation, ShaderDiagnosticSeverity, ShaderDiagnosticSource,
+        ShaderError,
+    },
 };
 
-use super::validator::ValidatorImpl;
+use super::validator::{
+    ShaderBinary, ShaderCompilationArtifact, ValidatorBackend, ValidatorImpl, ValidatorInfo,
+    ValidatorVersion,
+};
 
 pub struct Dxc {
     compiler: hassle_rs::DxcCompiler,
@@ -25,9 +35,14 @@ pub struct Dxc {
     #[allow(dead_code)] // Need to keep dxc alive while dependencies created
     dxc: hassle_rs::wrapper::Dxc,
 
+    // Version of the loaded libraries.
+    version: Option<ValidatorVersion>,
+    dxil_validator_version: Option<(u32, u32)>,
+
     // Cache regex for parsing.
     diagnostic_regex: regex::Regex,
     internal_diagnostic_regex: regex::Regex,
+    diagnostic_code_regex: regex::Regex,
 }
 
 struct DxcIncludeHandler<'a> {
@@ -74,9 +89,8 @@ impl hassle_rs::wrapper::DxcIncludeHandler for DxcIncludeHandler<'_> {
 }
 
 impl Dxc {
-    // This is the version bundled with DXC and which is expected.
-    // TODO: Find a way to get these values dynamically.
-    // Could preprocess them in small shader file and read them instead ?
+    // This is the version bundled with the server, used to generate the intrinsics database.
+    // Loaded library might differ, use Dxc::get_version to get the actual one.
     pub const DXC_VERSION_MAJOR: u32 = 1;
     pub const DXC_VERSION_MINOR: u32 = 8;
     pub const DXC_VERSION_RELEASE: u32 = 2405;
@@ -130,20 +144,97 @@ impl Dxc {
             }
             Err(_) => (None, None),
         };
+        let version = Self::query_version(&library, &compiler).ok();
+        let dxil_validator_version = validator
+            .as_ref()
+            .and_then(|validator| validator.version().ok());
         Ok(Self {
             dxc,
             compiler,
             library,
             dxil,
             validator,
+            version,
+            dxil_validator_version,
             diagnostic_regex: regex::Regex::new(r"(?m)^(.*?:\d+:\d+: .*:.*?)$").unwrap(),
             internal_diagnostic_regex: regex::Regex::new(r"(?s)^(.*?):(\d+):(\d+): (.*?):(.*)")
                 .unwrap(),
+            diagnostic_code_regex: regex::Regex::new(r"\[(-W[\w\-]+)\]\s*$").unwrap(),
         })
     }
+    // hassle-rs only expose IDxcVersionInfo for the DXIL validator.
+    // Read compiler version from the macros it predefines instead.
+    fn query_version(
+        library: &DxcLibrary,
+        compiler: &DxcCompiler,
+    ) -> Result<ValidatorVersion, HassleError> {
+        let blob = library.create_blob_with_encoding_from_str(
+            "__DXC_VERSION_MAJOR __DXC_VERSION_MINOR __DXC_VERSION_RELEASE __DXC_VERSION_COMMITS",
+        )?;
+        let result = compiler
+            .preprocess(&blob, "version.hlsl", &[], None, &[])
+            .map_err(|(_, hresult)| HassleError::Win32Error(hresult))?;
+        let preprocessed = library.get_blob_as_string(&result.get_result()?)?;
+        let numbers: Vec<u32> = preprocessed
+            .lines()
+            .filter(|line| !line.trim_start().starts_with('#')) // Skip line directives
+            .flat_map(|line| line.split_whitespace())
+            .filter_map(|number| number.parse::<u32>().ok())
+            .collect();
+        match numbers.as_slice() {
+            [major, minor, patch, build] => Ok(ValidatorVersion {
+                major: *major,
+                minor: *minor,
+                patch: *patch,
+                build: *build,
+            }),
+            _ => Err(HassleError::CompileError(format!(
+                "Failed to parse DXC version from {}",
+                preprocessed
+            ))),
+        }
+    }
     pub fn is_dxil_validation_available(&self) -> bool {
         self.dxil.is_some() && self.validator.is_some()
     }
+    /// Version of the DXIL validator, if available.
+    pub fn get_dxil_validator_version(&self) -> Option<(u32, u32)> {
+        self.dxil_validator_version
+    }
+    // Parse fix-it hint from clang snippet following the message:
+    //     float a = 1.0
+    //                  ^
+    //                  ;
+    // Tildes starting at the hint column are replaced, otherwise the hint is inserted.
+    // Columns are offsets in the printed source line, which might differ from file if it contains tabs.
+    fn parse_dxc_fix_it(msg: &str, file_path: &Path, line: u32) -> Option<ShaderDiagnosticFix> {
+        let mut lines = msg.lines().skip(2); // Skip message & source line.
+        let caret_line = lines.next()?;
+        let is_caret_line = caret_line.contains(['^', '~'])
+            && caret_line.chars().all(|c| c == ' ' || c == '^' || c == '~');
+        if !is_caret_line {
+            return None;
+        }
+        let fix_it_line = lines.next()?.trim_end();
+        let new_text = fix_it_line.trim_start();
+        if new_text.is_empty() || new_text.ends_with(" generated.") {
+            return None;
+        }
+        let start = (fix_it_line.len() - new_text.len()) as u32;
+        let removed = caret_line
+            .chars()
+            .skip(start as usize)
+            .take_while(|c| *c == '~')
+            .count() as u32;
+        Some(ShaderDiagnosticFix {
+            range: ShaderFileRange::new(
+                file_path.into(),
+                ShaderPosition::new(line, start),
+                ShaderPosition::new(line, start + removed),
+            ),
+            new_text: new_text.into(),
+        })
+    }
     fn parse_dxc_errors(
         &self,
         errors: &String,
@@ -186,27 +277,50 @@ impl Dxc {
                             .search_path_in_includes(Path::new(&relative_path))
                             .unwrap_or(file_path.into())
                     });
-                shader_error_list.push(ShaderDiagnostic {
-                    severity: match level {
-                        "error" => ShaderDiagnosticSeverity::Error,
-                        "warning" => ShaderDiagnosticSeverity::Warning,
-                        "note" => ShaderDiagnosticSeverity::Information,
-                        "hint" => ShaderDiagnosticSeverity::Hint,
-                        _ => ShaderDiagnosticSeverity::Error,
-                    },
-                    error: String::from(msg),
-                    range: ShaderFileRange::new(
-                        file_path.clone(),
-                        ShaderPosition::new(
-                            line.parse::<u32>().unwrap_or(1) - 1,
-                            pos.parse::<u32>().unwrap_or(0),
-                        ),
-                        ShaderPosition::new(
-                            line.parse::<u32>().unwrap_or(1) - 1,
-                            pos.parse::<u32>().unwrap_or(0),
-                        ),
-                    ),
-                });
+                let line = line.parse::<u32>().unwrap_or(1) - 1;
+                let pos = pos.parse::<u32>().unwrap_or(0);
+                let range = ShaderFileRange::new(
+                    file_path.clone(),
+                    ShaderPosition::new(line, pos),
+                    ShaderPosition::new(line, pos),
+                );
+                let fix = Self::parse_dxc_fix_it(msg, file_path, line);
+                // Notes belong to the previous diagnostic.
+                if level == "note" {
+                    if let Some(diagnostic) = shader_error_list.diagnostics.last_mut() {
+                        diagnostic.fixes.extend(fix);
+                        diagnostic
+                            .related_information
+                            .push(ShaderDiagnosticRelatedInformation {
+                                message: msg.lines().next().unwrap_or("").trim().into(),
+                                range,
+                            });
+                        continue;
+                    }
+                }
+                // Warning flag is used as code, such as [-Wconversion]
+                let code = msg
+                    .lines()
+                    .next()
+                    .and_then(|first_line| self.diagnostic_code_regex.captures(first_line))
+                    .and_then(|capture| capture.get(1))
+                    .map(|code| code.as_str().into());
+                shader_error_list.push(
+                    ShaderDiagnostic::new(
+                        match level {
+                            "error" => ShaderDiagnosticSeverity::Error,
+                            "warning" => ShaderDiagnosticSeverity::Warning,
+                            "note" => ShaderDiagnosticSeverity::Information,
+                            "hint" => ShaderDiagnosticSeverity::Hint,
+                            _ => ShaderDiagnosticSeverity::Error,
+                        },
+                        String::from(msg),
+                        range,
+                    )
+                    .with_code(code)
+                    .with_source(ShaderDiagnosticSource::Dxc)
+                    .with_fixes(fix.into_iter().collect()),
+                );
             }
         }
 
@@ -223,12 +337,13 @@ impl Dxc {
                 }
             }
             Ok(ShaderDiagnosticList {
-                diagnostics: vec![ShaderDiagnostic {
-                    severity: ShaderDiagnosticSeverity::Error,
-                    error: format!("Failed to parse errors: {}", &errors),
+                diagnostics: vec![ShaderDiagnostic::new(
+                    ShaderDiagnosticSeverity::Error,
+                    format!("Failed to parse errors: {}", &errors),
                     // Minimize impact of error by showing it only at beginning.
-                    range: ShaderFileRange::zero(file_path.into()),
-                }],
+                    ShaderFileRange::zero(file_path.into()),
+                )
+                .with_source(ShaderDiagnosticSource::Dxc)],
             })
         } else {
             Ok(shader_error_list)
@@ -242,15 +357,18 @@ impl Dxc {
     ) -> Result<ShaderDiagnosticList, ShaderError> {
         match error {
             HassleError::CompileError(err) => self.parse_dxc_errors(&err, file_path, &params),
-            HassleError::ValidationError(err) => Ok(ShaderDiagnosticList::from(ShaderDiagnostic {
-                severity: ShaderDiagnosticSeverity::Error,
-                error: err.to_string(),
-                range: ShaderFileRange::new(
-                    file_path.into(),
-                    ShaderPosition::new(0, 0),
-                    ShaderPosition::new(0, 0),
-                ),
-            })),
+            HassleError::ValidationError(err) => Ok(ShaderDiagnosticList::from(
+                ShaderDiagnostic::new(
+                    ShaderDiagnosticSeverity::Error,
+                    err.to_string(),
+                    ShaderFileRange::new(
+                        file_path.into(),
+                        ShaderPosition::new(0, 0),
+                        ShaderPosition::new(0, 0),
+                    ),
+                )
+                .with_source(ShaderDiagnosticSource::Dxc),
+            )),
             HassleError::LibLoadingError(err) => Err(ShaderError::InternalErr(err.to_string())),
             HassleError::LoadLibraryError { filename, inner } => {
                 Err(ShaderError::InternalErr(format!(
@@ -305,6 +423,30 @@ impl ValidatorImpl for Dxc {
         params: &ShaderParams,
         include_callback: &mut dyn FnMut(&Path) -> Option<String>,
     ) -> Result<ShaderDiagnosticList, ShaderError> {
+        self.compile_shader(shader_source, file_path, params, include_callback)
+            .map(|artifact| artifact.diagnostics)
+    }
+    fn get_version(&self) -> Option<ValidatorVersion> {
+        self.version
+    }
+    fn get_predefined_macros(&self) -> HashMap<String, String> {
+        match &self.version {
+            Some(version) => HashMap::from([
+                ("__DXC_VERSION_MAJOR".into(), version.major.to_string()),
+                ("__DXC_VERSION_MINOR".into(), version.minor.to_string()),
+                ("__DXC_VERSION_RELEASE".into(), version.patch.to_string()),
+                ("__DXC_VERSION_COMMITS".into(), version.build.to_string()),
+            ]),
+            None => HashMap::new(),
+        }
+    }
+    fn compile_shader(
+        &self,
+        shader_source: &str,
+        file_path: &Path,
+        params: &ShaderParams,
+        include_callback: &mut dyn FnMut(&Path) -> Option<String>,
+    ) -> Result<ShaderCompilationArtifact, ShaderError> {
         let file_name = self.get_file_name(file_path);
 
         let blob = match self
@@ -313,7 +455,7 @@ impl ValidatorImpl for Dxc {
         {
             Ok(blob) => blob,
             Err(err) => match self.from_hassle_error(err, file_path, &params) {
-                Ok(diagnostics) => return Ok(diagnostics),
+                Ok(diagnostics) => return Ok(ShaderCompilationArtifact::failed(diagnostics)),
                 Err(error) => return Err(error),
             },
         };
@@ -344,24 +486,73 @@ impl ValidatorImpl for Dxc {
             if params.compilation.hlsl.enable16bit_types {
                 options.push("-enable-16bit-types".into());
             }
-            if params.compilation.hlsl.spirv {
+            let hlsl = &params.compilation.hlsl;
+            if hlsl.spirv {
                 options.push("-spirv".into());
-                // Default target does not support lib profile, so this is required.
-                options.push("-fspv-target-env=vulkan1.3".into());
+                // Default target (vulkan1.0) does not support lib profile, so this is required.
+                options.push(format!(
+                    "-fspv-target-env={}",
+                    match hlsl.spirv_target_env {
+                        HlslSpirvTargetEnv::Vulkan1_0 => "vulkan1.0",
+                        HlslSpirvTargetEnv::Vulkan1_1 => "vulkan1.1",
+                        HlslSpirvTargetEnv::Vulkan1_1Spirv1_4 => "vulkan1.1spirv1.4",
+                        HlslSpirvTargetEnv::Vulkan1_2 => "vulkan1.2",
+                        HlslSpirvTargetEnv::Vulkan1_3 => "vulkan1.3",
+                        HlslSpirvTargetEnv::Universal1_5 => "universal1.5",
+                    }
+                ));
+                if let Some(vulkan_layout) = hlsl.vulkan_layout {
+                    options.push(
+                        match vulkan_layout {
+                            HlslVulkanLayout::Gl => "-fvk-use-gl-layout",
+                            HlslVulkanLayout::Dx => "-fvk-use-dx-layout",
+                            HlslVulkanLayout::Scalar => "-fvk-use-scalar-layout",
+                        }
+                        .into(),
+                    );
+                }
+            }
+            if let Some(matrix_packing) = hlsl.matrix_packing {
+                options.push(
+                    match matrix_packing {
+                        HlslMatrixPacking::ColumnMajor => "-Zpc",
+                        HlslMatrixPacking::RowMajor => "-Zpr",
+                    }
+                    .into(),
+                );
+            }
+            for warning in &hlsl.enabled_warnings {
+                options.push(format!("-W{}", warning));
+            }
+            for warning in &hlsl.disabled_warnings {
+                options.push(format!("-Wno-{}", warning));
             }
+            if hlsl.warnings_as_errors {
+                options.push("-WX".into());
+            }
+            // Extra args last so they can override previous ones.
+            options.extend(hlsl.extra_args.iter().cloned());
             options
         };
         let dxc_options_str: Vec<&str> = dxc_options.iter().map(|s| s.as_str()).collect();
+        // Stage is taken from variant, then user mappings. Mapped files without entry point use DXC default one.
+        let stage_mapping = params.compilation.get_stage_mapping(&file_name);
+        let shader_stage = params
+            .compilation
+            .shader_stage
+            .or(stage_mapping.map(|stage_mapping| stage_mapping.stage));
+        let entry_point = match (&params.compilation.entry_point, stage_mapping) {
+            (Some(entry_point), _) => entry_point.as_str(),
+            (None, Some(stage_mapping)) => stage_mapping.entry_point.as_deref().unwrap_or("main"),
+            (None, None) => "",
+        };
         let result = self.compiler.compile(
             &blob,
             file_name.as_str(),
-            match &params.compilation.entry_point {
-                Some(entry_point) => entry_point.as_str(),
-                None => "",
-            },
+            entry_point,
             format!(
                 "{}_{}",
-                get_profile(params.compilation.shader_stage),
+                get_profile(shader_stage),
                 match params.compilation.hlsl.shader_model {
                     HlslShaderModel::ShaderModel6 => "6_0",
                     HlslShaderModel::ShaderModel6_1 => "6_1",
@@ -391,14 +582,18 @@ impl ValidatorImpl for Dxc {
                 let error_blob = match dxc_result.get_error_buffer() {
                     Ok(blob) => blob,
                     Err(err) => match self.from_hassle_error(err, file_path, &params) {
-                        Ok(diagnostics) => return Ok(diagnostics),
+                        Ok(diagnostics) => {
+                            return Ok(ShaderCompilationArtifact::failed(diagnostics))
+                        }
                         Err(error) => return Err(error),
                     },
                 };
                 let warning_emitted = match self.library.get_blob_as_string(&error_blob.into()) {
                     Ok(string) => string,
                     Err(err) => match self.from_hassle_error(err, file_path, &params) {
-                        Ok(diagnostics) => return Ok(diagnostics),
+                        Ok(diagnostics) => {
+                            return Ok(ShaderCompilationArtifact::failed(diagnostics))
+                        }
                         Err(error) => return Err(error),
                     },
                 };
@@ -415,7 +610,9 @@ impl ValidatorImpl for Dxc {
                     Ok(blob) => blob,
                     Err(err) => match self.from_hassle_error(err, file_path, &params) {
                         Ok(diagnostics) => {
-                            return Ok(ShaderDiagnosticList::join(warning_diagnostics, diagnostics))
+                            return Ok(ShaderCompilationArtifact::failed(
+                                ShaderDiagnosticList::join(warning_diagnostics, diagnostics),
+                            ))
                         }
                         Err(error) => return Err(error),
                     },
@@ -430,47 +627,65 @@ impl ValidatorImpl for Dxc {
                                 Ok(blob) => blob,
                                 Err(err) => match self.from_hassle_error(err, file_path, &params) {
                                     Ok(diagnostics) => {
-                                        return Ok(ShaderDiagnosticList::join(
-                                            warning_diagnostics,
-                                            diagnostics,
+                                        return Ok(ShaderCompilationArtifact::failed(
+                                            ShaderDiagnosticList::join(
+                                                warning_diagnostics,
+                                                diagnostics,
+                                            ),
                                         ))
                                     }
                                     Err(error) => return Err(error),
                                 },
                             };
                         match validator.validate(blob_encoding.into()) {
-                            Ok(_) => Ok(warning_diagnostics),
+                            // Validated blob is signed in place.
+                            Ok(validated_blob) => Ok(ShaderCompilationArtifact {
+                                binary: Some(ShaderBinary::Dxil(validated_blob.to_vec())),
+                                diagnostics: warning_diagnostics,
+                            }),
                             Err((_dxc_res, hassle_err)) => {
                                 //let error_blob = dxc_err.0.get_error_buffer().map_err(|e| self.from_hassle_error(e))?;
                                 //let error_emitted = self.library.get_blob_as_string(&error_blob.into()).map_err(|e| self.from_hassle_error(e))?;
                                 match self.from_hassle_error(hassle_err, file_path, &params) {
-                                    Ok(diagnostics) => Ok(ShaderDiagnosticList::join(
-                                        warning_diagnostics,
-                                        diagnostics,
+                                    Ok(diagnostics) => Ok(ShaderCompilationArtifact::failed(
+                                        ShaderDiagnosticList::join(
+                                            warning_diagnostics,
+                                            diagnostics,
+                                        ),
                                     )),
                                     Err(err) => Err(err),
                                 }
                             }
                         }
                     } else {
-                        Ok(warning_diagnostics)
+                        Ok(ShaderCompilationArtifact {
+                            binary: Some(ShaderBinary::Dxil(result_blob.to_vec())),
+                            diagnostics: warning_diagnostics,
+                        })
                     }
                 } else {
-                    Ok(warning_diagnostics)
+                    Ok(ShaderCompilationArtifact {
+                        binary: Some(ShaderBinary::Spirv(result_blob.to_vec())),
+                        diagnostics: warning_diagnostics,
+                    })
                 }
             }
             Err((dxc_result, _hresult)) => {
                 let error_blob = match dxc_result.get_error_buffer() {
                     Ok(blob) => blob,
                     Err(err) => match self.from_hassle_error(err, file_path, &params) {
-                        Ok(diagnostics) => return Ok(diagnostics),
+                        Ok(diagnostics) => {
+                            return Ok(ShaderCompilationArtifact::failed(diagnostics))
+                        }
                         Err(error) => return Err(error),
                     },
                 };
                 let error_emitted = match self.library.get_blob_as_string(&error_blob.into()) {
                     Ok(string) => string,
                     Err(err) => match self.from_hassle_error(err, file_path, &params) {
-                        Ok(diagnostics) => return Ok(diagnostics),
+                        Ok(diagnostics) => {
+                            return Ok(ShaderCompilationArtifact::failed(diagnostics))
+                        }
                         Err(error) => return Err(error),
                     },
                 };
@@ -479,7 +694,7 @@ impl ValidatorImpl for Dxc {
                     file_path,
                     &params,
                 ) {
-                    Ok(diag) => Ok(diag),
+                    Ok(diag) => Ok(ShaderCompilationArtifact::failed(diag)),
                     Err(error) => Err(error),
                 }
             }
@@ -488,4 +703,17 @@ impl ValidatorImpl for Dxc {
     fn support(&self, _shader_stage: ShaderStage) -> bool {
         true // Support all shader stage
     }
+    fn get_backend(&self) -> ValidatorBackend {
+        ValidatorBackend::Dxc
+    }
+    fn get_info(&self) -> ValidatorInfo {
+        ValidatorInfo {
+            backend: ValidatorBackend::Dxc,
+            version: self.version,
+            dxil_validation: self.is_dxil_validation_available(),
+            dxil_validator_version: self.dxil_validator_version,
+            supported_stages: ShaderStage::ALL.to_vec(),
+            fallback: None,
+        }
+    }
 }
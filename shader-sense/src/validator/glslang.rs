@@ -1,11 +1,16 @@
 //! Validation for glsl with glslang
 
-use super::validator::ValidatorImpl;
+use super::validator::{ShaderBinary, ShaderCompilationArtifact, ValidatorBackend, ValidatorImpl};
 use crate::{
     include::IncludeHandler,
-    position::{ShaderFileRange, ShaderPosition},
-    shader::{GlslSpirvVersion, GlslTargetClient, ShaderParams, ShaderStage},
-    shader_error::{ShaderDiagnostic, ShaderDiagnosticList, ShaderDiagnosticSeverity, ShaderError},
+    position::{ShaderFileRange, ShaderPosition, ShaderRange},
+    shader::{
+        GlslCompilationParams, GlslSpirvVersion, GlslTargetClient, ShaderParams, ShaderStage,
+    },
+    shader_error::{
+        ShaderDiagnostic, ShaderDiagnosticList, ShaderDiagnosticSeverity, ShaderDiagnosticSource,
+        ShaderError,
+    },
 };
 use glslang::{
     error::GlslangError,
@@ -62,7 +67,7 @@ impl Glslang {
             compiler,
             diagnostic_regex: regex::Regex::new(r"(?m)^(.*?:(?:  \d+:\d+:)?)").unwrap(),
             internal_diagnostic_regex: regex::Regex::new(
-                r"(?s)^(.*?):(?: ((?:[a-zA-Z]:)?[\d\w\.\/\\\-]+):(\d+):(\d+):)?(.+)",
+                r"(?s)^(.*?):(?: ((?:[a-zA-Z]:)?[\d\w\.\/\\\-]+):(\d+):(?:(\d+):)?)?(.+)",
             )
             .unwrap(),
         }
@@ -124,6 +129,7 @@ impl Glslang {
         file_path: &Path,
         params: &ShaderParams,
         offset_first_line: bool,
+        unlocated_range: Option<&ShaderRange>,
     ) -> Result<ShaderDiagnosticList, ShaderError> {
         let mut shader_error_list = ShaderDiagnosticList::empty();
 
@@ -183,21 +189,27 @@ impl Glslang {
                     }
                 };
                 let pos = pos.parse::<u32>().unwrap_or(0);
-                shader_error_list.push(ShaderDiagnostic {
-                    severity: match level {
-                        "ERROR" => ShaderDiagnosticSeverity::Error,
-                        "WARNING" => ShaderDiagnosticSeverity::Warning,
-                        "NOTE" => ShaderDiagnosticSeverity::Information,
-                        "HINT" => ShaderDiagnosticSeverity::Hint,
-                        _ => ShaderDiagnosticSeverity::Error,
-                    },
-                    error: String::from(msg),
-                    range: ShaderFileRange::new(
-                        file_path.clone(),
+                let range = match (capture.get(3), unlocated_range) {
+                    (None, Some(unlocated_range)) => unlocated_range.clone(),
+                    _ => ShaderRange::new(
                         ShaderPosition::new(line, pos),
                         ShaderPosition::new(line, pos),
                     ),
-                });
+                };
+                shader_error_list.push(
+                    ShaderDiagnostic::new(
+                        match level {
+                            "ERROR" => ShaderDiagnosticSeverity::Error,
+                            "WARNING" => ShaderDiagnosticSeverity::Warning,
+                            "NOTE" => ShaderDiagnosticSeverity::Information,
+                            "HINT" => ShaderDiagnosticSeverity::Hint,
+                            _ => ShaderDiagnosticSeverity::Error,
+                        },
+                        String::from(msg),
+                        range.into_file(file_path.clone()),
+                    )
+                    .with_source(ShaderDiagnosticSource::Glslang),
+                );
             } else {
                 return Err(ShaderError::InternalErr(format!(
                     "Failed to parse regex: {}",
@@ -224,13 +236,13 @@ impl Glslang {
     ) -> Result<ShaderDiagnosticList, ShaderError> {
         match err {
             GlslangError::PreprocessError(error) => {
-                self.parse_errors(&error, file_path, &params, offset_first_line)
+                self.parse_errors(&error, file_path, &params, offset_first_line, None)
             }
             GlslangError::ParseError(error) => {
-                self.parse_errors(&error, file_path, &params, offset_first_line)
+                self.parse_errors(&error, file_path, &params, offset_first_line, None)
             }
             GlslangError::LinkError(error) => {
-                self.parse_errors(&error, file_path, &params, offset_first_line)
+                self.parse_errors(&error, file_path, &params, offset_first_line, None)
             }
             GlslangError::ShaderStageNotFound(stage) => Err(ShaderError::InternalErr(format!(
                 "Shader stage not found: {:#?}",
@@ -252,20 +264,104 @@ impl Glslang {
         }
     }
 }
-impl ValidatorImpl for Glslang {
-    fn validate_shader(
+impl Glslang {
+    // Find the label range of the entry point declaration, which is preceded by its return type.
+    fn find_entry_point_range(content: &str, entry_point: &str) -> Option<ShaderRange> {
+        let declaration_regex =
+            regex::Regex::new(&format!(r"\w\s+({})\s*\(", regex::escape(entry_point))).ok()?;
+        let label = declaration_regex.captures(content)?.get(1)?;
+        Some(ShaderRange::new(
+            ShaderPosition::from_byte_offset(content, label.start()).ok()?,
+            ShaderPosition::from_byte_offset(content, label.end()).ok()?,
+        ))
+    }
+    // Convert glsl options & glslangValidator arguments to messages.
+    // C API does not take arguments, so only the ones mapping to messages are supported.
+    // Unsupported arguments are skipped with a warning, to still validate the shader.
+    fn get_glsl_messages(
+        glsl: &GlslCompilationParams,
+        file_path: &Path,
+    ) -> (glslang::ShaderMessage, ShaderDiagnosticList) {
+        let mut messages = glslang::ShaderMessage::DEFAULT;
+        let mut diagnostics = ShaderDiagnosticList::empty();
+        if glsl.suppress_warnings {
+            messages |= glslang::ShaderMessage::SUPPRESS_WARNINGS;
+        }
+        if glsl.relaxed_errors {
+            messages |= glslang::ShaderMessage::RELAXED_ERRORS;
+        }
+        for arg in &glsl.extra_args {
+            messages |= match arg.as_str() {
+                "-w" | "--suppress-warnings" => glslang::ShaderMessage::SUPPRESS_WARNINGS,
+                "--relaxed-errors" => glslang::ShaderMessage::RELAXED_ERRORS,
+                "--keep-uncalled" | "--ku" => glslang::ShaderMessage::KEEP_UNCALLED,
+                arg => {
+                    diagnostics.push(
+                        ShaderDiagnostic::new(
+                            ShaderDiagnosticSeverity::Warning,
+                            format!("Unsupported glslang argument {} is ignored.", arg),
+                            ShaderFileRange::zero(file_path.into()),
+                        )
+                        .with_source(ShaderDiagnosticSource::Glslang),
+                    );
+                    glslang::ShaderMessage::DEFAULT
+                }
+            };
+        }
+        (messages, diagnostics)
+    }
+    // Process the shader, reporting argument issues along shader diagnostics.
+    fn process_shader(
         &self,
         content: &str,
         file_path: &Path,
         params: &ShaderParams,
         include_callback: &mut dyn FnMut(&Path) -> Option<String>,
-    ) -> Result<ShaderDiagnosticList, ShaderError> {
+        compile: bool,
+    ) -> Result<ShaderCompilationArtifact, ShaderError> {
+        let (glsl_messages, argument_diagnostics) = if self.hlsl {
+            (
+                glslang::ShaderMessage::DEFAULT,
+                ShaderDiagnosticList::empty(),
+            )
+        } else {
+            Self::get_glsl_messages(&params.compilation.glsl, file_path)
+        };
+        let mut artifact = self.process_glslang(
+            content,
+            file_path,
+            params,
+            include_callback,
+            glsl_messages,
+            compile,
+        )?;
+        artifact.diagnostics =
+            ShaderDiagnosticList::join(argument_diagnostics, artifact.diagnostics);
+        Ok(artifact)
+    }
+    // Parse the shader, and generate SPIR-V if compile is requested.
+    fn process_glslang(
+        &self,
+        content: &str,
+        file_path: &Path,
+        params: &ShaderParams,
+        include_callback: &mut dyn FnMut(&Path) -> Option<String>,
+        glsl_messages: glslang::ShaderMessage,
+        compile: bool,
+    ) -> Result<ShaderCompilationArtifact, ShaderError> {
         let file_name = self.get_file_name(file_path);
 
+        // Stage is taken from variant or #pragma shader_stage, then user mappings, then file extension.
+        // #pragma shader_stage is resolved by the preprocessor, see ShaderPreprocessor::get_compilation_params.
+        let stage_mapping = params.compilation.get_stage_mapping(&file_name);
+        let known_stage = params
+            .compilation
+            .shader_stage
+            .or(stage_mapping.map(|stage_mapping| stage_mapping.stage))
+            .or_else(|| ShaderStage::from_file_name(&file_name));
+        let is_stage_known = known_stage.is_some();
         let (shader_stage, shader_source, offset_first_line) =
-            if let Some(variant_stage) = params.compilation.shader_stage {
-                (variant_stage, content.into(), false)
-            } else if let Some(shader_stage) = ShaderStage::from_file_name(&file_name) {
+            if let Some(shader_stage) = known_stage {
                 (shader_stage, content.into(), false)
             } else {
                 // If we dont have a stage, might require some preprocess to avoid errors.
@@ -283,7 +379,14 @@ impl ValidatorImpl for Glslang {
                     } else {
                         // Header file with missing stage & missing version.
                         // WARN: Assumed this string is one line offset only.
-                        let version_header = String::from("#version 450\n");
+                        let version_header = format!(
+                            "{}\n",
+                            params
+                                .compilation
+                                .glsl
+                                .client
+                                .get_default_version_directive()
+                        );
                         (default_stage, version_header + content, true)
                     }
                 }
@@ -296,6 +399,16 @@ impl ValidatorImpl for Glslang {
             .iter()
             .map(|v| (&v.0 as &str, Some(&v.1 as &str)))
             .collect();
+        let entry_point = params
+            .compilation
+            .entry_point
+            .as_deref()
+            .or(stage_mapping.and_then(|stage_mapping| stage_mapping.entry_point.as_deref()))
+            .filter(|entry_point| !entry_point.is_empty());
+        // Link to report link errors (missing entry point, missing function definition...).
+        // Stage must be known, else it is most likely a header file.
+        // HLSL entry point is rarely main, so only link it if one is given.
+        let link = is_stage_known && (!self.hlsl || entry_point.is_some());
         let mut include_handler = GlslangIncludeHandler::new(
             file_path,
             params.context.includes.clone(),
@@ -326,9 +439,16 @@ impl ValidatorImpl for Glslang {
                     glslang::Target::None(Some(lang_version))
                 } else {
                     if params.compilation.glsl.client.is_opengl() {
-                        glslang::Target::OpenGL {
-                            version: glslang::OpenGlVersion::OpenGL4_5,
-                            spirv_version: None, // TODO ?
+                        if params.compilation.glsl.client.is_spirv_supported() {
+                            // glslang only expose OpenGL 4.5 client, which is what GL_ARB_gl_spirv requires.
+                            glslang::Target::OpenGL {
+                                version: glslang::OpenGlVersion::OpenGL4_5,
+                                spirv_version: None, // TODO ?
+                            }
+                        } else {
+                            // Older desktop profiles & ES cannot consume SPIR-V, and glslang
+                            // reject ES shaders with OpenGL client, so only validate the glsl.
+                            glslang::Target::None(None)
                         }
                     } else {
                         let client_version = match params.compilation.glsl.client {
@@ -351,7 +471,8 @@ impl ValidatorImpl for Glslang {
                         glslang::ShaderMessage::HLSL_ENABLE_16BIT_TYPES
                     } else {
                         glslang::ShaderMessage::DEFAULT
-                    },
+                    }
+                    | glsl_messages,
                 ..Default::default()
             },
             Some(&defines),
@@ -362,34 +483,96 @@ impl ValidatorImpl for Glslang {
             Ok(value) => value,
             Err(error) => match error {
                 Err(error) => return Err(error),
-                Ok(diag) => return Ok(diag),
+                Ok(diag) => return Ok(ShaderCompilationArtifact::failed(diag)),
             },
         };
-        let _shader = match glslang::Shader::new(&self.compiler, input)
+        // glslang requires a main entry point, so let it rename the source entry point to main
+        // the same way glslangValidator --source-entrypoint does.
+        let input = match entry_point {
+            Some(entry_point) if entry_point != "main" => {
+                input.with_source_entry_point(entry_point)
+            }
+            _ => input,
+        };
+        let shader = match glslang::Shader::new(&self.compiler, input)
             .map_err(|e| self.from_glslang_error(e, file_path, &params, offset_first_line))
         {
             Ok(value) => value,
             Err(error) => match error {
                 Err(error) => return Err(error),
-                Ok(diag) => return Ok(diag),
+                Ok(diag) => return Ok(ShaderCompilationArtifact::failed(diag)),
             },
         };
-        // Linking require main entry point.
-        // For now, glslang is expecting main entry point, no way to change this via C api.
-        /*if params.entry_point.is_some() {
-            let _spirv = match shader
-                .compile()
-                .map_err(|e| self.from_glslang_error(e, file_path, &params, offset_first_line))
-            {
-                Ok(value) => value,
-                Err(error) => match error {
+        if !compile && !link {
+            return Ok(ShaderCompilationArtifact::failed(
+                ShaderDiagnosticList::empty(), // No error detected.
+            ));
+        }
+        // Compilation link the program with the shader before generating SPIR-V.
+        let spirv = match shader.compile() {
+            Ok(value) => value,
+            // Link errors are mostly not located, and the function they refer to has no definition to point at.
+            // Report them on the entry point which is being linked instead.
+            Err(GlslangError::LinkError(error)) => {
+                let entry_point_range =
+                    Self::find_entry_point_range(content, entry_point.unwrap_or("main"));
+                return Ok(ShaderCompilationArtifact::failed(self.parse_errors(
+                    &error,
+                    file_path,
+                    &params,
+                    offset_first_line,
+                    entry_point_range.as_ref(),
+                )?));
+            }
+            // Only linking matters for validation, ignore SPIR-V generation issues (such as OpenGL without SPIR-V).
+            Err(_) if !compile => {
+                return Ok(ShaderCompilationArtifact::failed(
+                    ShaderDiagnosticList::empty(), // No error detected.
+                ));
+            }
+            Err(error) => {
+                match self.from_glslang_error(error, file_path, &params, offset_first_line) {
                     Err(error) => return Err(error),
-                    Ok(diag) => return Ok(diag),
-                },
-            };
-        }*/
+                    Ok(diag) => return Ok(ShaderCompilationArtifact::failed(diag)),
+                }
+            }
+        };
+        Ok(ShaderCompilationArtifact {
+            binary: if compile {
+                Some(ShaderBinary::Spirv(spirv))
+            } else {
+                None // Only linked for validation.
+            },
+            diagnostics: ShaderDiagnosticList::empty(),
+        })
+    }
+}
 
-        Ok(ShaderDiagnosticList::empty()) // No error detected.
+impl ValidatorImpl for Glslang {
+    fn validate_shader(
+        &self,
+        content: &str,
+        file_path: &Path,
+        params: &ShaderParams,
+        include_callback: &mut dyn FnMut(&Path) -> Option<String>,
+    ) -> Result<ShaderDiagnosticList, ShaderError> {
+        self.process_shader(content, file_path, params, include_callback, false)
+            .map(|artifact| artifact.diagnostics)
+    }
+    fn compile_shader(
+        &self,
+        content: &str,
+        file_path: &Path,
+        params: &ShaderParams,
+        include_callback: &mut dyn FnMut(&Path) -> Option<String>,
+    ) -> Result<ShaderCompilationArtifact, ShaderError> {
+        if !self.hlsl && !params.compilation.glsl.client.is_spirv_supported() {
+            return Err(ShaderError::ValidationError(format!(
+                "SPIR-V is not supported by target client {:?}.",
+                params.compilation.glsl.client
+            )));
+        }
+        self.process_shader(content, file_path, params, include_callback, true)
     }
     fn support(&self, shader_stage: ShaderStage) -> bool {
         if self.hlsl {
@@ -406,4 +589,7 @@ impl ValidatorImpl for Glslang {
             true // All stages supported.
         }
     }
+    fn get_backend(&self) -> ValidatorBackend {
+        ValidatorBackend::Glslang
+    }
 }
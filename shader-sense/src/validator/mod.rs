@@ -5,17 +5,27 @@ pub mod dxc;
 pub mod glslang;
 pub mod naga;
 pub mod validator;
+pub(crate) mod wgsl_composer;
 
 #[cfg(test)]
 mod tests {
     use std::{collections::HashMap, path::Path};
 
+    use crate::position::ShaderPosition;
+    use crate::reflection::{ShaderReflection, ShaderResourceKind};
     use crate::shader::{
-        ShaderCompilationParams, ShaderContextParams, ShaderParams, ShaderStage, ShadingLanguage,
+        GlslCompilationParams, GlslShadingLanguageTag, GlslTargetClient, HlslShadingLanguageTag,
+        ShaderCompilationParams, ShaderContextParams, ShaderFilePattern, ShaderParams, ShaderStage,
+        ShaderStageMapping, ShadingLanguage, WgslCapabilityProfile, WgslCompilationParams,
     };
 
     use super::validator::*;
     use super::*;
+    use crate::shader_error::{ShaderDiagnosticEntryPoint, ShaderDiagnosticSeverity};
+    use crate::symbols::{
+        shader_module_parser::ShaderModuleParser, symbol_provider::SymbolProvider,
+        symbols::ShaderEntryPoint,
+    };
 
     #[cfg(not(target_os = "wasi"))]
     fn find_test_dxc() -> Option<std::path::PathBuf> {
@@ -56,6 +66,37 @@ mod tests {
         }
     }
 
+    #[test]
+    fn glsl_unsupported_extra_arg() {
+        let validator = create_test_validator(ShadingLanguage::Glsl);
+        let file_path = Path::new("./test/glsl/ok.frag.glsl");
+        let shader_content = std::fs::read_to_string(file_path).unwrap();
+        // Unsupported arguments are skipped, and shader still validated.
+        match validator.validate_shader(
+            &shader_content,
+            file_path,
+            &ShaderParams {
+                compilation: ShaderCompilationParams {
+                    glsl: GlslCompilationParams {
+                        extra_args: vec!["--relaxed-errors".into(), "--unknown-arg".into()],
+                        ..Default::default()
+                    },
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+            &mut default_include_callback,
+        ) {
+            Ok(result) => {
+                println!("Diagnostic should have a single warning: {:#?}", result);
+                assert!(result.diagnostics.len() == 1);
+                assert!(result.diagnostics[0].severity == ShaderDiagnosticSeverity::Warning);
+                assert!(result.diagnostics[0].error.contains("--unknown-arg"));
+            }
+            Err(err) => panic!("{}", err),
+        }
+    }
+
     #[test]
     fn glsl_include_config() {
         let validator = create_test_validator(ShadingLanguage::Glsl);
@@ -131,6 +172,132 @@ mod tests {
         };
     }
 
+    #[test]
+    fn glsl_es_target_client() {
+        let validator = create_test_validator(ShadingLanguage::Glsl);
+        let file_path = Path::new("./test/glsl/es100.glsl");
+        let shader_content = std::fs::read_to_string(file_path).unwrap();
+        // Header file is validated with the #version of the target client.
+        match validator.validate_shader(
+            &shader_content,
+            file_path,
+            &ShaderParams {
+                compilation: ShaderCompilationParams {
+                    glsl: GlslCompilationParams {
+                        client: GlslTargetClient::OpenGLES100,
+                        ..Default::default()
+                    },
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+            &mut default_include_callback,
+        ) {
+            Ok(result) => {
+                println!("Diagnostic should be empty: {:#?}", result);
+                assert!(result.is_empty())
+            }
+            Err(err) => panic!("{}", err),
+        };
+        // texture2D is not available with Vulkan.
+        match validator.validate_shader(
+            &shader_content,
+            file_path,
+            &ShaderParams::default(),
+            &mut default_include_callback,
+        ) {
+            Ok(result) => {
+                println!("Diagnostic should not be empty: {:#?}", result);
+                assert!(!result.is_empty())
+            }
+            Err(err) => panic!("{}", err),
+        };
+    }
+
+    #[test]
+    fn glsl_pragma_stage() {
+        let validator = create_test_validator(ShadingLanguage::Glsl);
+        let file_path = Path::new("./test/glsl/pragma-stage.glsl");
+        let shader_content = std::fs::read_to_string(file_path).unwrap();
+        // Stage is resolved by the preprocessor.
+        let mut shader_module_parser = ShaderModuleParser::glsl();
+        let symbol_provider = SymbolProvider::glsl();
+        let shader_module = shader_module_parser
+            .create_module(file_path, &shader_content)
+            .unwrap();
+        let symbols = symbol_provider
+            .query_symbols(
+                &shader_module,
+                ShaderParams::default(),
+                &mut crate::symbols::symbol_provider::default_include_callback::<
+                    GlslShadingLanguageTag,
+                >,
+                None,
+            )
+            .unwrap();
+        let shader_params = ShaderParams {
+            compilation: symbols
+                .get_preprocessor()
+                .get_compilation_params(&ShaderCompilationParams::default()),
+            ..Default::default()
+        };
+        assert!(shader_params.compilation.shader_stage == Some(ShaderStage::Compute));
+        match validator.validate_shader(
+            &shader_content,
+            file_path,
+            &shader_params,
+            &mut default_include_callback,
+        ) {
+            Ok(result) => {
+                println!("Diagnostic should be empty: {:#?}", result);
+                assert!(result.is_empty())
+            }
+            Err(err) => panic!("{}", err),
+        };
+    }
+
+    #[test]
+    fn glsl_stage_mapping() {
+        let validator = create_test_validator(ShadingLanguage::Glsl);
+        let file_path = Path::new("./test/glsl/stage-mapping_cs.glsl");
+        let shader_content = std::fs::read_to_string(file_path).unwrap();
+        match validator.validate_shader(
+            &shader_content,
+            file_path,
+            &ShaderParams {
+                compilation: ShaderCompilationParams {
+                    stage_mappings: vec![ShaderStageMapping::new(
+                        ShaderFilePattern::Glob("*_cs.glsl".into()),
+                        ShaderStage::Compute,
+                        Some("csMain".into()),
+                    )],
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+            &mut default_include_callback,
+        ) {
+            Ok(result) => {
+                println!("Diagnostic should be empty: {:#?}", result);
+                assert!(result.is_empty())
+            }
+            Err(err) => panic!("{}", err),
+        };
+        // Without mapping, file is considered as a fragment shader.
+        match validator.validate_shader(
+            &shader_content,
+            file_path,
+            &ShaderParams::default(),
+            &mut default_include_callback,
+        ) {
+            Ok(result) => {
+                println!("Diagnostic should not be empty: {:#?}", result);
+                assert!(!result.is_empty())
+            }
+            Err(err) => panic!("{}", err),
+        };
+    }
+
     #[test]
     fn glsl_macro() {
         let validator = create_test_validator(ShadingLanguage::Glsl);
@@ -177,6 +344,170 @@ mod tests {
         };
     }
 
+    #[test]
+    fn glsl_link_error() {
+        let validator = create_test_validator(ShadingLanguage::Glsl);
+        let file_path = Path::new("./test/glsl/link-error.frag.glsl");
+        let shader_content = std::fs::read_to_string(file_path).unwrap();
+        match validator.validate_shader(
+            &shader_content,
+            file_path,
+            &ShaderParams::default(),
+            &mut default_include_callback,
+        ) {
+            Ok(result) => {
+                let diags = result.diagnostics;
+                println!("Diagnostic should not be empty: {:#?}", diags);
+                assert!(diags[0]
+                    .error
+                    .contains("No function definition (body) found"));
+                // Link errors are reported on the entry point being linked.
+                assert!(diags[0].range.start().line == 6);
+                assert!(diags[0].range.start().pos == 5);
+            }
+            Err(err) => panic!("{}", err),
+        };
+    }
+
+    #[test]
+    fn glsl_entry_point() {
+        let validator = create_test_validator(ShadingLanguage::Glsl);
+        let file_path = Path::new("./test/glsl/entry-point.frag.glsl");
+        let shader_content = std::fs::read_to_string(file_path).unwrap();
+        match validator.validate_shader(
+            &shader_content,
+            file_path,
+            &ShaderParams {
+                compilation: ShaderCompilationParams {
+                    entry_point: Some("fragMain".into()),
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+            &mut default_include_callback,
+        ) {
+            Ok(result) => {
+                println!("Diagnostic should be empty: {:#?}", result);
+                assert!(result.is_empty())
+            }
+            Err(err) => panic!("{}", err),
+        };
+        // Without entry point, main is missing at link time.
+        match validator.validate_shader(
+            &shader_content,
+            file_path,
+            &ShaderParams::default(),
+            &mut default_include_callback,
+        ) {
+            Ok(result) => {
+                let diags = result.diagnostics;
+                println!("Diagnostic should not be empty: {:#?}", diags);
+                assert!(diags[0].error.contains("Missing entry point"));
+            }
+            Err(err) => panic!("{}", err),
+        };
+    }
+
+    #[test]
+    #[cfg(not(target_os = "wasi"))] // Notes are emitted by DXC only.
+    fn hlsl_error_notes() {
+        use crate::shader_error::{ShaderDiagnosticSeverity, ShaderDiagnosticSource};
+
+        let validator = create_test_validator(ShadingLanguage::Hlsl);
+        let file_path = Path::new("./test/hlsl/error-notes.hlsl");
+        let shader_content = std::fs::read_to_string(file_path).unwrap();
+        match validator.validate_shader(
+            &shader_content,
+            file_path,
+            &ShaderParams::default(),
+            &mut default_include_callback,
+        ) {
+            Ok(result) => {
+                println!("Diagnostic should have notes: {:#?}", result);
+                assert!(!result.is_empty());
+                // Notes are attached to the diagnostic they belong to.
+                assert!(result
+                    .diagnostics
+                    .iter()
+                    .all(|diagnostic| diagnostic.severity
+                        != ShaderDiagnosticSeverity::Information
+                        && diagnostic.source == ShaderDiagnosticSource::Dxc));
+                assert!(result
+                    .diagnostics
+                    .iter()
+                    .any(|diagnostic| !diagnostic.related_information.is_empty()));
+            }
+            Err(err) => panic!("{}", err),
+        };
+    }
+
+    #[test]
+    #[cfg(not(target_os = "wasi"))] // Fix-its are emitted by DXC only.
+    fn hlsl_fix_it() {
+        let validator = create_test_validator(ShadingLanguage::Hlsl);
+        let file_path = Path::new("./test/hlsl/fix-it.hlsl");
+        let shader_content = std::fs::read_to_string(file_path).unwrap();
+        match validator.validate_shader(
+            &shader_content,
+            file_path,
+            &ShaderParams::default(),
+            &mut default_include_callback,
+        ) {
+            Ok(result) => {
+                println!("Diagnostic should have fix: {:#?}", result);
+                let fix = result
+                    .diagnostics
+                    .iter()
+                    .find_map(|diagnostic| diagnostic.fixes.first())
+                    .expect("Missing fix-it for missing semicolon.");
+                assert!(fix.new_text == ";");
+                assert!(fix.range.range.start == fix.range.range.end);
+                assert!(fix.range.range.start.line == 3);
+            }
+            Err(err) => panic!("{}", err),
+        };
+    }
+
+    #[test]
+    #[cfg(not(target_os = "wasi"))]
+    fn hlsl_version() {
+        let validator = create_test_validator(ShadingLanguage::Hlsl);
+        let version = validator
+            .get_version()
+            .expect("Failed to query DXC version.");
+        println!("DXC version: {}", version);
+        let macros = validator.get_predefined_macros();
+        assert!(macros.get("__DXC_VERSION_MAJOR") == Some(&version.major.to_string()));
+        assert!(macros.get("__DXC_VERSION_RELEASE") == Some(&version.patch.to_string()));
+    }
+
+    #[test]
+    fn validator_info() {
+        let hlsl_info = create_test_validator(ShadingLanguage::Hlsl).get_info();
+        #[cfg(not(target_os = "wasi"))]
+        {
+            assert!(hlsl_info.backend == ValidatorBackend::Dxc);
+            assert!(hlsl_info.version.is_some());
+            assert!(hlsl_info.supported_stages.len() == ShaderStage::ALL.len());
+        }
+        #[cfg(target_os = "wasi")]
+        assert!(hlsl_info.backend == ValidatorBackend::Glslang);
+        assert!(hlsl_info.fallback.is_none());
+        let glsl_info = create_test_validator(ShadingLanguage::Glsl).get_info();
+        assert!(glsl_info.backend == ValidatorBackend::Glslang);
+        assert!(!glsl_info.dxil_validation);
+        let wgsl_info = create_test_validator(ShadingLanguage::Wgsl).get_info();
+        assert!(wgsl_info.backend == ValidatorBackend::Naga);
+        assert!(
+            wgsl_info.supported_stages
+                == vec![
+                    ShaderStage::Vertex,
+                    ShaderStage::Fragment,
+                    ShaderStage::Compute
+                ]
+        );
+    }
+
     #[test]
     fn hlsl_ok() {
         let validator = create_test_validator(ShadingLanguage::Hlsl);
@@ -386,6 +717,197 @@ mod tests {
         };
     }
 
+    #[test]
+    #[cfg(not(target_os = "wasi"))] // Warning flags only supported by DXC.
+    fn hlsl_disabled_warnings() {
+        use crate::shader::HlslCompilationParams;
+
+        let validator = create_test_validator(ShadingLanguage::Hlsl);
+        let file_path = Path::new("./test/hlsl/conversion-warning.hlsl");
+        let shader_content = std::fs::read_to_string(file_path).unwrap();
+        // Check warning
+        match validator.validate_shader(
+            &shader_content,
+            file_path,
+            &ShaderParams::default(),
+            &mut default_include_callback,
+        ) {
+            Ok(result) => {
+                println!("Diagnostic should not be empty: {:#?}", result);
+                assert!(result
+                    .diagnostics
+                    .iter()
+                    .any(|diagnostic| diagnostic.code.as_deref() == Some("-Wconversion")));
+            }
+            Err(err) => panic!("{}", err),
+        };
+        // Check no warning
+        match validator.validate_shader(
+            &shader_content,
+            file_path,
+            &ShaderParams {
+                compilation: ShaderCompilationParams {
+                    hlsl: HlslCompilationParams {
+                        disabled_warnings: vec!["conversion".into()],
+                        ..Default::default()
+                    },
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+            &mut default_include_callback,
+        ) {
+            Ok(result) => {
+                println!("Diagnostic should be empty: {:#?}", result);
+                assert!(result.is_empty())
+            }
+            Err(err) => panic!("{}", err),
+        };
+    }
+
+    #[test]
+    fn glsl_compile() {
+        let validator = create_test_validator(ShadingLanguage::Glsl);
+        let file_path = Path::new("./test/glsl/ok.frag.glsl");
+        let shader_content = std::fs::read_to_string(file_path).unwrap();
+        match validator.compile_shader(
+            &shader_content,
+            file_path,
+            &ShaderParams::default(),
+            &mut default_include_callback,
+        ) {
+            Ok(artifact) => {
+                println!("Diagnostic should be empty: {:#?}", artifact.diagnostics);
+                assert!(artifact.diagnostics.is_empty());
+                match artifact.binary {
+                    Some(ShaderBinary::Spirv(words)) => assert!(words[0] == 0x07230203),
+                    binary => panic!("Expected SPIR-V binary, got {:?}", binary),
+                }
+            }
+            Err(err) => panic!("{}", err),
+        };
+    }
+
+    #[test]
+    #[cfg(not(target_os = "wasi"))] // Glslang does not output SPIR-V for HLSL target.
+    fn hlsl_compile_spirv() {
+        use crate::shader::HlslCompilationParams;
+
+        let validator = create_test_validator(ShadingLanguage::Hlsl);
+        let file_path = Path::new("./test/hlsl/spirv-shader.hlsl");
+        let shader_content = std::fs::read_to_string(file_path).unwrap();
+        match validator.compile_shader(
+            &shader_content,
+            file_path,
+            &ShaderParams {
+                compilation: ShaderCompilationParams {
+                    entry_point: Some("mainPS".into()),
+                    shader_stage: Some(ShaderStage::Fragment),
+                    hlsl: HlslCompilationParams {
+                        spirv: true,
+                        ..Default::default()
+                    },
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+            &mut default_include_callback,
+        ) {
+            Ok(artifact) => {
+                println!("Diagnostic should be empty: {:#?}", artifact.diagnostics);
+                assert!(artifact.diagnostics.is_empty());
+                match artifact.binary {
+                    Some(ShaderBinary::Spirv(words)) => assert!(words[0] == 0x07230203),
+                    binary => panic!("Expected SPIR-V binary, got {:?}", binary),
+                }
+            }
+            Err(err) => panic!("{}", err),
+        };
+    }
+
+    #[test]
+    #[cfg(not(target_os = "wasi"))] // DXIL is only output by DXC.
+    fn hlsl_reflection() {
+        let validator = create_test_validator(ShadingLanguage::Hlsl);
+        let file_path = Path::new("./test/hlsl/reflection.hlsl");
+        let shader_content = std::fs::read_to_string(file_path).unwrap();
+        match validator.reflect_shader(
+            &shader_content,
+            file_path,
+            &ShaderParams {
+                compilation: ShaderCompilationParams {
+                    entry_point: Some("CSMain".into()),
+                    shader_stage: Some(ShaderStage::Compute),
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+            &mut default_include_callback,
+        ) {
+            Ok(reflection) => {
+                println!("Reflection: {:#?}", reflection);
+                let globals = reflection.find_resource(1, 0).unwrap();
+                assert!(globals.kind == ShaderResourceKind::UniformBuffer);
+                let albedo = reflection.find_resource(0, 0).unwrap();
+                assert!(albedo.kind == ShaderResourceKind::SampledTexture);
+                let lights = reflection.find_resource(0, 2).unwrap();
+                assert!(lights.kind == ShaderResourceKind::ReadOnlyStorageBuffer);
+                let sampler = reflection.find_resource(0, 3).unwrap();
+                assert!(sampler.kind == ShaderResourceKind::Sampler);
+                let output = reflection.find_resource(2, 1).unwrap();
+                assert!(output.kind == ShaderResourceKind::StorageTexture);
+                let entry_point = reflection.find_entry_point("CSMain").unwrap();
+                assert!(entry_point.stage == Some(ShaderStage::Compute));
+                assert!(entry_point.workgroup_size == Some([8, 8, 1]));
+            }
+            Err(err) => panic!("{}", err),
+        };
+    }
+
+    #[test]
+    fn glsl_reflection() {
+        let validator = create_test_validator(ShadingLanguage::Glsl);
+        let file_path = Path::new("./test/glsl/uniforms.frag.glsl");
+        let shader_content = std::fs::read_to_string(file_path).unwrap();
+        match validator.reflect_shader(
+            &shader_content,
+            file_path,
+            &ShaderParams::default(),
+            &mut default_include_callback,
+        ) {
+            Ok(reflection) => {
+                println!("Reflection: {:#?}", reflection);
+                let global = reflection.find_resource(0, 0).unwrap();
+                assert!(global.kind == ShaderResourceKind::UniformBuffer);
+                assert!(global.name.as_deref() == Some("MatrixGlobal"));
+                let hidden = reflection.find_resource(0, 1).unwrap();
+                assert!(hidden.kind == ShaderResourceKind::UniformBuffer);
+                assert!(hidden.name.as_deref() == Some("u_accessor"));
+                let main = reflection.find_entry_point("main").unwrap();
+                assert!(main.stage == Some(ShaderStage::Fragment));
+            }
+            Err(err) => panic!("{}", err),
+        };
+    }
+
+    #[test]
+    fn spirv_reflection_overflow() {
+        // Nested arrays whose total size does not fit in u32 are reported instead of panicking.
+        fn instruction(opcode: u32, operands: &[u32]) -> Vec<u32> {
+            let mut words = vec![((operands.len() as u32 + 1) << 16) | opcode];
+            words.extend_from_slice(operands);
+            words
+        }
+        let mut words = vec![0x07230203, 0x00010000, 0, 8, 0];
+        words.extend(instruction(21, &[1, 32, 0])); // OpTypeInt %1 32 0
+        words.extend(instruction(43, &[1, 2, 0x10000])); // OpConstant %1 %2 65536
+        words.extend(instruction(28, &[3, 1, 2])); // OpTypeArray %3 %1 %2
+        words.extend(instruction(28, &[4, 3, 2])); // OpTypeArray %4 %3 %2
+        words.extend(instruction(32, &[5, 2, 4])); // OpTypePointer %5 Uniform %4
+        words.extend(instruction(59, &[5, 6, 2])); // OpVariable %5 %6 Uniform
+        assert!(ShaderReflection::from_spirv(&words).is_err());
+    }
+
     #[test]
     fn glsl_stages() {
         #[rustfmt::skip] // Keep them inline
@@ -486,6 +1008,98 @@ mod tests {
         }
     }
 
+    fn find_test_entry_points(
+        shading_language: ShadingLanguage,
+        file_path: &Path,
+        shader_content: &str,
+    ) -> Vec<ShaderEntryPoint> {
+        let mut shader_module_parser = ShaderModuleParser::from_shading_language(shading_language);
+        let symbol_provider = SymbolProvider::from_shading_language(shading_language);
+        let shader_module = shader_module_parser
+            .create_module(file_path, shader_content)
+            .unwrap();
+        let symbols = symbol_provider
+            .query_symbols(
+                &shader_module,
+                ShaderParams::default(),
+                &mut crate::symbols::symbol_provider::default_include_callback::<
+                    HlslShadingLanguageTag,
+                >,
+                None,
+            )
+            .unwrap();
+        symbol_provider
+            .query_entry_points(
+                &shader_module,
+                &symbols,
+                &ShaderParams::default(),
+                &mut default_include_callback,
+            )
+            .unwrap()
+    }
+
+    #[test]
+    fn hlsl_entry_points() {
+        let validator = create_test_validator(ShadingLanguage::Hlsl);
+        // All entry points are valid.
+        #[cfg(not(target_os = "wasi"))] // Geometry shader failing on WASI.
+        {
+            let file_path = Path::new("./test/hlsl/stages/graphics.hlsl");
+            let shader_content = std::fs::read_to_string(file_path).unwrap();
+            let entry_points =
+                find_test_entry_points(ShadingLanguage::Hlsl, file_path, &shader_content);
+            assert!(entry_points.len() == 5, "{:#?}", entry_points);
+            match validator.validate_entry_points(
+                &shader_content,
+                file_path,
+                &ShaderParams::default(),
+                &entry_points,
+                &mut default_include_callback,
+            ) {
+                Ok(result) => {
+                    println!("Diagnostic should be empty: {:#?}", result);
+                    assert!(result.is_empty())
+                }
+                Err(err) => panic!("{}", err),
+            };
+        }
+        // Error shared by all entry points is reported once, tagged with all of them.
+        let file_path = Path::new("./test/hlsl/entry-points.hlsl");
+        let shader_content = std::fs::read_to_string(file_path).unwrap();
+        let entry_points =
+            find_test_entry_points(ShadingLanguage::Hlsl, file_path, &shader_content);
+        assert!(entry_points.len() == 2, "{:#?}", entry_points);
+        match validator.validate_entry_points(
+            &shader_content,
+            file_path,
+            &ShaderParams::default(),
+            &entry_points,
+            &mut default_include_callback,
+        ) {
+            Ok(result) => {
+                println!(
+                    "Diagnostic should be tagged with both entry points: {:#?}",
+                    result
+                );
+                assert!(!result.is_empty());
+                assert!(result.diagnostics.iter().all(|diagnostic| {
+                    diagnostic.entry_points
+                        == vec![
+                            ShaderDiagnosticEntryPoint {
+                                label: "VSMain".into(),
+                                stage: ShaderStage::Vertex,
+                            },
+                            ShaderDiagnosticEntryPoint {
+                                label: "PSMain".into(),
+                                stage: ShaderStage::Fragment,
+                            },
+                        ]
+                }));
+            }
+            Err(err) => panic!("{}", err),
+        };
+    }
+
     #[test]
     fn wgsl_stages() {
         // Wgsl only support three main stages.
@@ -528,6 +1142,32 @@ mod tests {
         }
     }
 
+    #[test]
+    fn wgsl_reflection() {
+        let validator = create_test_validator(ShadingLanguage::Wgsl);
+        let file_path = Path::new("./test/wgsl/stages/compute.wgsl");
+        let shader_content = std::fs::read_to_string(file_path).unwrap();
+        match validator.reflect_shader(
+            &shader_content,
+            file_path,
+            &ShaderParams::default(),
+            &mut default_include_callback,
+        ) {
+            Ok(reflection) => {
+                println!("Reflection: {:#?}", reflection);
+                let data = reflection.find_resource(0, 0).unwrap();
+                assert!(data.kind == ShaderResourceKind::StorageBuffer);
+                assert!(data.name.as_deref() == Some("data"));
+                let entry_point = reflection.find_entry_point("CSMain").unwrap();
+                assert!(entry_point.stage == Some(ShaderStage::Compute));
+                assert!(entry_point.workgroup_size == Some([64, 1, 1]));
+                assert!(entry_point.inputs.len() == 1);
+                assert!(entry_point.inputs[0].built_in.as_deref() == Some("GlobalInvocationId"));
+            }
+            Err(err) => panic!("{}", err),
+        };
+    }
+
     #[test]
     fn wgsl_ok() {
         let validator = create_test_validator(ShadingLanguage::Wgsl);
@@ -546,4 +1186,136 @@ mod tests {
             Err(err) => panic!("{}", err),
         };
     }
+
+    #[test]
+    fn wgsl_imports() {
+        let validator = create_test_validator(ShadingLanguage::Wgsl);
+        let file_path = Path::new("./test/wgsl/imports/main.wgsl");
+        let shader_content = std::fs::read_to_string(file_path).unwrap();
+        match validator.validate_shader(
+            &shader_content,
+            file_path,
+            &ShaderParams {
+                context: ShaderContextParams {
+                    defines: HashMap::from([("USE_FOG".into(), "".into())]),
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+            &mut default_include_callback,
+        ) {
+            Ok(result) => {
+                println!("Diagnostic should be empty: {:#?}", result);
+                assert!(result.is_empty())
+            }
+            Err(err) => panic!("{}", err),
+        };
+        // Without shader def, the #else branch is validated.
+        match validator.validate_shader(
+            &shader_content,
+            file_path,
+            &ShaderParams::default(),
+            &mut default_include_callback,
+        ) {
+            Ok(result) => {
+                println!("Diagnostic should not be empty: {:#?}", result);
+                assert!(result
+                    .diagnostics
+                    .iter()
+                    .any(|diagnostic| diagnostic.range.file_path == file_path
+                        && diagnostic.range.range.start.line == 7));
+            }
+            Err(err) => panic!("{}", err),
+        };
+    }
+
+    #[test]
+    fn wgsl_missing_import() {
+        let validator = create_test_validator(ShadingLanguage::Wgsl);
+        let file_path = Path::new("./test/wgsl/imports/missing.wgsl");
+        let shader_content = std::fs::read_to_string(file_path).unwrap();
+        match validator.validate_shader(
+            &shader_content,
+            file_path,
+            &ShaderParams::default(),
+            &mut default_include_callback,
+        ) {
+            Ok(result) => {
+                println!("Diagnostic should not be empty: {:#?}", result);
+                assert!(result.diagnostics.iter().any(|diagnostic| diagnostic
+                    .range
+                    .range
+                    .start
+                    .line
+                    == 0));
+            }
+            Err(err) => panic!("{}", err),
+        };
+    }
+
+    #[test]
+    fn wgsl_labels() {
+        let validator = create_test_validator(ShadingLanguage::Wgsl);
+        let file_path = Path::new("./test/wgsl/redefinition.wgsl");
+        let shader_content = std::fs::read_to_string(file_path).unwrap();
+        match validator.validate_shader(
+            &shader_content,
+            file_path,
+            &ShaderParams::default(),
+            &mut default_include_callback,
+        ) {
+            Ok(result) => {
+                println!("Diagnostic should have related information: {:#?}", result);
+                assert!(result.diagnostics.len() == 1);
+                let diagnostic = &result.diagnostics[0];
+                // Primary label is the redefinition, secondary the previous definition.
+                assert!(diagnostic.range.range.start == ShaderPosition::new(4, 3));
+                assert!(diagnostic.range.range.end == ShaderPosition::new(4, 6));
+                assert!(diagnostic.related_information.len() == 1);
+                assert!(
+                    diagnostic.related_information[0].range.range.start
+                        == ShaderPosition::new(0, 3)
+                );
+            }
+            Err(err) => panic!("{}", err),
+        };
+    }
+
+    #[test]
+    fn wgsl_capabilities() {
+        let validator = create_test_validator(ShadingLanguage::Wgsl);
+        let file_path = Path::new("./test/wgsl/push_constants.wgsl");
+        let shader_content = std::fs::read_to_string(file_path).unwrap();
+        let validate = |wgsl: WgslCompilationParams| {
+            validator
+                .validate_shader(
+                    &shader_content,
+                    file_path,
+                    &ShaderParams {
+                        compilation: ShaderCompilationParams {
+                            wgsl,
+                            ..Default::default()
+                        },
+                        ..Default::default()
+                    },
+                    &mut default_include_callback,
+                )
+                .unwrap()
+        };
+        // Native is the default profile, to not break existing native users.
+        assert!(WgslCompilationParams::default().profile == WgslCapabilityProfile::Native);
+        assert!(validate(WgslCompilationParams::default()).is_empty());
+        // Push constants are not part of WebGPU baseline.
+        assert!(!validate(WgslCompilationParams {
+            profile: WgslCapabilityProfile::WebGpu,
+            ..Default::default()
+        })
+        .is_empty());
+        assert!(validate(WgslCompilationParams {
+            profile: WgslCapabilityProfile::WebGpu,
+            push_constants: true,
+            ..Default::default()
+        })
+        .is_empty());
+    }
 }
@@ -2,44 +2,178 @@
 
 use naga::{
     front::wgsl::{self, ParseError},
-    valid::{Capabilities, ValidationFlags},
+    valid::{Capabilities, ValidationError, ValidationFlags},
+    Module, Span, WithSpan,
 };
-use std::path::Path;
+use std::{cell::RefCell, error::Error, path::Path};
 
 use crate::{
     position::{ShaderFileRange, ShaderPosition},
-    shader::{ShaderParams, ShaderStage},
-    shader_error::{ShaderDiagnostic, ShaderDiagnosticList, ShaderDiagnosticSeverity, ShaderError},
+    reflection::ShaderReflection,
+    shader::{
+        ShaderParams, ShaderStage, WgslCapabilityProfile, WgslCompilationParams, WgslValidationFlag,
+    },
+    shader_error::{
+        ShaderDiagnostic, ShaderDiagnosticList, ShaderDiagnosticRelatedInformation,
+        ShaderDiagnosticSeverity, ShaderDiagnosticSource, ShaderError,
+    },
 };
 
-use super::validator::ValidatorImpl;
+use super::{
+    validator::{ValidatorBackend, ValidatorImpl},
+    wgsl_composer::{WgslComposer, WgslImportPathIndex},
+};
 
-pub struct Naga {}
+pub struct Naga {
+    import_path_index: RefCell<WgslImportPathIndex>, // Kept between validations as it is costly to build.
+}
 
 impl Naga {
     pub fn new() -> Self {
-        Self {}
+        Self {
+            import_path_index: RefCell::new(WgslImportPathIndex::default()),
+        }
     }
-    fn from_parse_err(err: ParseError, file_path: &Path, shader_content: &str) -> ShaderDiagnostic {
-        let error = err.emit_to_string(shader_content);
-        let loc = err.location(shader_content);
-        if let Some(loc) = loc {
-            ShaderDiagnostic {
-                severity: ShaderDiagnosticSeverity::Error,
-                error,
-                range: ShaderFileRange::new(
-                    file_path.into(),
-                    ShaderPosition::new(loc.line_number - 1, loc.line_position),
-                    ShaderPosition::new(loc.line_number - 1, loc.line_position),
-                ),
+    fn get_capabilities(params: &WgslCompilationParams) -> Capabilities {
+        let mut capabilities = match params.profile {
+            // Cube arrays & sample shading are part of WebGPU core.
+            WgslCapabilityProfile::WebGpu => {
+                Capabilities::CUBE_ARRAY_TEXTURES | Capabilities::MULTISAMPLED_SHADING
             }
-        } else {
-            ShaderDiagnostic {
-                severity: ShaderDiagnosticSeverity::Error,
-                error,
-                range: ShaderFileRange::zero(file_path.into()),
+            WgslCapabilityProfile::Native => Capabilities::all(),
+        };
+        if params.push_constants {
+            capabilities |= Capabilities::PUSH_CONSTANT;
+        }
+        if params.subgroups {
+            capabilities |= Capabilities::SUBGROUP;
+        }
+        if params.ray_query {
+            capabilities |= Capabilities::RAY_QUERY;
+        }
+        capabilities
+    }
+    fn get_validation_flags(params: &WgslCompilationParams) -> ValidationFlags {
+        params
+            .validation_flags
+            .iter()
+            .fold(ValidationFlags::empty(), |flags, flag| {
+                flags
+                    | match flag {
+                        WgslValidationFlag::Expressions => ValidationFlags::EXPRESSIONS,
+                        WgslValidationFlag::Blocks => ValidationFlags::BLOCKS,
+                        WgslValidationFlag::ControlFlowUniformity => {
+                            ValidationFlags::CONTROL_FLOW_UNIFORMITY
+                        }
+                        WgslValidationFlag::StructLayouts => ValidationFlags::STRUCT_LAYOUTS,
+                        WgslValidationFlag::Constants => ValidationFlags::CONSTANTS,
+                        WgslValidationFlag::Bindings => ValidationFlags::BINDINGS,
+                    }
+            })
+    }
+    fn span_to_range(
+        span: Span,
+        file_path: &Path,
+        shader_content: &str,
+    ) -> Option<ShaderFileRange> {
+        let range = span.to_range()?;
+        Some(ShaderFileRange::new(
+            file_path.into(),
+            ShaderPosition::from_byte_offset(shader_content, range.start).ok()?,
+            ShaderPosition::from_byte_offset(shader_content, range.end).ok()?,
+        ))
+    }
+    // First label is the primary one, others are stored as related information.
+    fn from_labels<'a>(
+        message: String,
+        labels: impl Iterator<Item = (Span, &'a str)>,
+        file_path: &Path,
+        shader_content: &str,
+    ) -> ShaderDiagnostic {
+        let mut labels = labels.filter_map(|(span, label)| {
+            Self::span_to_range(span, file_path, shader_content).map(|range| (range, label))
+        });
+        let (range, error) = match labels.next() {
+            Some((range, label)) if label.is_empty() || label == message => (range, message),
+            Some((range, label)) => (range, format!("{}: {}", message, label)),
+            None => (ShaderFileRange::zero(file_path.into()), message),
+        };
+        ShaderDiagnostic::new(ShaderDiagnosticSeverity::Error, error, range)
+            .with_source(ShaderDiagnosticSource::Naga)
+            .with_related_information(
+                labels
+                    .map(|(range, label)| ShaderDiagnosticRelatedInformation {
+                        message: label.into(),
+                        range,
+                    })
+                    .collect(),
+            )
+    }
+    fn from_parse_err(err: ParseError, file_path: &Path, shader_content: &str) -> ShaderDiagnostic {
+        Self::from_labels(
+            err.message().into(),
+            err.labels(),
+            file_path,
+            shader_content,
+        )
+    }
+    fn from_validation_err(
+        err: WithSpan<ValidationError>,
+        file_path: &Path,
+        shader_content: &str,
+    ) -> ShaderDiagnostic {
+        // Message of the error along with its sources.
+        let mut message = err.as_inner().to_string();
+        let mut source: &dyn Error = err.as_inner();
+        while let Some(next) = source.source() {
+            message.push_str(&format!("\n{}", next));
+            source = next;
+        }
+        Self::from_labels(
+            message,
+            err.spans().map(|(span, label)| (*span, label.as_str())),
+            file_path,
+            shader_content,
+        )
+    }
+    // Compose, parse & validate the module. Module is None if any error was found.
+    fn create_module(
+        &self,
+        shader_content: &str,
+        file_path: &Path,
+        params: &ShaderParams,
+        include_callback: &mut dyn FnMut(&Path) -> Option<String>,
+    ) -> (Option<Module>, ShaderDiagnosticList) {
+        // Resolve imports & shader defs before handing content to naga.
+        let composed_shader = WgslComposer::new(
+            file_path,
+            params,
+            &mut self.import_path_index.borrow_mut(),
+            include_callback,
+        )
+        .compose(file_path, shader_content);
+        let composed_content = composed_shader.content.as_str();
+        let source_map = &composed_shader.source_map;
+        let mut list = composed_shader.diagnostics;
+        let module = match wgsl::parse_str(composed_content) {
+            Ok(module) => module,
+            Err(err) => {
+                let diagnostic = Self::from_parse_err(err, file_path, composed_content);
+                list.push(source_map.map_diagnostic(diagnostic));
+                return (None, list);
             }
+        };
+
+        let mut validator = naga::valid::Validator::new(
+            Self::get_validation_flags(&params.compilation.wgsl),
+            Self::get_capabilities(&params.compilation.wgsl),
+        );
+        if let Err(err) = validator.validate(&module) {
+            let diagnostic = Self::from_validation_err(err, file_path, composed_content);
+            list.push(source_map.map_diagnostic(diagnostic));
+            return (None, list);
         }
+        (Some(module), list)
     }
 }
 impl ValidatorImpl for Naga {
@@ -47,43 +181,29 @@ impl ValidatorImpl for Naga {
         &self,
         shader_content: &str,
         file_path: &Path,
-        _params: &ShaderParams,
-        _include_callback: &mut dyn FnMut(&Path) -> Option<String>,
+        params: &ShaderParams,
+        include_callback: &mut dyn FnMut(&Path) -> Option<String>,
     ) -> Result<ShaderDiagnosticList, ShaderError> {
-        let module = match wgsl::parse_str(shader_content)
-            .map_err(|err| Self::from_parse_err(err, file_path, shader_content))
-        {
-            Ok(module) => module,
-            Err(diag) => {
-                return Ok(ShaderDiagnosticList::from(diag));
-            }
-        };
-
-        let mut validator =
-            naga::valid::Validator::new(ValidationFlags::all(), Capabilities::all());
-        if let Err(error) = validator.validate(&module) {
-            let mut list = ShaderDiagnosticList::empty();
-            for (span, _) in error.spans() {
-                let loc = span.location(shader_content);
-                list.push(ShaderDiagnostic {
-                    severity: ShaderDiagnosticSeverity::Error,
-                    error: error.emit_to_string(""),
-                    range: ShaderFileRange::new(
-                        file_path.into(),
-                        ShaderPosition::new(loc.line_number - 1, loc.line_position),
-                        ShaderPosition::new(loc.line_number - 1, loc.line_position),
-                    ),
-                });
-            }
-            if list.is_empty() {
-                Err(ShaderError::InternalErr(
-                    error.emit_to_string(shader_content),
-                ))
-            } else {
-                Ok(list)
-            }
-        } else {
-            Ok(ShaderDiagnosticList::empty())
+        let (_module, list) =
+            self.create_module(shader_content, file_path, params, include_callback);
+        Ok(list)
+    }
+    fn reflect_shader(
+        &self,
+        shader_content: &str,
+        file_path: &Path,
+        params: &ShaderParams,
+        include_callback: &mut dyn FnMut(&Path) -> Option<String>,
+    ) -> Result<ShaderReflection, ShaderError> {
+        // Naga does not output binary here, reflect the module directly.
+        match self.create_module(shader_content, file_path, params, include_callback) {
+            (Some(module), _) => Ok(ShaderReflection::from_naga(&module)),
+            (None, list) => Err(ShaderError::ValidationError(format!(
+                "Failed to compile shader for reflection: {}",
+                list.diagnostics
+                    .first()
+                    .map_or("unknown error", |diagnostic| diagnostic.error.as_str())
+            ))),
         }
     }
     fn support(&self, shader_stage: ShaderStage) -> bool {
@@ -92,4 +212,10 @@ impl ValidatorImpl for Naga {
             _ => false,
         }
     }
+    fn get_backend(&self) -> ValidatorBackend {
+        ValidatorBackend::Naga
+    }
+    fn on_file_saved(&self, file_path: &Path) {
+        self.import_path_index.borrow_mut().invalidate(file_path);
+    }
 }
@@ -1,11 +1,15 @@
 //! Validator trait implemented for all languages.
-use std::path::Path;
+use std::{collections::HashMap, fmt, path::Path};
+
+use serde::{Deserialize, Serialize};
 
 #[cfg(not(target_os = "wasi"))]
 use crate::validator::dxc::Dxc;
 use crate::{
+    reflection::ShaderReflection,
     shader::{ShaderParams, ShaderStage, ShadingLanguage},
-    shader_error::{ShaderDiagnosticList, ShaderError},
+    shader_error::{ShaderDiagnosticEntryPoint, ShaderDiagnosticList, ShaderError},
+    symbols::symbols::ShaderEntryPoint,
     validator::{glslang::Glslang, naga::Naga},
 };
 
@@ -14,6 +18,91 @@ pub fn default_include_callback(path: &Path) -> Option<String> {
     Some(std::fs::read_to_string(path).unwrap())
 }
 
+/// Version of the compiler used by a validator.
+#[derive(Debug, Default, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
+pub struct ValidatorVersion {
+    pub major: u32,
+    pub minor: u32,
+    pub patch: u32, // Release for DXC
+    pub build: u32, // Commit count for DXC
+}
+
+impl fmt::Display for ValidatorVersion {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}.{}.{}.{}",
+            self.major, self.minor, self.patch, self.build
+        )
+    }
+}
+
+/// Compiler backing a validator.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[serde(rename_all = "camelCase")]
+pub enum ValidatorBackend {
+    Dxc,
+    Glslang,
+    Naga,
+    Custom, // Validator implemented outside of this crate.
+}
+
+impl fmt::Display for ValidatorBackend {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ValidatorBackend::Dxc => write!(f, "DXC"),
+            ValidatorBackend::Glslang => write!(f, "glslang"),
+            ValidatorBackend::Naga => write!(f, "naga"),
+            ValidatorBackend::Custom => write!(f, "custom"),
+        }
+    }
+}
+
+/// Description of a validator, to let users know what is validating their shaders.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct ValidatorInfo {
+    pub backend: ValidatorBackend,
+    pub version: Option<ValidatorVersion>,
+    pub dxil_validation: bool, // DXIL validator available to validate & sign binaries. DXC only.
+    pub dxil_validator_version: Option<(u32, u32)>, // DXC only.
+    pub supported_stages: Vec<ShaderStage>,
+    pub fallback: Option<String>, // Reason why the preferred backend could not be used.
+}
+
+/// Binary produced by a successful compilation.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ShaderBinary {
+    Spirv(Vec<u32>), // SPIR-V words
+    Dxil(Vec<u8>),   // DXIL container
+}
+
+impl ShaderBinary {
+    /// Reflect the resource interface of the binary.
+    pub fn reflect(&self) -> Result<ShaderReflection, ShaderError> {
+        match self {
+            ShaderBinary::Spirv(words) => ShaderReflection::from_spirv(words),
+            ShaderBinary::Dxil(container) => ShaderReflection::from_dxil(container),
+        }
+    }
+}
+
+/// Result of a shader compilation.
+#[derive(Debug, Clone)]
+pub struct ShaderCompilationArtifact {
+    pub binary: Option<ShaderBinary>, // None if compilation failed.
+    pub diagnostics: ShaderDiagnosticList,
+}
+
+impl ShaderCompilationArtifact {
+    pub fn failed(diagnostics: ShaderDiagnosticList) -> Self {
+        Self {
+            binary: None,
+            diagnostics,
+        }
+    }
+}
+
 /// Trait that all validator must implement to validate files.
 pub trait ValidatorImpl {
     fn validate_shader(
@@ -24,8 +113,133 @@ pub trait ValidatorImpl {
         include_callback: &mut dyn FnMut(&Path) -> Option<String>,
     ) -> Result<ShaderDiagnosticList, ShaderError>;
 
+    /// Validate each of the given entry points with its own stage & merge their diagnostic list.
+    /// Entry points can be found with [`crate::symbols::symbol_provider::SymbolProvider::query_entry_points`].
+    /// Each diagnostic is tagged with the entry point which emitted it. Diagnostics emitted by several entry points are reported once.
+    /// Entry points whose stage is not supported are skipped. Without entry points, the shader is validated with the given params.
+    fn validate_entry_points(
+        &self,
+        shader_content: &str,
+        file_path: &Path,
+        params: &ShaderParams,
+        entry_points: &[ShaderEntryPoint],
+        include_callback: &mut dyn FnMut(&Path) -> Option<String>,
+    ) -> Result<ShaderDiagnosticList, ShaderError> {
+        if entry_points.is_empty() {
+            return self.validate_shader(shader_content, file_path, params, include_callback);
+        }
+        let mut diagnostic_list = ShaderDiagnosticList::empty();
+        for entry_point in entry_points {
+            if !self.support(entry_point.stage) {
+                continue;
+            }
+            let mut entry_point_params = params.clone();
+            entry_point_params.compilation.entry_point = Some(entry_point.label.clone());
+            entry_point_params.compilation.shader_stage = Some(entry_point.stage);
+            let entry_point_diagnostic_list = self.validate_shader(
+                shader_content,
+                file_path,
+                &entry_point_params,
+                include_callback,
+            )?;
+            let diagnostic_entry_point = ShaderDiagnosticEntryPoint {
+                label: entry_point.label.clone(),
+                stage: entry_point.stage,
+            };
+            for mut diagnostic in entry_point_diagnostic_list.diagnostics {
+                // Errors outside of entry points, such as syntax errors, are emitted by several of them.
+                match diagnostic_list.diagnostics.iter_mut().find(|reported| {
+                    reported.range == diagnostic.range
+                        && reported.error == diagnostic.error
+                        && reported.severity == diagnostic.severity
+                }) {
+                    Some(reported) => {
+                        if !reported.entry_points.contains(&diagnostic_entry_point) {
+                            reported.entry_points.push(diagnostic_entry_point.clone());
+                        }
+                    }
+                    None => {
+                        diagnostic.entry_points = vec![diagnostic_entry_point.clone()];
+                        diagnostic_list.push(diagnostic);
+                    }
+                }
+            }
+        }
+        Ok(diagnostic_list)
+    }
+
+    /// Compile the shader and return the binary along with the diagnostics.
+    /// Validators which do not output any binary return an error.
+    fn compile_shader(
+        &self,
+        _shader_content: &str,
+        _file_path: &Path,
+        _params: &ShaderParams,
+        _include_callback: &mut dyn FnMut(&Path) -> Option<String>,
+    ) -> Result<ShaderCompilationArtifact, ShaderError> {
+        Err(ShaderError::ValidationError(
+            "This validator does not support compilation.".into(),
+        ))
+    }
+
+    /// Compile the shader and reflect its resource interface.
+    /// Fail if the shader does not compile.
+    fn reflect_shader(
+        &self,
+        shader_content: &str,
+        file_path: &Path,
+        params: &ShaderParams,
+        include_callback: &mut dyn FnMut(&Path) -> Option<String>,
+    ) -> Result<ShaderReflection, ShaderError> {
+        let artifact = self.compile_shader(shader_content, file_path, params, include_callback)?;
+        match artifact.binary {
+            Some(binary) => binary.reflect(),
+            None => Err(ShaderError::ValidationError(format!(
+                "Failed to compile shader for reflection: {}",
+                artifact
+                    .diagnostics
+                    .diagnostics
+                    .first()
+                    .map_or("unknown error", |diagnostic| diagnostic.error.as_str())
+            ))),
+        }
+    }
+
+    /// Version of the loaded compiler, if it can be queried.
+    fn get_version(&self) -> Option<ValidatorVersion> {
+        None
+    }
+
+    /// Macros predefined by the compiler, which are not visible in the source.
+    fn get_predefined_macros(&self) -> HashMap<String, String> {
+        HashMap::new()
+    }
+
+    /// Notify that a file was saved on disk, so that what was cached from it can be dropped.
+    fn on_file_saved(&self, _file_path: &Path) {}
+
     fn support(&self, shader_stage: ShaderStage) -> bool;
 
+    /// Compiler backing this validator.
+    fn get_backend(&self) -> ValidatorBackend {
+        ValidatorBackend::Custom
+    }
+
+    /// Describe this validator.
+    fn get_info(&self) -> ValidatorInfo {
+        ValidatorInfo {
+            backend: self.get_backend(),
+            version: self.get_version(),
+            dxil_validation: false,
+            dxil_validator_version: None,
+            supported_stages: ShaderStage::ALL
+                .into_iter()
+                .filter(|shader_stage| self.support(*shader_stage))
+                .collect(),
+            fallback: None,
+        }
+    }
+
     fn get_file_name(&self, path: &Path) -> String {
         String::from(path.file_name().unwrap().to_string_lossy())
     }
@@ -52,6 +266,7 @@ pub trait ValidatorImpl {
 /// ```
 pub struct Validator {
     imp: Box<dyn ValidatorImpl>,
+    fallback: Option<String>, // Reason of the fallback on another backend, if any.
 }
 impl Validator {
     /// Create a validator for Glsl.
@@ -59,20 +274,51 @@ impl Validator {
     pub fn glsl() -> Self {
         Self {
             imp: Box::new(Glslang::glsl()),
+            fallback: None,
         }
     }
     /// Create a validator for Hlsl.
     /// It will use DXC if it is available and fallback on glslang if its not supported.
     /// Note that glslang support for HLSL is not as advanced as dxc.
     pub fn hlsl() -> Self {
-        Self {
-            #[cfg(not(target_os = "wasi"))]
-            imp: match Dxc::new(Dxc::find_dxc_library()) {
-                Ok(dxc) => Box::new(dxc),
-                Err(_) => Box::new(Glslang::hlsl()), // Failed to instantiate dxc. Fallback to glslang.
+        #[cfg(not(target_os = "wasi"))]
+        let dxc: Result<Box<dyn ValidatorImpl>, String> = Dxc::new(Dxc::find_dxc_library())
+            .map(|dxc| Box::new(dxc) as Box<dyn ValidatorImpl>)
+            .map_err(|err| format!("Failed to load DXC library: {}", err));
+        #[cfg(target_os = "wasi")]
+        let dxc: Result<Box<dyn ValidatorImpl>, String> =
+            Err("DXC is not supported on WASI.".into());
+        match dxc {
+            Ok(imp) => Self {
+                imp,
+                fallback: None,
             },
-            #[cfg(target_os = "wasi")]
-            imp: Box::new(Glslang::hlsl()),
+            // Failed to instantiate dxc. Fallback to glslang.
+            Err(fallback) => Self {
+                imp: Box::new(Glslang::hlsl()),
+                fallback: Some(fallback),
+            },
+        }
+    }
+    /// Create a validator for Hlsl using the DXC libraries of the given folder.
+    /// Unlike [`Validator::hlsl`], it does not fallback on glslang and return an error if the library failed to load.
+    /// If no folder is given, the libraries are searched next to the executable, then in global path.
+    #[cfg(not(target_os = "wasi"))]
+    pub fn dxc(library_path: Option<std::path::PathBuf>) -> Result<Self, ShaderError> {
+        let library_path = library_path.or_else(Dxc::find_dxc_library);
+        match Dxc::new(library_path.clone()) {
+            Ok(dxc) => Ok(Self {
+                imp: Box::new(dxc),
+                fallback: None,
+            }),
+            Err(err) => Err(ShaderError::InternalErr(match library_path {
+                Some(library_path) => format!(
+                    "Failed to load DXC library from {}: {}",
+                    library_path.display(),
+                    err
+                ),
+                None => format!("Failed to load DXC library from global path: {}", err),
+            })),
         }
     }
     /// Create a validator for Wgsl.
@@ -80,6 +326,7 @@ impl Validator {
     pub fn wgsl() -> Self {
         Self {
             imp: Box::new(Naga::new()),
+            fallback: None,
         }
     }
     /// Create a validator from the given [`ShadingLanguage`]
@@ -104,4 +351,73 @@ impl Validator {
         self.imp
             .validate_shader(shader_content, file_path, params, include_callback)
     }
+    /// Validate each of the given entry points with its own stage & merge their diagnostic list.
+    /// Entry points can be found with [`crate::symbols::symbol_provider::SymbolProvider::query_entry_points`].
+    /// Each diagnostic is tagged with the entry point which emitted it.
+    pub fn validate_entry_points(
+        &self,
+        shader_content: &str,
+        file_path: &Path,
+        params: &ShaderParams,
+        entry_points: &[ShaderEntryPoint],
+        include_callback: &mut dyn FnMut(&Path) -> Option<String>,
+    ) -> Result<ShaderDiagnosticList, ShaderError> {
+        self.imp.validate_entry_points(
+            shader_content,
+            file_path,
+            params,
+            entry_points,
+            include_callback,
+        )
+    }
+    /// Compile a shader and return the binary with its diagnostic list, or an error if the process failed.
+    /// Binary is SPIR-V for glslang or DXC targeting SPIR-V, DXIL otherwise.
+    /// Binary is None if compilation failed, diagnostics holding the reason.
+    pub fn compile_shader(
+        &self,
+        shader_content: &str,
+        file_path: &Path,
+        params: &ShaderParams,
+        include_callback: &mut dyn FnMut(&Path) -> Option<String>,
+    ) -> Result<ShaderCompilationArtifact, ShaderError> {
+        self.imp
+            .compile_shader(shader_content, file_path, params, include_callback)
+    }
+    /// Compile a shader and reflect its resource interface, or return an error if the shader does not compile.
+    /// Reflection is read from the SPIR-V or DXIL binary, or from the naga module for Wgsl.
+    pub fn reflect_shader(
+        &self,
+        shader_content: &str,
+        file_path: &Path,
+        params: &ShaderParams,
+        include_callback: &mut dyn FnMut(&Path) -> Option<String>,
+    ) -> Result<ShaderReflection, ShaderError> {
+        self.imp
+            .reflect_shader(shader_content, file_path, params, include_callback)
+    }
+    /// Get the version of the compiler used for validation, if it can be queried.
+    pub fn get_version(&self) -> Option<ValidatorVersion> {
+        self.imp.get_version()
+    }
+    /// Describe the validator: backend used, its version and the supported stages.
+    /// If the backend is not the preferred one for the language, the reason is given as fallback.
+    pub fn get_info(&self) -> ValidatorInfo {
+        ValidatorInfo {
+            fallback: self.fallback.clone(),
+            ..self.imp.get_info()
+        }
+    }
+    /// Check if the given stage can be validated.
+    pub fn support(&self, shader_stage: ShaderStage) -> bool {
+        self.imp.support(shader_stage)
+    }
+    /// Get the macros predefined by the compiler used for validation.
+    /// These should be used when evaluating preprocessor regions of a file.
+    pub fn get_predefined_macros(&self) -> HashMap<String, String> {
+        self.imp.get_predefined_macros()
+    }
+    /// Notify that a file was saved on disk, so that the validator drop what it cached from it.
+    pub fn on_file_saved(&self, file_path: &Path) {
+        self.imp.on_file_saved(file_path)
+    }
 }
@@ -0,0 +1,700 @@
+//! Composition of naga_oil style WGSL modules before validation with naga.
+//!
+//! Naga does not know anything about `#import` & `#define_import_path`.
+//! Imported modules are resolved, preprocessed with shader defs and appended to the main module.
+//! Every line is kept so that positions in composed content can be mapped back to their file.
+use std::{
+    collections::{HashMap, HashSet},
+    path::{Path, PathBuf},
+    sync::LazyLock,
+};
+
+use regex::Regex;
+
+use crate::{
+    include::IncludeHandler,
+    position::{ShaderFileRange, ShaderPosition, ShaderRange},
+    shader::ShaderParams,
+    shader_error::{
+        ShaderDiagnostic, ShaderDiagnosticList, ShaderDiagnosticRelatedInformation,
+        ShaderDiagnosticSeverity,
+    },
+};
+
+/// Avoid walking huge folder when looking for `#define_import_path`.
+const IMPORT_PATH_INDEX_FILE_LIMIT: usize = 4096;
+const IMPORT_PATH_INDEX_DEPTH_LIMIT: usize = 8;
+
+// Shader def substituted in code, such as #{MAX_LIGHTS}.
+static SHADER_DEF_REGEX: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"#\{(\w+)\}").unwrap());
+// Comparison of #if directive, such as #if LIGHT_COUNT > 2.
+static CONDITION_REGEX: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^(\w+)\s*(==|!=|<=|>=|<|>)\s*(\S+)$").unwrap());
+// Qualifiers of a path, such as common::lighting:: in common::lighting::diffuse.
+static QUALIFIERS_REGEX: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(^|[^\w:])((?:\w+::)+)").unwrap());
+// Identifier which is not a member or qualified.
+static IDENTIFIER_REGEX: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(^|[^\w:.])(\w+)\b").unwrap());
+
+/// Modules declaring their import path with `#define_import_path`, indexed per directory.
+/// Walking directories is costly, so an index is kept until a file of its directory changes.
+#[derive(Debug, Default)]
+pub struct WgslImportPathIndex {
+    directories: HashMap<PathBuf, HashMap<String, PathBuf>>,
+}
+
+impl WgslImportPathIndex {
+    /// Drop the index of all directories containing this file.
+    pub fn invalidate(&mut self, file_path: &Path) {
+        self.directories
+            .retain(|directory, _| !file_path.starts_with(directory));
+    }
+    // Find the module declaring this import path, indexing directories on first use.
+    fn find(&mut self, directories: &[PathBuf], module_path: &str) -> Option<PathBuf> {
+        directories.iter().find_map(|directory| {
+            self.directories
+                .entry(directory.clone())
+                .or_insert_with(|| Self::index_directory(directory))
+                .get(module_path)
+                .cloned()
+        })
+    }
+    // Files are read from disk, as reading them through the include callback would make them dependencies.
+    fn index_directory(directory: &Path) -> HashMap<String, PathBuf> {
+        fn visit(
+            directory: &Path,
+            depth: usize,
+            file_count: &mut usize,
+            index: &mut HashMap<String, PathBuf>,
+        ) {
+            if depth > IMPORT_PATH_INDEX_DEPTH_LIMIT {
+                return;
+            }
+            let entries = match std::fs::read_dir(directory) {
+                Ok(entries) => entries,
+                Err(_) => return,
+            };
+            for entry in entries.flatten() {
+                if *file_count >= IMPORT_PATH_INDEX_FILE_LIMIT {
+                    return;
+                }
+                let path = entry.path();
+                if path.is_dir() {
+                    visit(&path, depth + 1, file_count, index);
+                } else if path.extension().map(|e| e == "wgsl").unwrap_or(false) {
+                    *file_count += 1;
+                    if let Ok(content) = std::fs::read_to_string(&path) {
+                        if let Some(import_path) = content
+                            .lines()
+                            .map(|line| line.trim())
+                            .find_map(|line| line.strip_prefix("#define_import_path"))
+                        {
+                            if let Ok(path) = crate::include::canonicalize(&path) {
+                                index.entry(import_path.trim().into()).or_insert(path);
+                            }
+                        }
+                    }
+                }
+            }
+        }
+        let mut index = HashMap::new();
+        let mut file_count = 0;
+        visit(directory, 0, &mut file_count, &mut index);
+        index
+    }
+}
+
+/// Lines of the composed content coming from a single file.
+#[derive(Debug, Clone)]
+struct WgslSourceChunk {
+    composed_line: u32,
+    line_count: u32,
+    file_path: PathBuf,
+}
+
+/// Map positions in composed content to their original file.
+#[derive(Debug, Default, Clone)]
+pub struct WgslSourceMap {
+    chunks: Vec<WgslSourceChunk>,
+}
+
+impl WgslSourceMap {
+    /// Map a position in composed content to its original file & position.
+    pub fn map_position(&self, position: &ShaderPosition) -> Option<(&Path, ShaderPosition)> {
+        self.chunks
+            .iter()
+            .find(|chunk| {
+                position.line >= chunk.composed_line
+                    && position.line < chunk.composed_line + chunk.line_count
+            })
+            .map(|chunk| {
+                (
+                    chunk.file_path.as_path(),
+                    ShaderPosition::new(position.line - chunk.composed_line, position.pos),
+                )
+            })
+    }
+    /// Map a range in composed content to its original file range.
+    /// If range end is not in the same file, range is collapsed to its start.
+    pub fn map_range(&self, range: &ShaderRange) -> Option<ShaderFileRange> {
+        let (file_path, start) = self.map_position(&range.start)?;
+        let end = match self.map_position(&range.end) {
+            Some((end_file_path, end)) if end_file_path == file_path => end,
+            _ => start.clone(),
+        };
+        Some(ShaderFileRange::new(file_path.into(), start, end))
+    }
+    /// Map a diagnostic computed on composed content to its original file.
+    pub fn map_diagnostic(&self, diagnostic: ShaderDiagnostic) -> ShaderDiagnostic {
+        ShaderDiagnostic {
+            range: self
+                .map_range(&diagnostic.range.range)
+                .unwrap_or(diagnostic.range),
+            related_information: diagnostic
+                .related_information
+                .into_iter()
+                .map(|related| ShaderDiagnosticRelatedInformation {
+                    range: self
+                        .map_range(&related.range.range)
+                        .unwrap_or(related.range),
+                    message: related.message,
+                })
+                .collect(),
+            ..diagnostic
+        }
+    }
+}
+
+/// Result of the composition of a WGSL module with its imports.
+pub struct WgslComposedShader {
+    pub content: String,
+    pub source_map: WgslSourceMap,
+    pub diagnostics: ShaderDiagnosticList,
+}
+
+/// An `#import` declaration.
+#[derive(Debug, Clone)]
+struct WgslImport {
+    module_path: String,
+    quoted: bool,
+    alias: Option<String>,
+    items: Vec<(String, Option<String>)>, // Item & its alias
+    range: ShaderRange,
+}
+
+/// Conditional block state for `#ifdef` & co.
+struct WgslConditional {
+    parent_active: bool,
+    active: bool,
+    branch_taken: bool,
+}
+
+pub struct WgslComposer<'a> {
+    include_handler: IncludeHandler,
+    include_callback: &'a mut dyn FnMut(&Path) -> Option<String>,
+    shader_defs: HashMap<String, String>,
+    index_directories: Vec<PathBuf>,
+    import_path_index: &'a mut WgslImportPathIndex,
+    composed_modules: HashSet<PathBuf>,
+    content: String,
+    line_count: u32,
+    source_map: WgslSourceMap,
+    diagnostics: ShaderDiagnosticList,
+}
+
+impl<'a> WgslComposer<'a> {
+    pub fn new(
+        file_path: &Path,
+        params: &ShaderParams,
+        import_path_index: &'a mut WgslImportPathIndex,
+        include_callback: &'a mut dyn FnMut(&Path) -> Option<String>,
+    ) -> Self {
+        // Modules declaring an import path are looked up in these directories.
+        let mut index_directories: Vec<PathBuf> = params.context.includes.clone();
+        index_directories.extend(params.context.path_remapping.values().cloned());
+        if let Some(parent) = file_path.parent() {
+            index_directories.push(parent.into());
+        }
+        Self {
+            include_handler: IncludeHandler::main(
+                file_path,
+                params.context.includes.clone(),
+                params.context.path_remapping.clone(),
+            ),
+            include_callback,
+            shader_defs: params.context.defines.clone(),
+            index_directories,
+            import_path_index,
+            composed_modules: HashSet::new(),
+            content: String::new(),
+            line_count: 0,
+            source_map: WgslSourceMap::default(),
+            diagnostics: ShaderDiagnosticList::empty(),
+        }
+    }
+    /// Compose the main module with all its imports.
+    pub fn compose(mut self, file_path: &Path, shader_content: &str) -> WgslComposedShader {
+        self.composed_modules.insert(file_path.into());
+        self.compose_module(file_path, shader_content);
+        WgslComposedShader {
+            content: self.content,
+            source_map: self.source_map,
+            diagnostics: self.diagnostics,
+        }
+    }
+    fn compose_module(&mut self, file_path: &Path, shader_content: &str) {
+        let (mut content, imports) = self.preprocess(file_path, shader_content);
+        let mut imported_modules = Vec::new();
+        for import in &imports {
+            match self.resolve_import(import) {
+                Some((module_content, module_path, qualifiers, items)) => {
+                    for qualifier in qualifiers {
+                        content = strip_qualifier(&content, &qualifier);
+                    }
+                    for (item, alias) in items {
+                        content = rename_item(&content, &alias, &item);
+                    }
+                    if self.composed_modules.insert(module_path.clone()) {
+                        imported_modules.push((module_path, module_content));
+                    }
+                }
+                None => self.diagnostics.push(ShaderDiagnostic::new(
+                    ShaderDiagnosticSeverity::Error,
+                    format!("Failed to find import {}", import.module_path),
+                    import.range.clone_into_file(file_path.into()),
+                )),
+            }
+        }
+        // Store module content
+        let line_count = content.split('\n').count() as u32;
+        self.source_map.chunks.push(WgslSourceChunk {
+            composed_line: self.line_count,
+            line_count,
+            file_path: file_path.into(),
+        });
+        self.content.push_str(&content);
+        self.content.push('\n');
+        self.line_count += line_count;
+        // Then its imports.
+        for (module_path, module_content) in imported_modules {
+            self.include_handler.push_directory_stack(&module_path);
+            self.compose_module(&module_path, &module_content);
+        }
+    }
+    // Return module content, qualifiers to strip from importer & renamed items.
+    fn resolve_import(
+        &mut self,
+        import: &WgslImport,
+    ) -> Option<(String, PathBuf, Vec<String>, Vec<(String, String)>)> {
+        let get_renamed_items = |items: &Vec<(String, Option<String>)>| {
+            items
+                .iter()
+                .filter_map(|(item, alias)| alias.clone().map(|alias| (item.clone(), alias)))
+                .collect::<Vec<(String, String)>>()
+        };
+        if import.quoted {
+            let (content, path) = self
+                .include_handler
+                .search_in_includes(Path::new(&import.module_path), self.include_callback)?;
+            return Some((
+                content,
+                path,
+                import.alias.iter().cloned().collect(),
+                get_renamed_items(&import.items),
+            ));
+        }
+        if let Some((content, path)) = self.find_module(&import.module_path) {
+            // Module can be accessed through its full path, its name or its alias.
+            let mut qualifiers = vec![import.module_path.clone()];
+            match &import.alias {
+                Some(alias) => qualifiers.push(alias.clone()),
+                None => qualifiers.extend(
+                    import
+                        .module_path
+                        .rsplit("::")
+                        .next()
+                        .map(|name| name.to_string()),
+                ),
+            }
+            return Some((content, path, qualifiers, get_renamed_items(&import.items)));
+        }
+        // Might be an item imported directly (foo::bar::item)
+        if import.items.is_empty() {
+            if let Some((module_path, item)) = import.module_path.rsplit_once("::") {
+                if let Some((content, path)) = self.find_module(module_path) {
+                    let items = match &import.alias {
+                        Some(alias) => vec![(item.to_string(), alias.clone())],
+                        None => vec![],
+                    };
+                    return Some((content, path, vec![module_path.into()], items));
+                }
+            }
+        }
+        None
+    }
+    // Find a module from its import path.
+    fn find_module(&mut self, module_path: &str) -> Option<(String, PathBuf)> {
+        // First, consider import path as a relative path.
+        let relative_path = format!("{}.wgsl", module_path.replace("::", "/"));
+        for relative_path in [relative_path.clone(), format!("/{}", relative_path)] {
+            if let Some(module) = self
+                .include_handler
+                .search_in_includes(Path::new(&relative_path), self.include_callback)
+            {
+                return Some(module);
+            }
+        }
+        // Then look for a module declaring this import path.
+        let path = self
+            .import_path_index
+            .find(&self.index_directories, module_path)?;
+        (self.include_callback)(&path).map(|content| (content, path))
+    }
+    // Preprocess shader defs & directives, keeping line count intact.
+    fn preprocess(&mut self, file_path: &Path, shader_content: &str) -> (String, Vec<WgslImport>) {
+        let mut lines = Vec::new();
+        let mut imports = Vec::new();
+        let mut conditionals: Vec<WgslConditional> = Vec::new();
+        let mut pending_import: Option<(String, ShaderPosition)> = None;
+        for (line_index, line) in shader_content.split('\n').enumerate() {
+            let line_index = line_index as u32;
+            let active = conditionals.last().map(|c| c.active).unwrap_or(true);
+            // Multiline import, waiting for closing brace.
+            if let Some((mut import, start)) = pending_import.take() {
+                import.push_str(line);
+                if line.contains('}') {
+                    let range = ShaderRange::new(
+                        start,
+                        ShaderPosition::new(line_index, line.chars().count() as u32),
+                    );
+                    imports.extend(parse_imports(&import, &range));
+                } else {
+                    pending_import = Some((import, start));
+                }
+                lines.push(String::new());
+                continue;
+            }
+            let trimmed = line.trim();
+            if !trimmed.starts_with('#') || trimmed.starts_with("#{") {
+                lines.push(if active {
+                    SHADER_DEF_REGEX
+                        .replace_all(line, |captures: &regex::Captures| {
+                            match self.shader_defs.get(&captures[1]) {
+                                Some(value) => value.clone(),
+                                None => captures[0].to_string(),
+                            }
+                        })
+                        .to_string()
+                } else {
+                    String::new()
+                });
+                continue;
+            }
+            let range = ShaderRange::new(
+                ShaderPosition::new(line_index, (line.len() - line.trim_start().len()) as u32),
+                ShaderPosition::new(line_index, line.trim_end().chars().count() as u32),
+            );
+            let directive_length = trimmed[1..]
+                .find(|c: char| !c.is_alphanumeric() && c != '_')
+                .unwrap_or(trimmed.len() - 1);
+            let directive = &trimmed[1..1 + directive_length];
+            let argument = trimmed[1 + directive_length..].trim();
+            match directive {
+                "ifdef" | "ifndef" | "if" => {
+                    let condition = if active {
+                        self.evaluate_condition(directive, argument, file_path, &range)
+                    } else {
+                        false
+                    };
+                    conditionals.push(WgslConditional {
+                        parent_active: active,
+                        active: active && condition,
+                        branch_taken: condition,
+                    });
+                }
+                "else" => match conditionals.last() {
+                    Some(conditional) => {
+                        let parent_active = conditional.parent_active;
+                        let branch_taken = conditional.branch_taken;
+                        // Handle chained else ifdef / else if.
+                        let condition = if branch_taken || !parent_active {
+                            false
+                        } else if argument.is_empty() {
+                            true
+                        } else {
+                            let (directive, argument) =
+                                argument.split_once(' ').unwrap_or((argument, ""));
+                            self.evaluate_condition(directive, argument.trim(), file_path, &range)
+                        };
+                        let conditional = conditionals.last_mut().unwrap();
+                        conditional.active = parent_active && condition;
+                        conditional.branch_taken = branch_taken || condition;
+                    }
+                    None => self.push_error("#else without #if", file_path, &range),
+                },
+                "endif" => {
+                    if conditionals.pop().is_none() {
+                        self.push_error("#endif without #if", file_path, &range);
+                    }
+                }
+                "define_import_path" => {}
+                "import" if active => {
+                    if argument.contains('{') && !argument.contains('}') {
+                        pending_import = Some((argument.to_string(), range.start.clone()));
+                    } else {
+                        imports.extend(parse_imports(argument, &range));
+                    }
+                }
+                "define" if active => {
+                    let (name, value) = argument.split_once(' ').unwrap_or((argument, ""));
+                    self.shader_defs
+                        .insert(name.trim().into(), value.trim().into());
+                }
+                _ if active => self.diagnostics.push(ShaderDiagnostic::new(
+                    ShaderDiagnosticSeverity::Warning,
+                    format!("Unknown preprocessor directive #{}", directive),
+                    range.clone_into_file(file_path.into()),
+                )),
+                _ => {}
+            }
+            lines.push(String::new());
+        }
+        if !conditionals.is_empty() {
+            let last_line = shader_content.split('\n').count() as u32 - 1;
+            self.push_error(
+                "Missing #endif",
+                file_path,
+                &ShaderRange::new(
+                    ShaderPosition::new(last_line, 0),
+                    ShaderPosition::new(last_line, 0),
+                ),
+            );
+        }
+        (lines.join("\n"), imports)
+    }
+    fn push_error(&mut self, error: &str, file_path: &Path, range: &ShaderRange) {
+        self.diagnostics.push(ShaderDiagnostic::new(
+            ShaderDiagnosticSeverity::Error,
+            error.into(),
+            range.clone_into_file(file_path.into()),
+        ));
+    }
+    fn evaluate_condition(
+        &mut self,
+        directive: &str,
+        argument: &str,
+        file_path: &Path,
+        range: &ShaderRange,
+    ) -> bool {
+        match directive {
+            "ifdef" => self.shader_defs.contains_key(argument),
+            "ifndef" => !self.shader_defs.contains_key(argument),
+            "if" => {
+                let (name, operator, expected) = match CONDITION_REGEX.captures(argument) {
+                    Some(captures) => (
+                        captures[1].to_string(),
+                        captures[2].to_string(),
+                        captures[3].to_string(),
+                    ),
+                    None => (argument.to_string(), "!=".into(), "false".into()),
+                };
+                let value = match self.shader_defs.get(&name) {
+                    Some(value) if value.is_empty() => "true".to_string(),
+                    Some(value) => value.clone(),
+                    None => {
+                        self.diagnostics.push(ShaderDiagnostic::new(
+                            ShaderDiagnosticSeverity::Warning,
+                            format!("Shader def {} is not defined", name),
+                            range.clone_into_file(file_path.into()),
+                        ));
+                        return false;
+                    }
+                };
+                match (value.parse::<i64>(), expected.parse::<i64>()) {
+                    (Ok(value), Ok(expected)) => match operator.as_str() {
+                        "==" => value == expected,
+                        "!=" => value != expected,
+                        "<=" => value <= expected,
+                        ">=" => value >= expected,
+                        "<" => value < expected,
+                        ">" => value > expected,
+                        _ => unreachable!(),
+                    },
+                    _ => match operator.as_str() {
+                        "==" => value == expected,
+                        "!=" => value != expected,
+                        _ => {
+                            self.push_error(
+                                "Only == and != are supported for non integer shader defs",
+                                file_path,
+                                range,
+                            );
+                            false
+                        }
+                    },
+                }
+            }
+            _ => {
+                self.push_error(
+                    format!("Unknown conditional directive #{}", directive).as_str(),
+                    file_path,
+                    range,
+                );
+                false
+            }
+        }
+    }
+}
+
+// Parse the argument of an #import directive, which can hold multiple imports.
+fn parse_imports(argument: &str, range: &ShaderRange) -> Vec<WgslImport> {
+    // Split on top level commas.
+    let mut imports = Vec::new();
+    let mut depth = 0;
+    let mut start = 0;
+    for (index, c) in argument.char_indices() {
+        match c {
+            '{' => depth += 1,
+            '}' => depth -= 1,
+            ',' if depth == 0 => {
+                imports.push(&argument[start..index]);
+                start = index + 1;
+            }
+            _ => {}
+        }
+    }
+    imports.push(&argument[start..]);
+    imports
+        .into_iter()
+        .map(|import| import.trim())
+        .filter(|import| !import.is_empty())
+        .map(|import| {
+            let split_alias = |value: &str| match value.split_once(" as ") {
+                Some((value, alias)) => (value.trim().to_string(), Some(alias.trim().to_string())),
+                None => (value.trim().to_string(), None),
+            };
+            if import.starts_with('"') {
+                let end = import[1..].find('"').map(|e| e + 1).unwrap_or(import.len());
+                WgslImport {
+                    module_path: import[1..end].into(),
+                    quoted: true,
+                    alias: split_alias(&import[end..]).1,
+                    items: vec![],
+                    range: range.clone(),
+                }
+            } else if let Some((module_path, items)) = import.split_once("::{") {
+                WgslImport {
+                    module_path: module_path.trim().into(),
+                    quoted: false,
+                    alias: None,
+                    items: items
+                        .trim_end_matches('}')
+                        .split(',')
+                        .map(|item| item.trim())
+                        .filter(|item| !item.is_empty())
+                        .map(split_alias)
+                        .collect(),
+                    range: range.clone(),
+                }
+            } else {
+                let (module_path, alias) = split_alias(import);
+                WgslImport {
+                    module_path,
+                    quoted: false,
+                    alias,
+                    items: vec![],
+                    range: range.clone(),
+                }
+            }
+        })
+        .collect()
+}
+
+// Replace qualifier:: by whitespaces to keep columns intact.
+fn strip_qualifier(content: &str, qualifier: &str) -> String {
+    let prefix = format!("{}::", qualifier);
+    QUALIFIERS_REGEX
+        .replace_all(content, |captures: &regex::Captures| {
+            match captures[2].strip_prefix(prefix.as_str()) {
+                Some(qualifiers) => {
+                    format!("{}{}{}", &captures[1], " ".repeat(prefix.len()), qualifiers)
+                }
+                None => captures[0].to_string(),
+            }
+        })
+        .to_string()
+}
+
+// Replace an item alias by the item label, padding it to keep columns intact if possible.
+fn rename_item(content: &str, alias: &str, item: &str) -> String {
+    IDENTIFIER_REGEX
+        .replace_all(content, |captures: &regex::Captures| {
+            if &captures[2] == alias {
+                let padding = captures[2].len().saturating_sub(item.len());
+                format!("{}{}{}", &captures[1], " ".repeat(padding), item)
+            } else {
+                captures[0].to_string()
+            }
+        })
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use crate::{
+        position::{ShaderPosition, ShaderRange},
+        shader::{ShaderContextParams, ShaderParams},
+        validator::validator::default_include_callback,
+    };
+
+    use super::{WgslComposer, WgslImportPathIndex};
+
+    #[test]
+    fn test_compose_imports() {
+        let file_path = Path::new("./test/wgsl/imports/main.wgsl");
+        let shader_content = std::fs::read_to_string(file_path).unwrap();
+        let params = ShaderParams {
+            context: ShaderContextParams {
+                defines: [("USE_FOG".to_string(), "".to_string())].into(),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let mut import_path_index = WgslImportPathIndex::default();
+        let mut include_callback = default_include_callback;
+        let composed = WgslComposer::new(
+            file_path,
+            &params,
+            &mut import_path_index,
+            &mut include_callback,
+        )
+        .compose(file_path, &shader_content);
+        assert!(
+            composed.diagnostics.is_empty(),
+            "{:#?}",
+            composed.diagnostics
+        );
+        assert!(naga::front::wgsl::parse_str(&composed.content).is_ok());
+        // Main file lines are mapped to themselves.
+        let (path, position) = composed
+            .source_map
+            .map_position(&ShaderPosition::new(3, 4))
+            .unwrap();
+        assert!(path == file_path);
+        assert!(position == ShaderPosition::new(3, 4));
+        // Imported lines are mapped to their file.
+        let main_line_count = shader_content.split('\n').count() as u32;
+        let range = composed
+            .source_map
+            .map_range(&ShaderRange::new(
+                ShaderPosition::new(main_line_count + 1, 0),
+                ShaderPosition::new(main_line_count + 1, 2),
+            ))
+            .unwrap();
+        assert!(range.file_path.ends_with("lighting.wgsl"));
+        assert!(range.range.start.line == 1);
+    }
+}
@@ -0,0 +1,6 @@
+// Helper include file for GLSL ES 1.00.
+precision mediump float;
+
+vec4 sampleColor(sampler2D colorTexture, vec2 uv) {
+    return texture2D(colorTexture, uv);
+}
@@ -0,0 +1,5 @@
+#version 460
+#extension GL_EXT_mesh_shader : require
+
+void main() {
+}
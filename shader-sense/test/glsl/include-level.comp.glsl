@@ -14,7 +14,7 @@ uint fibonacci(uint nthNumber) {
     return currentNumber;
 }
 
-void compute() {
+void main() {
     Test test;
     float level = level0;
     //vec4 frags = gl_FragCoord; // Error
@@ -0,0 +1,9 @@
+#version 450
+
+float compute(float value);
+
+layout(location = 0) out vec4 color;
+
+void main() {
+    color = vec4(compute(1.0));
+}
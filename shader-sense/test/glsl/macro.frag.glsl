@@ -1,5 +1,5 @@
 #version 450
 
-void compute() {
+void main() {
     float level = CUSTOM_MACRO;
 }
\ No newline at end of file
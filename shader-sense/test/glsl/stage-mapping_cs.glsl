@@ -0,0 +1,9 @@
+#version 450
+
+layout(local_size_x = 8, local_size_y = 8) in;
+
+layout(binding = 0, r32f) uniform image2D outputImage;
+
+void csMain() {
+    imageStore(outputImage, ivec2(gl_GlobalInvocationID.xy), vec4(1.0));
+}
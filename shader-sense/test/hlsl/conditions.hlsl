@@ -0,0 +1,39 @@
+#define BASE 2
+#define FEATURE_LEVEL (BASE + 3)
+#define ALIAS FEATURE_LEVEL
+#define EMPTY
+
+void main() {
+// Expression defines
+#if FEATURE_LEVEL >= (BASE + 2)
+    float a = 0;
+#endif
+// Alias & defined without parenthesis
+#if ALIAS * 2 == 10 && defined ALIAS
+    float b = 0;
+#endif
+// Ternary
+#if defined(EMPTY) ? 0 : 1
+    float c = 0;
+#endif
+// Signed overflow wraps
+#if 0x7FFFFFFFFFFFFFFF + 1 < 0
+    float d = 0;
+#endif
+// Unsigned conversion
+#if -1 < 0u
+    float e = 0;
+#endif
+// Division by zero
+#if 1 / 0
+    float f = 0;
+#endif
+// Undefined macros are zero
+#if UNDEFINED_MACRO || (1 << 2) == 4
+    float g = 0;
+#endif
+// Short circuit does not evaluate division
+#if 0 && (1 / 0)
+    float h = 0;
+#endif
+}
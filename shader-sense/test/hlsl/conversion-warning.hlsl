@@ -0,0 +1,7 @@
+
+float4 main() : SV_Target
+{
+    float4 value = float4(1.0, 2.0, 3.0, 4.0);
+    float2 truncated = value; // Implicit truncation warning
+    return float4(truncated, 0.0, 1.0);
+}
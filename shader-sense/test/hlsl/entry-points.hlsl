@@ -0,0 +1,11 @@
+float4 helper() {
+    return undeclaredVariable;
+}
+
+float4 VSMain(uint vertexID : SV_VertexID) : SV_Position {
+    return helper();
+}
+
+float4 PSMain() : SV_Target {
+    return helper();
+}
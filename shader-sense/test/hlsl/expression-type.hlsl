@@ -0,0 +1,35 @@
+struct Light {
+    float3 color;
+    float intensity;
+    float3 getColor() {
+        return color * intensity;
+    }
+};
+
+struct Material {
+    Light light;
+    Light getLight() {
+        return light;
+    }
+};
+
+StructuredBuffer<Light> lights;
+Light lightArray[4];
+Texture2D<float4> tex;
+SamplerState samplerState;
+
+Light GetLight(uint i) {
+    return lights[i];
+}
+
+float4 main(float2 uv : TEXCOORD0) : SV_Target {
+    Material material;
+    float3 a = GetLight(0).color;
+    float3 b = lights[0].color;
+    float3 c = lightArray[1].color;
+    float3 d = tex.Sample(samplerState, uv).rgb;
+    float e = material.getLight().intensity;
+    float3 f = material.light.getColor().zyx;
+    float g = lights.Load(0).intensity;
+    return float4(a + b + c + d + f, e + g);
+}
@@ -0,0 +1,6 @@
+
+float4 main() : SV_Target
+{
+    float value = 1.0 // Missing semicolon
+    return float4(value, value, value, 1.0);
+}
@@ -0,0 +1,30 @@
+#define ADD(a, b) ((a) + (b))
+#define TWICE(x) ADD(x, x)
+#define CONCAT(a, b) a ## b
+#define VALUE_ONE 1
+#define COUNT(...) ADD(__VA_ARGS__)
+#define FUNCTION_LIKE()
+
+void main() {
+// Arguments are substituted
+#if ADD(1, 2) == 3
+    float a = 0;
+#endif
+// Nested invocations
+#if TWICE(ADD(1, 1)) == 4
+    float b = 0;
+#endif
+// Token pasting
+#if CONCAT(VALUE_, ONE)
+    float c = 0;
+#endif
+// Variadic arguments
+#if COUNT(2, 3) == 5
+    float d = 0;
+#endif
+// Function-like macro without arguments is not expanded.
+#if ADD
+    float e = 0;
+#endif
+    float f = TWICE(ADD(1, 2));
+}
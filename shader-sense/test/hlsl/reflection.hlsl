@@ -0,0 +1,22 @@
+struct Light
+{
+    float3 position;
+    float intensity;
+};
+
+cbuffer Globals : register(b0, space1)
+{
+    float4x4 viewProjection;
+};
+
+Texture2D<float4> albedo : register(t0);
+StructuredBuffer<Light> lights : register(t2);
+SamplerState linearSampler : register(s3);
+RWTexture2D<float4> output : register(u1, space2);
+
+[numthreads(8, 8, 1)]
+void CSMain(uint3 id : SV_DispatchThreadID)
+{
+    float4 color = albedo.SampleLevel(linearSampler, float2(id.xy) / 64.0, 0);
+    output[id.xy] = mul(viewProjection, color * lights[0].intensity);
+}
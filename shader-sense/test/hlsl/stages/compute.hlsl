@@ -5,4 +5,16 @@ void CSMain(uint3 dispatchThreadID : SV_DispatchThreadID)
 {
     uint index = dispatchThreadID.x + dispatchThreadID.y * 8;
     outputBuffer[index] = index;
+}
+
+#if 0
+[numthreads(8, 8, 1)]
+#endif
+void DisabledCSMain(uint3 id)
+{
+}
+
+/* [numthreads(8, 8, 1)] */
+void CommentedCSMain(uint3 id)
+{
 }
\ No newline at end of file
@@ -0,0 +1,21 @@
+struct AmplificationPayload
+{
+    uint meshletID;
+};
+
+// Compute shader dispatched alongside the DispatchMesh call of ASMain.
+RWStructuredBuffer<uint> counters;
+
+[numthreads(8, 1, 1)]
+void ASMain(uint groupID : SV_GroupID)
+{
+    AmplificationPayload payload;
+    payload.meshletID = groupID;
+    DispatchMesh(1, 1, 1, payload);
+}
+
+[numthreads(8, 1, 1)]
+void CSMain(uint threadID : SV_DispatchThreadID)
+{
+    counters[threadID] = 0;
+}
@@ -0,0 +1,7 @@
+#define_import_path common::lighting
+
+const LIGHT_DIRECTION: vec3<f32> = vec3<f32>(0.0, 1.0, 0.0);
+
+fn diffuse(normal: vec3<f32>, direction: vec3<f32>) -> vec3<f32> {
+    return vec3<f32>(max(dot(normal, direction), 0.0));
+}
@@ -0,0 +1,16 @@
+#import common::lighting
+
+fn shade(normal: vec3<f32>) -> vec3<f32> {
+    var color = lighting::diffuse(normal, lighting::LIGHT_DIRECTION);
+#ifdef USE_FOG
+    color = color * 0.5;
+#else
+    color = color * fog_factor; // ERROR without USE_FOG
+#endif
+    return color;
+}
+
+@fragment
+fn main(@location(0) normal: vec3<f32>) -> @location(0) vec4<f32> {
+    return vec4<f32>(shade(normal), 1.0);
+}
@@ -0,0 +1,6 @@
+var<push_constant> color: vec4<f32>;
+
+@fragment
+fn main() -> @location(0) vec4<f32> {
+    return color;
+}
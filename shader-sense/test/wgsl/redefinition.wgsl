@@ -0,0 +1,7 @@
+fn foo() -> f32 {
+    return 1.0;
+}
+
+fn foo() -> f32 {
+    return 2.0;
+}
@@ -0,0 +1,22 @@
+struct Material {
+    albedo: vec3<f32>,
+    roughness: f32,
+}
+
+struct Surface {
+    materials: array<Material, 4>,
+    normal: vec3<f32>,
+}
+
+fn get_surface() -> Surface {
+    var surface: Surface;
+    surface.normal = vec3<f32>(0.0, 1.0, 0.0);
+    return surface;
+}
+
+@fragment
+fn main() -> @location(0) vec4<f32> {
+    let surface = get_surface();
+    let roughness = surface.materials[0].roughness;
+    return vec4<f32>(get_surface().normal, roughness);
+}